@@ -0,0 +1,336 @@
+#![allow(dead_code)]
+use std::collections::{BTreeMap, HashMap};
+
+use crate::css::StyledNode;
+use crate::dom::{collapse_whitespace, Element, Node, NodeType};
+use crate::layout::Display;
+// ==============================
+// https://www.w3.org/TR/html-aam-1.0/
+// https://www.w3.org/TR/accname-1.2/
+//
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct AccessibilityNode {
+  pub role: String,
+  pub name: Option<String>,
+  // states and properties, e.g. `level`, `checked` or `aria-*` attributes without the prefix.
+  pub properties: BTreeMap<String, String>,
+  pub children: Vec<AccessibilityNode>,
+}
+
+// elements that are never rendered, regardless of the author stylesheet.
+const NON_RENDERED_ELEMENTS: [&str; 9] = [
+  "head", "title", "meta", "link", "base", "style", "script", "template", "noscript",
+];
+
+// roles whose name can be computed from their content.
+const NAME_FROM_CONTENT_ROLES: [&str; 14] = [
+  "button",
+  "cell",
+  "checkbox",
+  "columnheader",
+  "heading",
+  "link",
+  "menuitem",
+  "option",
+  "radio",
+  "row",
+  "rowheader",
+  "switch",
+  "tab",
+  "tooltip",
+];
+
+// attributes already reflected in the name or visibility of a node.
+const CONSUMED_ARIA_ATTRIBUTES: [&str; 3] = ["aria-label", "aria-labelledby", "aria-hidden"];
+
+/// Build the accessibility tree of a styled document. The root is always a `document` node named
+/// after the `<title>` element.
+pub fn accessibility_tree(root: &StyledNode) -> AccessibilityNode {
+  let context = NameContext::new(root.node);
+  let title = root
+    .node
+    .descendants()
+    .into_iter()
+    .find(|node| node.is_element("title"));
+  let name = title
+    .map(|node| normalize_name(&node.text_content()))
+    .filter(|name| !name.is_empty());
+  let children = build_nodes(root, &context);
+  AccessibilityNode { role: "document".to_string(), name, properties: BTreeMap::new(), children }
+}
+
+// lookups over the whole document that name computation needs.
+struct NameContext<'a> {
+  elements_by_id: HashMap<&'a str, &'a Node>,
+  labels: Vec<&'a Node>,
+}
+
+impl<'a> NameContext<'a> {
+  fn new(root: &'a Node) -> Self {
+    let mut elements_by_id = HashMap::new();
+    let mut labels = Vec::new();
+    for node in std::iter::once(root).chain(root.descendants()) {
+      if let Some(id) = node.attribute("id") {
+        elements_by_id.entry(id).or_insert(node);
+      }
+      if node.is_element("label") {
+        labels.push(node);
+      }
+    }
+    NameContext { elements_by_id, labels }
+  }
+
+  // `<label for=id>` elements pointing at `element`, and the `<label>` wrapping it if any.
+  fn labels_for(&self, element: &Element, node: &Node) -> Vec<&'a Node> {
+    let id = element.attribute("id");
+    let labels = self.labels.iter().filter(|label| match label.attribute("for") {
+      Some(target) => Some(target) == id,
+      None => label
+        .descendants()
+        .into_iter()
+        .any(|descendant| std::ptr::eq(descendant, node)),
+    });
+    labels.copied().collect()
+  }
+}
+
+// Accessible nodes for `styled` and its subtree. Nodes without a role are left out of the tree and
+// their children take their place.
+fn build_nodes(styled: &StyledNode, context: &NameContext) -> Vec<AccessibilityNode> {
+  let element = match &styled.node.node_type {
+    NodeType::Text(text) => {
+      let text = normalize_name(text);
+      if text.is_empty() {
+        return Vec::new();
+      }
+      return vec![AccessibilityNode {
+        role: "text".to_string(),
+        name: Some(text),
+        properties: BTreeMap::new(),
+        children: Vec::new(),
+      }];
+    }
+    NodeType::Element(element) => element,
+  };
+  if is_hidden(styled, element) {
+    return Vec::new();
+  }
+
+  let children: Vec<AccessibilityNode> = styled
+    .children
+    .iter()
+    .flat_map(|child| build_nodes(child, context))
+    .collect();
+  let Some(role) = role(element) else {
+    return children;
+  };
+  if role == "none" || role == "presentation" {
+    return children;
+  }
+
+  let name = accessible_name(styled.node, element, &role, context);
+  let properties = properties(element, &role);
+  // an image's fallback content isn't exposed.
+  let children = if role == "img" { Vec::new() } else { children };
+  vec![AccessibilityNode { role, name, properties, children }]
+}
+
+fn is_hidden(styled: &StyledNode, element: &Element) -> bool {
+  if NON_RENDERED_ELEMENTS.contains(&element.tag_name.as_str()) {
+    return true;
+  }
+  if element.attribute("hidden").is_some() || element.attribute("aria-hidden") == Some("true") {
+    return true;
+  }
+  styled.display() == Display::None
+}
+
+/// The explicit `role` attribute (its first token), or the implicit role of the element.
+pub fn role(element: &Element) -> Option<String> {
+  if let Some(role) = element
+    .attribute("role")
+    .and_then(|role| role.split_ascii_whitespace().next())
+  {
+    return Some(role.to_ascii_lowercase());
+  }
+  implicit_role(element).map(|role| role.to_string())
+}
+
+// https://www.w3.org/TR/html-aam-1.0/#html-element-role-mappings
+pub fn implicit_role(element: &Element) -> Option<&'static str> {
+  let role = match &*element.tag_name.to_ascii_lowercase() {
+    "a" | "area" if element.attribute("href").is_some() => "link",
+    "article" => "article",
+    "aside" => "complementary",
+    "button" => "button",
+    "dialog" => "dialog",
+    "dd" => "definition",
+    "dt" => "term",
+    "fieldset" => "group",
+    "figure" => "figure",
+    "footer" => "contentinfo",
+    "form" => "form",
+    "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => "heading",
+    "header" => "banner",
+    "hr" => "separator",
+    "img" if element.attribute("alt") == Some("") => "presentation",
+    "img" => "img",
+    "input" => return input_role(element),
+    "li" => "listitem",
+    "main" => "main",
+    "nav" => "navigation",
+    "ol" | "ul" | "menu" => "list",
+    "option" => "option",
+    "p" => "paragraph",
+    "progress" => "progressbar",
+    // a section is only a landmark when it's named.
+    "section"
+      if ["aria-label", "aria-labelledby", "title"]
+        .iter()
+        .any(|name| element.attribute(name).is_some_and(|value| !value.trim().is_empty())) =>
+    {
+      "region"
+    }
+    "select" if element.attribute("multiple").is_some() => "listbox",
+    "select" => "combobox",
+    "table" => "table",
+    "tbody" | "thead" | "tfoot" => "rowgroup",
+    "td" => "cell",
+    "textarea" => "textbox",
+    "th" => "columnheader",
+    "tr" => "row",
+    _ => return None,
+  };
+  Some(role)
+}
+
+fn input_role(element: &Element) -> Option<&'static str> {
+  let input_type = element.attribute("type").unwrap_or("text").to_ascii_lowercase();
+  let role = match &*input_type {
+    "button" | "submit" | "reset" | "image" => "button",
+    "checkbox" => "checkbox",
+    "radio" => "radio",
+    "range" => "slider",
+    "number" => "spinbutton",
+    "search" => "searchbox",
+    "hidden" => return None,
+    _ => "textbox",
+  };
+  Some(role)
+}
+
+// https://www.w3.org/TR/accname-1.2/#computation-steps
+fn accessible_name(node: &Node, element: &Element, role: &str, context: &NameContext) -> Option<String> {
+  if let Some(ids) = element.attribute("aria-labelledby") {
+    let referenced = ids
+      .split_ascii_whitespace()
+      .filter_map(|id| context.elements_by_id.get(id));
+    let name = referenced
+      .map(|node| text_alternative(node))
+      .collect::<Vec<_>>()
+      .join(" ");
+    if let Some(name) = non_empty(&name) {
+      return Some(name);
+    }
+  }
+  if let Some(name) = element.attribute("aria-label").and_then(non_empty) {
+    return Some(name);
+  }
+  if let Some(name) = native_name(node, element, context) {
+    return Some(name);
+  }
+  if NAME_FROM_CONTENT_ROLES.contains(&role) {
+    if let Some(name) = non_empty(&text_alternative(node)) {
+      return Some(name);
+    }
+  }
+  element.attribute("title").and_then(non_empty)
+}
+
+// Names coming from the host language: `alt`, labels and button values.
+fn native_name(node: &Node, element: &Element, context: &NameContext) -> Option<String> {
+  let tag_name = element.tag_name.to_ascii_lowercase();
+  let input_type = element.attribute("type").unwrap_or("text").to_ascii_lowercase();
+  if tag_name == "img" || tag_name == "area" || (tag_name == "input" && input_type == "image") {
+    return element.attribute("alt").and_then(non_empty);
+  }
+  if tag_name == "input" && matches!(&*input_type, "button" | "submit" | "reset") {
+    let default = match &*input_type {
+      "submit" => "Submit",
+      "reset" => "Reset",
+      _ => "",
+    };
+    return non_empty(element.attribute("value").unwrap_or(default));
+  }
+  if matches!(
+    &*tag_name,
+    "input" | "select" | "textarea" | "button" | "meter" | "output" | "progress"
+  ) {
+    let labels = context.labels_for(element, node);
+    let name = labels
+      .iter()
+      .map(|label| text_alternative(label))
+      .collect::<Vec<_>>()
+      .join(" ");
+    return non_empty(&name);
+  }
+  None
+}
+
+// Text of a subtree, using the text alternative of embedded images.
+fn text_alternative(node: &Node) -> String {
+  match &node.node_type {
+    NodeType::Text(text) => text.clone(),
+    NodeType::Element(element) => {
+      if let Some(label) = element.attribute("aria-label").and_then(non_empty) {
+        return label;
+      }
+      if element.tag_name.eq_ignore_ascii_case("img") {
+        return element.attribute("alt").unwrap_or_default().to_string();
+      }
+      node.children.iter().map(text_alternative).collect::<Vec<_>>().join("")
+    }
+  }
+}
+
+fn properties(element: &Element, role: &str) -> BTreeMap<String, String> {
+  let mut properties = BTreeMap::new();
+  if role == "heading" {
+    let level = element
+      .tag_name
+      .strip_prefix(['h', 'H'])
+      .filter(|level| level.parse::<u8>().is_ok());
+    properties.insert("level".to_string(), level.unwrap_or("2").to_string());
+  }
+  if role == "checkbox" || role == "radio" {
+    // the current checkedness, which starts from the `checked` attribute.
+    properties.insert("checked".to_string(), element.state.checked.to_string());
+  }
+  if element.attribute("disabled").is_some() {
+    properties.insert("disabled".to_string(), "true".to_string());
+  }
+  if element.attribute("required").is_some() {
+    properties.insert("required".to_string(), "true".to_string());
+  }
+  for (attribute, value) in element.atributes.iter() {
+    if !attribute.starts_with("aria-") || CONSUMED_ARIA_ATTRIBUTES.contains(&attribute.as_str()) {
+      continue;
+    }
+    properties.insert(attribute.trim_start_matches("aria-").to_string(), value.clone());
+  }
+  properties
+}
+
+// strip and collapse whitespace, as names are exposed to assistive technology.
+fn normalize_name(text: &str) -> String {
+  collapse_whitespace(text)
+}
+
+fn non_empty(text: &str) -> Option<String> {
+  let name = normalize_name(text);
+  if name.is_empty() {
+    return None;
+  }
+  Some(name)
+}
@@ -0,0 +1,387 @@
+#![allow(dead_code)]
+use crate::css::{ColorValue, ComponentValue, Token};
+// ==============================
+// https://www.w3.org/TR/css-color-4/
+// Every color is converted to `ColorValue::RBGColorValue`, except `currentcolor`, which depends on
+// the element's `color`.
+//
+
+// https://www.w3.org/TR/css-color-4/#named-colors
+const NAMED_COLORS: [(&str, u32); 148] = [
+  ("aliceblue", 0xf0f8ff),
+  ("antiquewhite", 0xfaebd7),
+  ("aqua", 0x00ffff),
+  ("aquamarine", 0x7fffd4),
+  ("azure", 0xf0ffff),
+  ("beige", 0xf5f5dc),
+  ("bisque", 0xffe4c4),
+  ("black", 0x000000),
+  ("blanchedalmond", 0xffebcd),
+  ("blue", 0x0000ff),
+  ("blueviolet", 0x8a2be2),
+  ("brown", 0xa52a2a),
+  ("burlywood", 0xdeb887),
+  ("cadetblue", 0x5f9ea0),
+  ("chartreuse", 0x7fff00),
+  ("chocolate", 0xd2691e),
+  ("coral", 0xff7f50),
+  ("cornflowerblue", 0x6495ed),
+  ("cornsilk", 0xfff8dc),
+  ("crimson", 0xdc143c),
+  ("cyan", 0x00ffff),
+  ("darkblue", 0x00008b),
+  ("darkcyan", 0x008b8b),
+  ("darkgoldenrod", 0xb8860b),
+  ("darkgray", 0xa9a9a9),
+  ("darkgreen", 0x006400),
+  ("darkgrey", 0xa9a9a9),
+  ("darkkhaki", 0xbdb76b),
+  ("darkmagenta", 0x8b008b),
+  ("darkolivegreen", 0x556b2f),
+  ("darkorange", 0xff8c00),
+  ("darkorchid", 0x9932cc),
+  ("darkred", 0x8b0000),
+  ("darksalmon", 0xe9967a),
+  ("darkseagreen", 0x8fbc8f),
+  ("darkslateblue", 0x483d8b),
+  ("darkslategray", 0x2f4f4f),
+  ("darkslategrey", 0x2f4f4f),
+  ("darkturquoise", 0x00ced1),
+  ("darkviolet", 0x9400d3),
+  ("deeppink", 0xff1493),
+  ("deepskyblue", 0x00bfff),
+  ("dimgray", 0x696969),
+  ("dimgrey", 0x696969),
+  ("dodgerblue", 0x1e90ff),
+  ("firebrick", 0xb22222),
+  ("floralwhite", 0xfffaf0),
+  ("forestgreen", 0x228b22),
+  ("fuchsia", 0xff00ff),
+  ("gainsboro", 0xdcdcdc),
+  ("ghostwhite", 0xf8f8ff),
+  ("gold", 0xffd700),
+  ("goldenrod", 0xdaa520),
+  ("gray", 0x808080),
+  ("green", 0x008000),
+  ("greenyellow", 0xadff2f),
+  ("grey", 0x808080),
+  ("honeydew", 0xf0fff0),
+  ("hotpink", 0xff69b4),
+  ("indianred", 0xcd5c5c),
+  ("indigo", 0x4b0082),
+  ("ivory", 0xfffff0),
+  ("khaki", 0xf0e68c),
+  ("lavender", 0xe6e6fa),
+  ("lavenderblush", 0xfff0f5),
+  ("lawngreen", 0x7cfc00),
+  ("lemonchiffon", 0xfffacd),
+  ("lightblue", 0xadd8e6),
+  ("lightcoral", 0xf08080),
+  ("lightcyan", 0xe0ffff),
+  ("lightgoldenrodyellow", 0xfafad2),
+  ("lightgray", 0xd3d3d3),
+  ("lightgreen", 0x90ee90),
+  ("lightgrey", 0xd3d3d3),
+  ("lightpink", 0xffb6c1),
+  ("lightsalmon", 0xffa07a),
+  ("lightseagreen", 0x20b2aa),
+  ("lightskyblue", 0x87cefa),
+  ("lightslategray", 0x778899),
+  ("lightslategrey", 0x778899),
+  ("lightsteelblue", 0xb0c4de),
+  ("lightyellow", 0xffffe0),
+  ("lime", 0x00ff00),
+  ("limegreen", 0x32cd32),
+  ("linen", 0xfaf0e6),
+  ("magenta", 0xff00ff),
+  ("maroon", 0x800000),
+  ("mediumaquamarine", 0x66cdaa),
+  ("mediumblue", 0x0000cd),
+  ("mediumorchid", 0xba55d3),
+  ("mediumpurple", 0x9370db),
+  ("mediumseagreen", 0x3cb371),
+  ("mediumslateblue", 0x7b68ee),
+  ("mediumspringgreen", 0x00fa9a),
+  ("mediumturquoise", 0x48d1cc),
+  ("mediumvioletred", 0xc71585),
+  ("midnightblue", 0x191970),
+  ("mintcream", 0xf5fffa),
+  ("mistyrose", 0xffe4e1),
+  ("moccasin", 0xffe4b5),
+  ("navajowhite", 0xffdead),
+  ("navy", 0x000080),
+  ("oldlace", 0xfdf5e6),
+  ("olive", 0x808000),
+  ("olivedrab", 0x6b8e23),
+  ("orange", 0xffa500),
+  ("orangered", 0xff4500),
+  ("orchid", 0xda70d6),
+  ("palegoldenrod", 0xeee8aa),
+  ("palegreen", 0x98fb98),
+  ("paleturquoise", 0xafeeee),
+  ("palevioletred", 0xdb7093),
+  ("papayawhip", 0xffefd5),
+  ("peachpuff", 0xffdab9),
+  ("peru", 0xcd853f),
+  ("pink", 0xffc0cb),
+  ("plum", 0xdda0dd),
+  ("powderblue", 0xb0e0e6),
+  ("purple", 0x800080),
+  ("rebeccapurple", 0x663399),
+  ("red", 0xff0000),
+  ("rosybrown", 0xbc8f8f),
+  ("royalblue", 0x4169e1),
+  ("saddlebrown", 0x8b4513),
+  ("salmon", 0xfa8072),
+  ("sandybrown", 0xf4a460),
+  ("seagreen", 0x2e8b57),
+  ("seashell", 0xfff5ee),
+  ("sienna", 0xa0522d),
+  ("silver", 0xc0c0c0),
+  ("skyblue", 0x87ceeb),
+  ("slateblue", 0x6a5acd),
+  ("slategray", 0x708090),
+  ("slategrey", 0x708090),
+  ("snow", 0xfffafa),
+  ("springgreen", 0x00ff7f),
+  ("steelblue", 0x4682b4),
+  ("tan", 0xd2b48c),
+  ("teal", 0x008080),
+  ("thistle", 0xd8bfd8),
+  ("tomato", 0xff6347),
+  ("turquoise", 0x40e0d0),
+  ("violet", 0xee82ee),
+  ("wheat", 0xf5deb3),
+  ("white", 0xffffff),
+  ("whitesmoke", 0xf5f5f5),
+  ("yellow", 0xffff00),
+  ("yellowgreen", 0x9acd32),
+];
+
+impl ColorValue {
+  /// A named color, `transparent` or `currentcolor`, ignoring case.
+  pub fn from_name(name: &str) -> Option<ColorValue> {
+    let name = name.to_ascii_lowercase();
+    match &*name {
+      "transparent" => return Some(ColorValue::RBGColorValue(0, 0, 0, 0)),
+      "currentcolor" => return Some(ColorValue::CurrentColor),
+      _ => {}
+    }
+    let (_, rgb) = NAMED_COLORS.iter().find(|(color_name, _)| *color_name == name)?;
+    let [_, r, g, b] = rgb.to_be_bytes();
+    Some(ColorValue::RBGColorValue(r, g, b, 255))
+  }
+
+  /// A `#rgb`, `#rgba`, `#rrggbb` or `#rrggbbaa` color, without the `#`.
+  // https://www.w3.org/TR/css-color-4/#hex-notation
+  pub fn from_hex(hex: &str) -> Option<ColorValue> {
+    if !hex.chars().all(|character| character.is_ascii_hexdigit()) {
+      return None;
+    }
+    let digit = |index: usize| u8::from_str_radix(&hex[index..index + 1], 16).unwrap();
+    let pair = |index: usize| u8::from_str_radix(&hex[index..index + 2], 16).unwrap();
+    let color = match hex.len() {
+      3 => ColorValue::RBGColorValue(digit(0) * 17, digit(1) * 17, digit(2) * 17, 255),
+      4 => ColorValue::RBGColorValue(digit(0) * 17, digit(1) * 17, digit(2) * 17, digit(3) * 17),
+      6 => ColorValue::RBGColorValue(pair(0), pair(2), pair(4), 255),
+      8 => ColorValue::RBGColorValue(pair(0), pair(2), pair(4), pair(6)),
+      _ => return None,
+    };
+    Some(color)
+  }
+
+  /// The color as `(red, green, blue, alpha)`, or `None` for `currentcolor`.
+  pub fn to_rgba(&self) -> Option<(u8, u8, u8, u8)> {
+    match self {
+      ColorValue::RBGColorValue(r, g, b, a) => Some((*r, *g, *b, *a)),
+      ColorValue::CurrentColor => None,
+    }
+  }
+}
+
+/// Parse a hex color, color function or color keyword.
+pub fn parse_color(value: &ComponentValue) -> Result<ColorValue, String> {
+  match value {
+    ComponentValue::Token(Token::Hash { value, .. }) => {
+      ColorValue::from_hex(value).ok_or_else(|| format!("invalid hex color '#{}'", value))
+    }
+    ComponentValue::Token(Token::Ident(name)) => {
+      ColorValue::from_name(name).ok_or_else(|| format!("unknown color '{}'", name))
+    }
+    ComponentValue::Function { name, arguments } => {
+      parse_color_function(name, arguments).ok_or_else(|| format!("invalid color '{}'", value))
+    }
+    value => Err(format!("expected a color, got '{}'", value)),
+  }
+}
+
+pub fn is_color_function(name: &str) -> bool {
+  matches!(&*name.to_ascii_lowercase(), "rgb" | "rgba" | "hsl" | "hsla" | "hwb")
+}
+
+// https://www.w3.org/TR/css-color-4/#rgb-functions
+// https://www.w3.org/TR/css-color-4/#the-hsl-notation
+// https://www.w3.org/TR/css-color-4/#the-hwb-notation
+fn parse_color_function(name: &str, arguments: &[ComponentValue]) -> Option<ColorValue> {
+  let name = name.to_ascii_lowercase();
+  let (channels, alpha, is_legacy) = split_arguments(arguments, name != "hwb")?;
+  if is_legacy && !is_valid_legacy_color(&name, &channels, alpha) {
+    return None;
+  }
+  let alpha = match alpha {
+    Some(alpha) => alpha_value(alpha)?,
+    None => 1.0,
+  };
+  let (r, g, b) = match &*name {
+    "rgb" | "rgba" => (
+      rgb_channel(channels[0])?,
+      rgb_channel(channels[1])?,
+      rgb_channel(channels[2])?,
+    ),
+    "hsl" | "hsla" => hsl_to_rgb(hue(channels[0])?, percentage(channels[1])?, percentage(channels[2])?),
+    "hwb" => hwb_to_rgb(hue(channels[0])?, percentage(channels[1])?, percentage(channels[2])?),
+    _ => return None,
+  };
+  Some(ColorValue::RBGColorValue(
+    to_channel(r),
+    to_channel(g),
+    to_channel(b),
+    to_channel(alpha),
+  ))
+}
+
+// The three channels and the optional alpha of `r, g, b, a` (when `allow_commas`) or `r g b / a`,
+// and whether the legacy comma syntax was used.
+type ColorArguments<'a> = (Vec<&'a Token>, Option<&'a Token>, bool);
+
+fn split_arguments(arguments: &[ComponentValue], allow_commas: bool) -> Option<ColorArguments<'_>> {
+  let comma = ComponentValue::Token(Token::Comma);
+  let mut values: Vec<&ComponentValue> = Vec::new();
+  let mut separators = Vec::new();
+  for argument in arguments {
+    match argument {
+      ComponentValue::Token(Token::Whitespace) => {}
+      ComponentValue::Token(Token::Comma) | ComponentValue::Token(Token::Delim('/')) => {
+        separators.push((values.len(), argument))
+      }
+      argument => values.push(argument),
+    }
+  }
+  let tokens: Vec<&Token> = values
+    .iter()
+    .map(|value| match value {
+      ComponentValue::Token(token) => Some(token),
+      _ => None,
+    })
+    .collect::<Option<_>>()?;
+  let is_legacy = separators.iter().any(|(_, separator)| **separator == comma);
+  let expected_separators: Vec<usize> = match (is_legacy, tokens.len()) {
+    (true, 3) => vec![1, 2],
+    (true, 4) => vec![1, 2, 3],
+    (false, 3) => vec![],
+    (false, 4) => vec![3],
+    _ => return None,
+  };
+  let positions: Vec<usize> = separators.iter().map(|(position, _)| *position).collect();
+  let separator_kind_is_valid = separators
+    .iter()
+    .all(|(_, separator)| (**separator == comma) == is_legacy);
+  if positions != expected_separators || !separator_kind_is_valid || (is_legacy && !allow_commas) {
+    return None;
+  }
+  Some((tokens[..3].to_vec(), tokens.get(3).copied(), is_legacy))
+}
+
+// The legacy comma syntax takes no `none`, `rgb()` channels that are all numbers or all
+// percentages, and `hsl()` saturation and lightness as percentages.
+// https://www.w3.org/TR/css-color-4/#typedef-legacy-rgb-syntax
+// https://www.w3.org/TR/css-color-4/#typedef-legacy-hsl-syntax
+fn is_valid_legacy_color(name: &str, channels: &[&Token], alpha: Option<&Token>) -> bool {
+  if channels
+    .iter()
+    .chain(alpha.as_ref())
+    .any(|token| matches!(token, Token::Ident(_)))
+  {
+    return false;
+  }
+  let is_number = |token: &&Token| matches!(token, Token::Number { .. });
+  let is_percentage = |token: &&Token| matches!(token, Token::Percentage(_));
+  match name {
+    "rgb" | "rgba" => channels.iter().all(is_number) || channels.iter().all(is_percentage),
+    _ => channels[1..].iter().all(is_percentage),
+  }
+}
+
+// A 0-255 channel from a number or percentage, in 0-1.
+fn rgb_channel(token: &Token) -> Option<f32> {
+  match token {
+    Token::Number { value, .. } => Some(value / 255.0),
+    Token::Percentage(value) => Some(value / 100.0),
+    Token::Ident(none) if none.eq_ignore_ascii_case("none") => Some(0.0),
+    _ => None,
+  }
+}
+
+// An alpha from a number or percentage, in 0-1.
+fn alpha_value(token: &Token) -> Option<f32> {
+  match token {
+    Token::Number { value, .. } => Some(*value),
+    Token::Percentage(value) => Some(value / 100.0),
+    Token::Ident(none) if none.eq_ignore_ascii_case("none") => Some(0.0),
+    _ => None,
+  }
+}
+
+// A saturation, lightness, whiteness or blackness, in 0-1.
+fn percentage(token: &Token) -> Option<f32> {
+  match token {
+    Token::Percentage(value) => Some((value / 100.0).clamp(0.0, 1.0)),
+    // the modern syntax also accepts plain numbers, as percentages.
+    Token::Number { value, .. } => Some((value / 100.0).clamp(0.0, 1.0)),
+    Token::Ident(none) if none.eq_ignore_ascii_case("none") => Some(0.0),
+    _ => None,
+  }
+}
+
+// A hue in degrees.
+fn hue(token: &Token) -> Option<f32> {
+  let degrees = match token {
+    Token::Number { value, .. } => *value,
+    Token::Dimension { value, unit, .. } => match &*unit.to_ascii_lowercase() {
+      "deg" => *value,
+      "grad" => value * 0.9,
+      "rad" => value.to_degrees(),
+      "turn" => value * 360.0,
+      _ => return None,
+    },
+    Token::Ident(none) if none.eq_ignore_ascii_case("none") => 0.0,
+    _ => return None,
+  };
+  Some(degrees.rem_euclid(360.0))
+}
+
+// https://www.w3.org/TR/css-color-4/#hsl-to-rgb
+fn hsl_to_rgb(hue: f32, saturation: f32, lightness: f32) -> (f32, f32, f32) {
+  let channel = |n: f32| {
+    let k = (n + hue / 30.0) % 12.0;
+    let a = saturation * lightness.min(1.0 - lightness);
+    lightness - a * (k - 3.0).min(9.0 - k).clamp(-1.0, 1.0)
+  };
+  (channel(0.0), channel(8.0), channel(4.0))
+}
+
+// https://www.w3.org/TR/css-color-4/#hwb-to-rgb
+fn hwb_to_rgb(hue: f32, whiteness: f32, blackness: f32) -> (f32, f32, f32) {
+  if whiteness + blackness >= 1.0 {
+    let gray = whiteness / (whiteness + blackness);
+    return (gray, gray, gray);
+  }
+  let (r, g, b) = hsl_to_rgb(hue, 1.0, 0.5);
+  let scale = |channel: f32| channel * (1.0 - whiteness - blackness) + whiteness;
+  (scale(r), scale(g), scale(b))
+}
+
+// A 0-1 value as a 0-255 channel.
+fn to_channel(value: f32) -> u8 {
+  (value.clamp(0.0, 1.0) * 255.0).round() as u8
+}
@@ -0,0 +1,238 @@
+#![allow(dead_code)]
+use crate::css::{ContentItem, DeclarationValue};
+use crate::dom::Element;
+// ==============================
+// https://www.w3.org/TR/css-content-3/
+// https://www.w3.org/TR/css-lists-3/#auto-numbering
+// The text of `content` depends on the counters in scope and the quote depth, which both depend on
+// the elements before it in tree order, so it is generated while walking the document.
+//
+
+// the `quotes` for `auto`: double quotes, then single quotes when nested.
+const DEFAULT_QUOTES: [(&str, &str); 2] = [("\u{201c}", "\u{201d}"), ("\u{2018}", "\u{2019}")];
+
+const ROMAN_NUMERALS: [(i32, &str); 13] = [
+  (1000, "m"),
+  (900, "cm"),
+  (500, "d"),
+  (400, "cd"),
+  (100, "c"),
+  (90, "xc"),
+  (50, "l"),
+  (40, "xl"),
+  (10, "x"),
+  (9, "ix"),
+  (5, "v"),
+  (4, "iv"),
+  (1, "i"),
+];
+
+/// Counters and quote depth while walking a document in tree order.
+#[derive(Debug, Clone, Default)]
+pub struct GeneratedContent {
+  // the counters in scope with their values, outermost first.
+  counters: Vec<(String, i32)>,
+  // where the counters created by the current element and its siblings start.
+  sibling_scope: usize,
+  quote_depth: usize,
+}
+
+// What `GeneratedContent::leave` restores.
+pub struct CounterScope {
+  length: usize,
+  sibling_scope: usize,
+}
+
+impl GeneratedContent {
+  pub fn new() -> Self {
+    GeneratedContent::default()
+  }
+
+  /// Start the children of an element. Counters created from here on, by the children or by the
+  /// element's `::before`, go out of scope with `leave`.
+  pub fn enter(&mut self) -> CounterScope {
+    let scope = CounterScope { length: self.counters.len(), sibling_scope: self.sibling_scope };
+    self.sibling_scope = self.counters.len();
+    scope
+  }
+
+  pub fn leave(&mut self, scope: CounterScope) {
+    self.counters.truncate(scope.length);
+    self.sibling_scope = scope.sibling_scope;
+  }
+
+  /// Apply the `counter-reset`, `counter-increment` and `counter-set` of an element, in that
+  /// order. List items also increment the `list-item` counter, unless `counter-increment` names it.
+  // https://www.w3.org/TR/css-lists-3/#creating-a-counter
+  pub fn update_counters(&mut self, property: &dyn Fn(&str) -> Option<DeclarationValue>, list_item: bool) {
+    for (name, value) in counter_changes(property("counter-reset"), 0) {
+      // a counter created by a previous sibling is replaced.
+      let sibling_counter = self.counters[self.sibling_scope..]
+        .iter()
+        .rposition(|(counter, _)| *counter == name);
+      match sibling_counter {
+        Some(position) => self.counters[self.sibling_scope + position].1 = value,
+        None => self.counters.push((name, value)),
+      }
+    }
+    let mut increments = counter_changes(property("counter-increment"), 1);
+    if list_item && !increments.iter().any(|(name, _)| name == "list-item") {
+      increments.push(("list-item".to_string(), 1));
+    }
+    for (name, value) in increments {
+      *self.counter(&name) += value;
+    }
+    for (name, value) in counter_changes(property("counter-set"), 0) {
+      *self.counter(&name) = value;
+    }
+  }
+
+  /// The text of `content` items for a pseudo-element of `element`, with the `quotes` in effect.
+  pub fn resolve(&mut self, items: &[ContentItem], element: &Element, quotes: Option<&DeclarationValue>) -> String {
+    let quotes = quote_pairs(quotes);
+    let quote = |depth: usize, open: bool| match quotes.get(depth).or(quotes.last()) {
+      Some((open_quote, _)) if open => open_quote.clone(),
+      Some((_, close_quote)) => close_quote.clone(),
+      None => String::new(),
+    };
+    let mut text = String::new();
+    for item in items {
+      match item {
+        ContentItem::String(string) => text.push_str(string),
+        ContentItem::Attr(name) => {
+          let attribute = element
+            .atributes
+            .iter()
+            .find(|(attribute, _)| attribute.eq_ignore_ascii_case(name));
+          text.push_str(attribute.map_or("", |(_, value)| value));
+        }
+        ContentItem::Counter(name, style) => text.push_str(&format_counter(*self.counter(name), style)),
+        ContentItem::Counters(name, separator, style) => {
+          self.counter(name);
+          let counters = self.counters.iter().filter(|(counter, _)| counter == name);
+          let values: Vec<String> = counters.map(|(_, value)| format_counter(*value, style)).collect();
+          text.push_str(&values.join(separator));
+        }
+        ContentItem::OpenQuote => {
+          text.push_str(&quote(self.quote_depth, true));
+          self.quote_depth += 1;
+        }
+        ContentItem::CloseQuote if self.quote_depth > 0 => {
+          self.quote_depth -= 1;
+          text.push_str(&quote(self.quote_depth, false));
+        }
+        ContentItem::CloseQuote => {}
+        ContentItem::NoOpenQuote => self.quote_depth += 1,
+        ContentItem::NoCloseQuote => self.quote_depth = self.quote_depth.saturating_sub(1),
+      }
+    }
+    text
+  }
+
+  /// The text of a `::marker` without `content`: the `list-item` counter or a bullet, as given by
+  /// `list-style-type`. `None` for `list-style-type: none`.
+  // https://www.w3.org/TR/css-lists-3/#content-property
+  pub fn marker_text(&mut self, list_style_type: &str) -> Option<String> {
+    match list_style_type {
+      "none" => None,
+      "disc" | "circle" | "square" => Some(format!("{} ", format_counter(0, list_style_type))),
+      style => Some(format!("{}. ", format_counter(*self.counter("list-item"), style))),
+    }
+  }
+
+  // The innermost counter named `name`, created with the value 0 if there's none in scope.
+  fn counter(&mut self, name: &str) -> &mut i32 {
+    let position = self.counters.iter().rposition(|(counter, _)| counter == name);
+    let position = position.unwrap_or_else(|| {
+      self.counters.push((name.to_string(), 0));
+      self.counters.len() - 1
+    });
+    &mut self.counters[position].1
+  }
+}
+
+// The counter names of a `counter-*` value, each with its integer or `default`, e.g. `a 2 b`.
+fn counter_changes(value: Option<DeclarationValue>, default: i32) -> Vec<(String, i32)> {
+  let values = match value {
+    Some(DeclarationValue::List(values)) => values,
+    Some(value) => vec![value],
+    None => Vec::new(),
+  };
+  let mut changes: Vec<(String, i32)> = Vec::new();
+  for value in values {
+    match value {
+      DeclarationValue::Keyword(name) if name != "none" => changes.push((name, default)),
+      DeclarationValue::Integer(integer) => {
+        if let Some((_, value)) = changes.last_mut() {
+          *value = integer;
+        }
+      }
+      _ => {}
+    }
+  }
+  changes
+}
+
+// The open and close quotes of a `quotes` value, outermost first.
+fn quote_pairs(quotes: Option<&DeclarationValue>) -> Vec<(String, String)> {
+  match quotes {
+    Some(DeclarationValue::Content(strings)) => {
+      let strings: Vec<String> = strings
+        .iter()
+        .filter_map(|item| match item {
+          ContentItem::String(string) => Some(string.clone()),
+          _ => None,
+        })
+        .collect();
+      let pairs = strings.chunks_exact(2);
+      pairs.map(|pair| (pair[0].clone(), pair[1].clone())).collect()
+    }
+    Some(DeclarationValue::Keyword(keyword)) if keyword == "none" => Vec::new(),
+    _ => DEFAULT_QUOTES
+      .iter()
+      .map(|(open, close)| (open.to_string(), close.to_string()))
+      .collect(),
+  }
+}
+
+/// Represent a counter value in a counter style. Unknown styles, and values a style can't
+/// represent, fall back to `decimal`.
+// https://www.w3.org/TR/css-counter-styles-3/#predefined-counters
+pub fn format_counter(value: i32, style: &str) -> String {
+  match style {
+    "none" => String::new(),
+    "disc" => "\u{2022}".to_string(),
+    "circle" => "\u{25e6}".to_string(),
+    "square" => "\u{25aa}".to_string(),
+    "decimal-leading-zero" if (0..10).contains(&value) => format!("0{}", value),
+    "lower-alpha" | "lower-latin" if value > 0 => alphabetic(value),
+    "upper-alpha" | "upper-latin" if value > 0 => alphabetic(value).to_ascii_uppercase(),
+    "lower-roman" if (1..4000).contains(&value) => roman(value),
+    "upper-roman" if (1..4000).contains(&value) => roman(value).to_ascii_uppercase(),
+    _ => value.to_string(),
+  }
+}
+
+// a, b, ..., z, aa, ab, ...
+fn alphabetic(value: i32) -> String {
+  let mut letters = Vec::new();
+  let mut value = value;
+  while value > 0 {
+    value -= 1;
+    letters.push((b'a' + (value % 26) as u8) as char);
+    value /= 26;
+  }
+  letters.iter().rev().collect()
+}
+
+fn roman(value: i32) -> String {
+  let mut numeral = String::new();
+  let mut value = value;
+  for (amount, symbol) in ROMAN_NUMERALS {
+    while value >= amount {
+      numeral.push_str(symbol);
+      value -= amount;
+    }
+  }
+  numeral
+}
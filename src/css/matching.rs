@@ -0,0 +1,360 @@
+#![allow(dead_code)]
+use crate::css::{
+  AttributeOperator, AttributeSelector, CaseSensitivity, Combinator, Nth, PseudoClass, Selector, SimpleSelector,
+};
+use crate::dom::{self, Element, Node, NodeType};
+// ==============================
+// https://www.w3.org/TR/selectors-4/#match-against-element
+// Complex selectors are matched right to left: the subject against the element, then each compound
+// against the elements its combinator leads to, e.g. the ancestors for `div p`.
+//
+
+// Attributes whose values are matched ignoring ASCII case in HTML documents.
+// https://html.spec.whatwg.org/multipage/semantics-other.html#case-sensitivity-of-selectors
+const CASE_INSENSITIVE_ATTRIBUTES: [&str; 46] = [
+  "accept",
+  "accept-charset",
+  "align",
+  "alink",
+  "axis",
+  "bgcolor",
+  "charset",
+  "checked",
+  "clear",
+  "codetype",
+  "color",
+  "compact",
+  "declare",
+  "defer",
+  "dir",
+  "direction",
+  "disabled",
+  "enctype",
+  "face",
+  "frame",
+  "hreflang",
+  "http-equiv",
+  "lang",
+  "language",
+  "link",
+  "media",
+  "method",
+  "multiple",
+  "nohref",
+  "noresize",
+  "noshade",
+  "nowrap",
+  "readonly",
+  "rel",
+  "rev",
+  "rules",
+  "scope",
+  "scrolling",
+  "selected",
+  "shape",
+  "target",
+  "text",
+  "type",
+  "valign",
+  "valuetype",
+  "vlink",
+];
+
+/// An element with its ancestors, as DOM nodes don't point to their parent.
+#[derive(Debug, Clone, Copy)]
+pub struct ElementContext<'a, 'b> {
+  pub node: &'a Node,
+  pub element: &'a Element,
+  // the index of `node` among its parent's children.
+  pub index: usize,
+  // the ancestors of `node`, outermost first, each with its index among its parent's children.
+  pub ancestors: &'b [(&'a Node, usize)],
+}
+
+impl<'a, 'b> ElementContext<'a, 'b> {
+  /// `None` if `node` isn't an element.
+  pub fn new(node: &'a Node, index: usize, ancestors: &'b [(&'a Node, usize)]) -> Option<Self> {
+    Some(ElementContext { node, element: node.element()?, index, ancestors })
+  }
+
+  pub fn parent(&self) -> Option<ElementContext<'a, 'b>> {
+    let ((parent, index), ancestors) = self.ancestors.split_last()?;
+    ElementContext::new(parent, *index, ancestors)
+  }
+
+  /// The namespace of the element, derived from its ancestors.
+  pub fn namespace(&self) -> &'static str {
+    let elements = self.ancestors.iter().filter_map(|(node, _)| node.element());
+    let parent = elements.fold(None, |parent, element| Some((element, element.namespace(parent))));
+    self.element.namespace(parent)
+  }
+
+  /// Whether the element is disabled: by its own `disabled` attribute, as an `<option>` in a
+  /// disabled `<optgroup>`, or as a form control inside a `<fieldset disabled>`, as for forms.
+  // https://html.spec.whatwg.org/multipage/semantics-other.html#concept-element-disabled
+  pub fn is_disabled(&self) -> bool {
+    if self.element.state.disabled {
+      return true;
+    }
+    match &*self.element.tag_name.to_ascii_lowercase() {
+      "option" => self.parent().is_some_and(|parent| {
+        parent.element.tag_name.eq_ignore_ascii_case("optgroup") && parent.element.state.disabled
+      }),
+      "button" | "input" | "select" | "textarea" | "fieldset" => {
+        // each ancestor with the index of its child on the way down to this element.
+        let child_indices = self.ancestors.iter().skip(1).map(|(_, index)| *index);
+        let child_indices = child_indices.chain(std::iter::once(self.index));
+        let mut ancestors = self.ancestors.iter().zip(child_indices);
+        ancestors.any(|((ancestor, _), child_index)| dom::fieldset_disables_child(ancestor, child_index))
+      }
+      _ => false,
+    }
+  }
+
+  /// The ancestor elements, nearest first.
+  pub fn ancestors(&self) -> impl Iterator<Item = ElementContext<'a, 'b>> {
+    std::iter::successors(self.parent(), |ancestor| ancestor.parent())
+  }
+
+  /// The element siblings before this element, nearest first.
+  pub fn previous_siblings(&self) -> Vec<ElementContext<'a, 'b>> {
+    let Some((parent, _)) = self.ancestors.last() else {
+      return Vec::new();
+    };
+    let siblings = parent.children[..self.index].iter().enumerate().rev();
+    siblings
+      .filter_map(|(index, sibling)| ElementContext::new(sibling, index, self.ancestors))
+      .collect()
+  }
+
+  /// The element siblings after this element, nearest first.
+  pub fn next_siblings(&self) -> Vec<ElementContext<'a, 'b>> {
+    let Some((parent, _)) = self.ancestors.last() else {
+      return Vec::new();
+    };
+    let siblings = parent.children.iter().enumerate().skip(self.index + 1);
+    siblings
+      .filter_map(|(index, sibling)| ElementContext::new(sibling, index, self.ancestors))
+      .collect()
+  }
+
+  /// Whether `predicate` holds for a child element. The children only live as long as the call,
+  /// as their ancestors include this element.
+  pub fn any_child(&self, predicate: &dyn Fn(&ElementContext) -> bool) -> bool {
+    let mut ancestors = self.ancestors.to_vec();
+    ancestors.push((self.node, self.index));
+    let children = self.node.children.iter().enumerate();
+    let mut children = children.filter_map(|(index, child)| ElementContext::new(child, index, &ancestors));
+    children.any(|child| predicate(&child))
+  }
+
+  /// Whether `predicate` holds for a descendant element, in tree order.
+  pub fn any_descendant(&self, predicate: &dyn Fn(&ElementContext) -> bool) -> bool {
+    self.any_child(&|child| predicate(child) || child.any_descendant(predicate))
+  }
+}
+
+/// Whether `selector` matches the element of `context`.
+// http://www.w3.org/TR/CSS2/selector.html#pattern-matching
+pub fn matches(context: &ElementContext, selector: &Selector) -> bool {
+  match selector {
+    Selector::Simple(simple_selector) => matches_simple_selector(context, simple_selector),
+    Selector::Complex { compounds, subject } => {
+      matches_simple_selector(context, subject) && matches_compounds(context, compounds)
+    }
+  }
+}
+
+fn matches_any(context: &ElementContext, selectors: &[Selector]) -> bool {
+  selectors.iter().any(|selector| matches(context, selector))
+}
+
+// Whether `compounds`, right to left, match the elements that their combinators lead to from the
+// element of `context`.
+fn matches_compounds(context: &ElementContext, compounds: &[(SimpleSelector, Combinator)]) -> bool {
+  let Some(((selector, combinator), compounds)) = compounds.split_last() else {
+    return true;
+  };
+  let matches_from = |candidate: &ElementContext| {
+    matches_simple_selector(candidate, selector) && matches_compounds(candidate, compounds)
+  };
+  match combinator {
+    Combinator::Child => context.parent().is_some_and(|parent| matches_from(&parent)),
+    Combinator::Descendant => context.ancestors().any(|ancestor| matches_from(&ancestor)),
+    Combinator::NextSibling => context.previous_siblings().first().is_some_and(matches_from),
+    Combinator::SubsequentSibling => context.previous_siblings().iter().any(matches_from),
+  }
+}
+
+fn matches_simple_selector(context: &ElementContext, selector: &SimpleSelector) -> bool {
+  let elem = context.element;
+  // Check type selector, ignoring ASCII case for HTML elements.
+  let is_html = context.namespace() == dom::HTML_NAMESPACE;
+  if selector.tag_name.iter().any(|name| {
+    if is_html {
+      !elem.tag_name.eq_ignore_ascii_case(name)
+    } else {
+      elem.tag_name != *name
+    }
+  }) {
+    return false;
+  }
+
+  // Check namespace
+  if selector
+    .namespace
+    .iter()
+    .any(|namespace| !namespace.matches(Some(context.namespace())))
+  {
+    return false;
+  }
+
+  // Check ID selector
+  if selector.id.iter().any(|id| elem.id() != Some(id)) {
+    return false;
+  }
+
+  // Check class selectors
+  let elem_classes = elem.classes();
+  if selector.class.iter().any(|class| !elem_classes.contains(&**class)) {
+    return false;
+  }
+
+  // Check attribute selectors
+  if !selector
+    .attributes
+    .iter()
+    .all(|attribute| matches_attribute(elem, attribute))
+  {
+    return false;
+  }
+
+  // Check pseudo-classes
+  if !selector
+    .pseudo_classes
+    .iter()
+    .all(|pseudo_class| matches_pseudo_class(context, pseudo_class))
+  {
+    return false;
+  }
+  // We didn't find any non-matching selector components.
+  true
+}
+
+// https://www.w3.org/TR/selectors-4/#attribute-representation
+fn matches_attribute(elem: &Element, selector: &AttributeSelector) -> bool {
+  // attribute names are ASCII case-insensitive in HTML.
+  let attribute = elem
+    .atributes
+    .iter()
+    .find(|(name, _)| name.eq_ignore_ascii_case(&selector.name));
+  let Some((name, value)) = attribute else {
+    return false;
+  };
+  // attributes from the HTML parser have no namespace.
+  if selector.namespace.iter().any(|namespace| !namespace.matches(None)) {
+    return false;
+  }
+  let ignore_case = match selector.case_sensitivity {
+    CaseSensitivity::Default => CASE_INSENSITIVE_ATTRIBUTES.contains(&&*name.to_ascii_lowercase()),
+    CaseSensitivity::Insensitive => true,
+    CaseSensitivity::Sensitive => false,
+  };
+  let (value, expected) = match ignore_case {
+    true => (value.to_ascii_lowercase(), selector.value.to_ascii_lowercase()),
+    false => (value.clone(), selector.value.clone()),
+  };
+  match selector.operator {
+    AttributeOperator::Exists => true,
+    AttributeOperator::Equals => value == expected,
+    AttributeOperator::Includes => value.split_ascii_whitespace().any(|word| word == expected),
+    AttributeOperator::DashMatch => value == expected || value.starts_with(&format!("{}-", expected)),
+    // an empty value never matches the substring operators.
+    AttributeOperator::Prefix => !expected.is_empty() && value.starts_with(&expected),
+    AttributeOperator::Suffix => !expected.is_empty() && value.ends_with(&expected),
+    AttributeOperator::Substring => !expected.is_empty() && value.contains(&expected),
+  }
+}
+
+fn matches_pseudo_class(context: &ElementContext, pseudo_class: &PseudoClass) -> bool {
+  let state = context.element.state;
+  let same_type = |sibling: &ElementContext| sibling.element.tag_name == context.element.tag_name;
+  // the position among the siblings that `is_counted`, from the start or the end.
+  let position = |siblings: Vec<ElementContext>, is_counted: &dyn Fn(&ElementContext) -> bool| {
+    1 + siblings.iter().filter(|sibling| is_counted(sibling)).count()
+  };
+  let nth_child = |nth: &Nth, of: &Option<Vec<Selector>>, siblings: Vec<ElementContext>| match of {
+    Some(selectors) => {
+      matches_any(context, selectors) && nth.matches(position(siblings, &|sibling| matches_any(sibling, selectors)))
+    }
+    None => nth.matches(position(siblings, &|_| true)),
+  };
+  match pseudo_class {
+    PseudoClass::FirstChild => context.previous_siblings().is_empty(),
+    PseudoClass::LastChild => context.next_siblings().is_empty(),
+    PseudoClass::OnlyChild => context.previous_siblings().is_empty() && context.next_siblings().is_empty(),
+    PseudoClass::FirstOfType => !context.previous_siblings().iter().any(same_type),
+    PseudoClass::LastOfType => !context.next_siblings().iter().any(same_type),
+    PseudoClass::OnlyOfType => {
+      !context.previous_siblings().iter().any(same_type) && !context.next_siblings().iter().any(same_type)
+    }
+    PseudoClass::NthChild(nth, of) => nth_child(nth, of, context.previous_siblings()),
+    PseudoClass::NthLastChild(nth, of) => nth_child(nth, of, context.next_siblings()),
+    PseudoClass::NthOfType(nth) => nth.matches(position(context.previous_siblings(), &same_type)),
+    PseudoClass::NthLastOfType(nth) => nth.matches(position(context.next_siblings(), &same_type)),
+    // no child elements and no text.
+    PseudoClass::Empty => context.node.children.iter().all(|child| match &child.node_type {
+      NodeType::Text(text) => text.is_empty(),
+      NodeType::Element(_) => false,
+    }),
+    PseudoClass::Root => context.ancestors.is_empty(),
+    PseudoClass::Not(selectors) => !matches_any(context, selectors),
+    PseudoClass::Is(selectors) | PseudoClass::Where(selectors) => matches_any(context, selectors),
+    PseudoClass::Has(relative_selectors) => relative_selectors
+      .iter()
+      .any(|(combinator, selector)| matches_relative(context, *combinator, selector)),
+    PseudoClass::Hover => state.hover,
+    PseudoClass::Active => state.active,
+    PseudoClass::Focus => state.focus,
+    PseudoClass::FocusWithin => state.focus_within,
+    PseudoClass::FocusVisible => state.focus_visible,
+    PseudoClass::Checked => state.checked,
+    PseudoClass::Disabled => context.is_disabled(),
+    PseudoClass::Visited => state.visited,
+    PseudoClass::Target => state.target,
+  }
+}
+
+// Whether an element reached from the element of `context` through `combinator` starts a chain of
+// elements matching `selector` left to right, as in `:has(> a b)`.
+// https://www.w3.org/TR/selectors-4/#relational
+fn matches_relative(context: &ElementContext, combinator: Combinator, selector: &Selector) -> bool {
+  let (compounds, subject) = match selector {
+    Selector::Simple(simple_selector) => (&[][..], simple_selector),
+    Selector::Complex { compounds, subject } => (compounds.as_slice(), subject),
+  };
+  // each compound with the combinator that leads to it, left to right.
+  let mut steps = vec![combinator];
+  steps.extend(compounds.iter().map(|(_, combinator)| *combinator));
+  let selectors: Vec<&SimpleSelector> = compounds
+    .iter()
+    .map(|(compound, _)| compound)
+    .chain([subject])
+    .collect();
+  let steps: Vec<(Combinator, &SimpleSelector)> = steps.into_iter().zip(selectors).collect();
+  matches_forward(context, &steps)
+}
+
+fn matches_forward(context: &ElementContext, steps: &[(Combinator, &SimpleSelector)]) -> bool {
+  let Some(((combinator, selector), steps)) = steps.split_first() else {
+    return true;
+  };
+  let matches_from =
+    |candidate: &ElementContext| matches_simple_selector(candidate, selector) && matches_forward(candidate, steps);
+  match combinator {
+    Combinator::Child => context.any_child(&matches_from),
+    Combinator::Descendant => context.any_descendant(&matches_from),
+    Combinator::NextSibling => context.next_siblings().first().is_some_and(matches_from),
+    Combinator::SubsequentSibling => context.next_siblings().iter().any(matches_from),
+  }
+}
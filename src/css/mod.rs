@@ -1,8 +1,20 @@
 #![allow(dead_code, unused_imports)]
+mod color;
+mod content;
 mod jit;
+mod matching;
 mod node;
 mod parser;
+mod serializer;
+mod shorthand;
+mod tokenizer;
 mod tree;
+pub use color::*;
+pub use content::*;
+pub use matching::*;
 pub use node::*;
 pub use parser::CSSParser;
+pub use serializer::*;
+pub use shorthand::*;
+pub use tokenizer::*;
 pub use tree::*;
@@ -1,79 +1,822 @@
 #![allow(dead_code)]
-#[derive(Debug, Clone, serde::Serialize)]
+use std::fmt;
+
+use crate::css::Token;
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct StyleSheet {
   pub rules: Vec<Rule>,
+  // `/*! ... */` comments, when the parser was asked to preserve them.
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub comments: Vec<Comment>,
+  // where the style sheet comes from; parsed style sheets are author style sheets.
+  #[serde(default, skip_serializing_if = "Origin::is_author")]
+  pub origin: Origin,
+  // the `@namespace` rules, which come before the style rules.
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub namespaces: Vec<NamespaceRule>,
+}
+
+// `@namespace prefix url(...)`
+// https://www.w3.org/TR/css-namespaces-3/
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct NamespaceRule {
+  // `None` for the default namespace.
+  pub prefix: Option<String>,
+  pub url: String,
+}
+
+// https://www.w3.org/TR/css-cascade-4/#cascading-origins
+#[derive(Debug, Clone, Copy, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum Origin {
+  UserAgent,
+  User,
+  #[default]
+  Author,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Comment {
+  // the text between `/*` and `*/`, including the `!`.
+  pub text: String,
+  // the index of the rule the comment comes before; `rules.len()` for comments after the last rule.
+  pub rule_index: usize,
 }
-#[derive(Debug, Clone, serde::Serialize)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct Rule {
+  // in source order.
   pub selectors: Vec<Selector>,
   pub declarations: Vec<Declaration>,
 }
 
-#[derive(Debug, Clone, serde::Serialize)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct Declaration {
   pub name: String,
   pub value: DeclarationValue,
+  // `!important`
+  #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+  pub important: bool,
+}
+
+// https://www.w3.org/TR/css-syntax-3/#component-value
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum ComponentValue {
+  Token(Token),
+  Function {
+    name: String,
+    arguments: Vec<ComponentValue>,
+  },
+  // a `{}`, `[]` or `()` block, by its opening character.
+  Block {
+    opening: char,
+    values: Vec<ComponentValue>,
+  },
 }
 
-#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
 pub enum DeclarationValue {
   Keyword(String),
+  // a dimension or percentage, e.g. `10px`, `50%` or `90deg`.
   Length(f32, UnitValue),
+  Number(f32),
+  Integer(i32),
   ColorValue(ColorValue),
+  // whitespace-separated values, e.g. `0 auto` or `12px/1.5 Georgia, serif`.
+  List(Vec<DeclarationValue>),
+  // a `,` or `/` between the values of a list.
+  Separator(char),
+  // the items of `content`, e.g. `"§ " counter(section)`, or the strings of `quotes`.
+  Content(Vec<ContentItem>),
 }
 
-#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+// https://www.w3.org/TR/css-content-3/#content-property
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum ContentItem {
+  String(String),
+  // `attr(name)`, the value of an attribute of the element.
+  Attr(String),
+  // `counter(name, style)`, the innermost counter of that name.
+  Counter(String, String),
+  // `counters(name, separator, style)`, every counter of that name, outermost first.
+  Counters(String, String, String),
+  OpenQuote,
+  CloseQuote,
+  // change the quote depth without inserting a quote.
+  NoOpenQuote,
+  NoCloseQuote,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
 pub enum ColorValue {
-  HexColorValue(String),
+  // red, green, blue and alpha; the parser produces every color in this form.
   RBGColorValue(u8, u8, u8, u8),
-  HSLColorValue(u8, u8, u8, u8),
+  // `currentcolor`, the value of the element's `color` property.
+  CurrentColor,
 }
 
-#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
 pub enum UnitValue {
+  // https://www.w3.org/TR/css-values-4/#lengths
   Px,
   Em,
   Rem,
   Percent,
+  Ex,
+  Ch,
+  Vw,
+  Vh,
+  Vmin,
+  Vmax,
+  Cm,
+  Mm,
+  Q,
+  In,
+  Pt,
+  Pc,
+  // https://www.w3.org/TR/css-values-4/#angles
+  Deg,
+  Grad,
+  Rad,
+  Turn,
+  // https://www.w3.org/TR/css-values-4/#time
+  S,
+  Ms,
+  // https://www.w3.org/TR/css-values-4/#resolution
+  Dpi,
+  Dpcm,
+  Dppx,
 }
 
-#[derive(Debug, Clone, serde::Serialize)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub enum Selector {
   // .selector
   Simple(SimpleSelector),
+  // `div.test > p`: the compound selectors left of the subject, each with the combinator after it.
+  Complex {
+    compounds: Vec<(SimpleSelector, Combinator)>,
+    subject: SimpleSelector,
+  },
 }
-#[derive(Debug, Clone, serde::Serialize)]
+
+// https://www.w3.org/TR/selectors-4/#combinators
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum Combinator {
+  // `a b`
+  Descendant,
+  // `a > b`
+  Child,
+  // `a + b`
+  NextSibling,
+  // `a ~ b`
+  SubsequentSibling,
+}
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct SimpleSelector {
+  // `None` for the universal selector `*`, which may be left out.
   pub tag_name: Option<String>,
+  // the namespace of the element; any namespace if `None`.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub namespace: Option<NamespaceConstraint>,
   pub id: Option<String>,
   pub class: Vec<String>,
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub attributes: Vec<AttributeSelector>,
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub pseudo_classes: Vec<PseudoClass>,
+  // only allowed in the subject of a selector, e.g. `p.note::before`.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub pseudo_element: Option<PseudoElement>,
+}
+
+// https://www.w3.org/TR/css-pseudo-4/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub enum PseudoElement {
+  Before,
+  After,
+  Marker,
+  FirstLine,
+  FirstLetter,
+}
+
+// The namespace a type or attribute selector requires, by the prefix before `|`.
+// https://www.w3.org/TR/selectors-4/#type-nmsp
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum NamespaceConstraint {
+  // `*|`
+  Any,
+  // `|`, no namespace.
+  None,
+  // `prefix|`, with the namespace URL of the prefix.
+  Prefix(String, String),
+  // the default namespace, for type selectors without a prefix.
+  Default(String),
+}
+
+// https://www.w3.org/TR/selectors-4/#attribute-selectors
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct AttributeSelector {
+  pub name: String,
+  // no namespace if `None`, as attributes don't take the default namespace.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub namespace: Option<NamespaceConstraint>,
+  pub operator: AttributeOperator,
+  // empty for `[attr]`.
+  pub value: String,
+  pub case_sensitivity: CaseSensitivity,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum AttributeOperator {
+  // `[attr]`
+  Exists,
+  // `[attr=value]`
+  Equals,
+  // `[attr~=value]`, one of the whitespace-separated words.
+  Includes,
+  // `[attr|=value]`, `value` or starting with `value-`.
+  DashMatch,
+  // `[attr^=value]`
+  Prefix,
+  // `[attr$=value]`
+  Suffix,
+  // `[attr*=value]`
+  Substring,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum CaseSensitivity {
+  // case-sensitive, except for the HTML attributes whose values aren't.
+  Default,
+  // the `i` flag
+  Insensitive,
+  // the `s` flag
+  Sensitive,
+}
+
+// https://www.w3.org/TR/selectors-4/#structural-pseudos
+// https://www.w3.org/TR/selectors-4/#logical-combination
+// https://www.w3.org/TR/selectors-4/#useraction-pseudos
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub enum PseudoClass {
+  FirstChild,
+  LastChild,
+  OnlyChild,
+  FirstOfType,
+  LastOfType,
+  OnlyOfType,
+  // `:nth-child(An+B of S)`, counting only the siblings matching `S`.
+  NthChild(Nth, Option<Vec<Selector>>),
+  NthLastChild(Nth, Option<Vec<Selector>>),
+  NthOfType(Nth),
+  NthLastOfType(Nth),
+  Empty,
+  Root,
+  Not(Vec<Selector>),
+  Is(Vec<Selector>),
+  // `:is()` without specificity.
+  Where(Vec<Selector>),
+  // relative selectors, e.g. `> img` in `:has(> img)`.
+  Has(Vec<(Combinator, Selector)>),
+  // matched against the `ElementState` of the element.
+  Hover,
+  Active,
+  Focus,
+  FocusWithin,
+  FocusVisible,
+  Checked,
+  Disabled,
+  Visited,
+  Target,
+}
+
+// The `An+B` of `:nth-child()`: the positions `a * n + b` for every n >= 0, counting from 1.
+// https://www.w3.org/TR/css-syntax-3/#anb-microsyntax
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Nth {
+  pub a: i32,
+  pub b: i32,
+}
+
+impl Nth {
+  pub fn matches(&self, position: usize) -> bool {
+    let offset = position as i32 - self.b;
+    match self.a {
+      0 => offset == 0,
+      a => offset % a == 0 && offset / a >= 0,
+    }
+  }
+}
+
+impl Default for SimpleSelector {
+  fn default() -> Self {
+    SimpleSelector::new()
+  }
 }
 
 impl SimpleSelector {
   pub fn new() -> Self {
-    SimpleSelector { tag_name: None, id: None, class: Vec::new() }
+    SimpleSelector {
+      tag_name: None,
+      namespace: None,
+      id: None,
+      class: Vec::new(),
+      attributes: Vec::new(),
+      pseudo_classes: Vec::new(),
+      pseudo_element: None,
+    }
   }
 }
 
 pub type Specificity = (usize, usize, usize);
 
+impl Default for Selector {
+  fn default() -> Self {
+    Selector::new()
+  }
+}
+
 impl Selector {
   pub fn new() -> Self {
     Selector::Simple(SimpleSelector::new())
   }
   pub fn specificity(&self) -> Specificity {
     // http://www.w3.org/TR/selectors/#specificity
-    let Selector::Simple(SimpleSelector { tag_name, id, class }) = self;
-    return (tag_name.iter().count(), id.iter().count(), class.len());
+    match self {
+      Selector::Simple(simple_selector) => simple_selector.specificity(),
+      // the sum over every compound selector.
+      Selector::Complex { compounds, subject } => {
+        let specificities = compounds.iter().map(|(compound, _)| compound.specificity());
+        specificities.fold(subject.specificity(), add_specificity)
+      }
+    }
+  }
+
+  /// The pseudo-element the selector styles, instead of the element it matches.
+  pub fn pseudo_element(&self) -> Option<PseudoElement> {
+    match self {
+      Selector::Simple(simple_selector) => simple_selector.pseudo_element,
+      Selector::Complex { subject, .. } => subject.pseudo_element,
+    }
+  }
+}
+
+impl SimpleSelector {
+  pub fn specificity(&self) -> Specificity {
+    // (ids, classes, attributes and pseudo-classes, type selectors), compared in that order.
+    // pseudo-elements count as type selectors.
+    let classes = self.class.len() + self.attributes.len();
+    let types = self.tag_name.iter().count() + self.pseudo_element.iter().count();
+    let specificity = (self.id.iter().count(), classes, types);
+    let pseudo_classes = self
+      .pseudo_classes
+      .iter()
+      .map(|pseudo_class| pseudo_class.specificity());
+    pseudo_classes.fold(specificity, add_specificity)
+  }
+}
+
+impl PseudoClass {
+  // https://www.w3.org/TR/selectors-4/#specificity-rules
+  pub fn specificity(&self) -> Specificity {
+    // the most specific selector of the list.
+    let most_specific = |selectors: &[Selector]| {
+      let specificities = selectors.iter().map(|selector| selector.specificity());
+      specificities.max().unwrap_or_default()
+    };
+    match self {
+      PseudoClass::Where(_) => (0, 0, 0),
+      PseudoClass::Is(selectors) | PseudoClass::Not(selectors) => most_specific(selectors),
+      PseudoClass::Has(relative_selectors) => {
+        let specificities = relative_selectors.iter().map(|(_, selector)| selector.specificity());
+        specificities.max().unwrap_or_default()
+      }
+      PseudoClass::NthChild(_, Some(selectors)) | PseudoClass::NthLastChild(_, Some(selectors)) => {
+        add_specificity((0, 1, 0), most_specific(selectors))
+      }
+      _ => (0, 1, 0),
+    }
   }
 }
 
+fn add_specificity((ids, classes, types): Specificity, other: Specificity) -> Specificity {
+  (ids + other.0, classes + other.1, types + other.2)
+}
+
+impl NamespaceConstraint {
+  /// Whether an element or attribute in `namespace` satisfies the constraint.
+  pub fn matches(&self, namespace: Option<&str>) -> bool {
+    match self {
+      NamespaceConstraint::Any => true,
+      NamespaceConstraint::None => namespace.is_none(),
+      NamespaceConstraint::Prefix(_, url) | NamespaceConstraint::Default(url) => namespace == Some(url.as_str()),
+    }
+  }
+}
+
+impl Origin {
+  pub fn is_author(&self) -> bool {
+    *self == Origin::Author
+  }
+
+  /// The rank of declarations from this origin in the cascade, lowest first. Important
+  /// declarations beat normal ones and reverse the order of the origins.
+  // https://www.w3.org/TR/css-cascade-4/#cascade-origin
+  pub fn precedence(&self, important: bool) -> u8 {
+    match (self, important) {
+      (Origin::UserAgent, false) => 0,
+      (Origin::User, false) => 1,
+      (Origin::Author, false) => 2,
+      (Origin::Author, true) => 3,
+      (Origin::User, true) => 4,
+      (Origin::UserAgent, true) => 5,
+    }
+  }
+}
+
+// font-relative units are resolved against the initial `medium` font size.
+const DEFAULT_FONT_SIZE: f32 = 16.0;
+
 impl DeclarationValue {
+  /// The value in px. Font-relative lengths use the default font size; percentages and
+  /// viewport-relative lengths need a reference size and are 0.
   pub fn to_px(&self) -> f32 {
     match self {
-      DeclarationValue::Length(px, _) => *px,
+      DeclarationValue::Length(value, unit) => match unit {
+        UnitValue::Em | UnitValue::Rem => value * DEFAULT_FONT_SIZE,
+        UnitValue::Ex | UnitValue::Ch => value * DEFAULT_FONT_SIZE / 2.0,
+        unit => unit.absolute_px().map_or(0.0, |px| value * px),
+      },
       // TODO: support other unit
       _ => 0.0,
     }
   }
 }
+
+impl UnitValue {
+  /// The unit for a dimension suffix, e.g. `px` or `Q`, ignoring case.
+  pub fn parse(unit: &str) -> Option<UnitValue> {
+    let unit = match &*unit.to_ascii_lowercase() {
+      "px" => UnitValue::Px,
+      "em" => UnitValue::Em,
+      "rem" => UnitValue::Rem,
+      "%" => UnitValue::Percent,
+      "ex" => UnitValue::Ex,
+      "ch" => UnitValue::Ch,
+      "vw" => UnitValue::Vw,
+      "vh" => UnitValue::Vh,
+      "vmin" => UnitValue::Vmin,
+      "vmax" => UnitValue::Vmax,
+      "cm" => UnitValue::Cm,
+      "mm" => UnitValue::Mm,
+      "q" => UnitValue::Q,
+      "in" => UnitValue::In,
+      "pt" => UnitValue::Pt,
+      "pc" => UnitValue::Pc,
+      "deg" => UnitValue::Deg,
+      "grad" => UnitValue::Grad,
+      "rad" => UnitValue::Rad,
+      "turn" => UnitValue::Turn,
+      "s" => UnitValue::S,
+      "ms" => UnitValue::Ms,
+      "dpi" => UnitValue::Dpi,
+      "dpcm" => UnitValue::Dpcm,
+      "dppx" | "x" => UnitValue::Dppx,
+      _ => return None,
+    };
+    Some(unit)
+  }
+
+  pub fn is_length(&self) -> bool {
+    !self.is_angle() && !self.is_time() && !self.is_resolution() && *self != UnitValue::Percent
+  }
+
+  pub fn is_angle(&self) -> bool {
+    matches!(
+      self,
+      UnitValue::Deg | UnitValue::Grad | UnitValue::Rad | UnitValue::Turn
+    )
+  }
+
+  pub fn is_time(&self) -> bool {
+    matches!(self, UnitValue::S | UnitValue::Ms)
+  }
+
+  pub fn is_resolution(&self) -> bool {
+    matches!(self, UnitValue::Dpi | UnitValue::Dpcm | UnitValue::Dppx)
+  }
+
+  /// px per unit for absolute lengths, where 1in is 96px.
+  // https://www.w3.org/TR/css-values-4/#absolute-lengths
+  pub fn absolute_px(&self) -> Option<f32> {
+    let px = match self {
+      UnitValue::Px => 1.0,
+      UnitValue::In => 96.0,
+      UnitValue::Cm => 96.0 / 2.54,
+      UnitValue::Mm => 96.0 / 25.4,
+      UnitValue::Q => 96.0 / 101.6,
+      UnitValue::Pt => 96.0 / 72.0,
+      UnitValue::Pc => 16.0,
+      _ => return None,
+    };
+    Some(px)
+  }
+}
+
+impl fmt::Display for DeclarationValue {
+  fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      DeclarationValue::Keyword(keyword) => write!(formatter, "{}", keyword),
+      DeclarationValue::Length(value, unit) => write!(formatter, "{}{}", value, unit),
+      DeclarationValue::Number(value) => write!(formatter, "{}", value),
+      DeclarationValue::Integer(value) => write!(formatter, "{}", value),
+      DeclarationValue::ColorValue(color) => write!(formatter, "{}", color),
+      DeclarationValue::List(values) => {
+        for (index, value) in values.iter().enumerate() {
+          let after_slash = index > 0 && values[index - 1] == DeclarationValue::Separator('/');
+          if index > 0 && !after_slash && !matches!(value, DeclarationValue::Separator(_)) {
+            write!(formatter, " ")?;
+          }
+          write!(formatter, "{}", value)?;
+        }
+        Ok(())
+      }
+      DeclarationValue::Separator(separator) => write!(formatter, "{}", separator),
+      DeclarationValue::Content(items) => {
+        let items: Vec<String> = items.iter().map(|item| item.to_string()).collect();
+        write!(formatter, "{}", items.join(" "))
+      }
+    }
+  }
+}
+
+impl fmt::Display for ContentItem {
+  fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+    let string = |value: &str| Token::String(value.to_string());
+    match self {
+      ContentItem::String(value) => write!(formatter, "{}", string(value)),
+      ContentItem::Attr(name) => write!(formatter, "attr({})", name),
+      ContentItem::Counter(name, style) => write!(formatter, "counter({}, {})", name, style),
+      ContentItem::Counters(name, separator, style) => {
+        write!(formatter, "counters({}, {}, {})", name, string(separator), style)
+      }
+      ContentItem::OpenQuote => write!(formatter, "open-quote"),
+      ContentItem::CloseQuote => write!(formatter, "close-quote"),
+      ContentItem::NoOpenQuote => write!(formatter, "no-open-quote"),
+      ContentItem::NoCloseQuote => write!(formatter, "no-close-quote"),
+    }
+  }
+}
+
+impl fmt::Display for NamespaceConstraint {
+  fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      NamespaceConstraint::Any => write!(formatter, "*|"),
+      NamespaceConstraint::None => write!(formatter, "|"),
+      NamespaceConstraint::Prefix(prefix, _) => write!(formatter, "{}|", prefix),
+      NamespaceConstraint::Default(_) => Ok(()),
+    }
+  }
+}
+
+impl fmt::Display for PseudoElement {
+  fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+    let name = match self {
+      PseudoElement::Before => "before",
+      PseudoElement::After => "after",
+      PseudoElement::Marker => "marker",
+      PseudoElement::FirstLine => "first-line",
+      PseudoElement::FirstLetter => "first-letter",
+    };
+    write!(formatter, "::{}", name)
+  }
+}
+
+impl fmt::Display for Selector {
+  fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      Selector::Simple(simple_selector) => write!(formatter, "{}", simple_selector),
+      Selector::Complex { compounds, subject } => {
+        for (compound, combinator) in compounds {
+          write!(formatter, "{}{}", compound, combinator)?;
+        }
+        write!(formatter, "{}", subject)
+      }
+    }
+  }
+}
+
+impl fmt::Display for Combinator {
+  fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+    let combinator = match self {
+      Combinator::Descendant => " ",
+      Combinator::Child => " > ",
+      Combinator::NextSibling => " + ",
+      Combinator::SubsequentSibling => " ~ ",
+    };
+    write!(formatter, "{}", combinator)
+  }
+}
+
+impl fmt::Display for SimpleSelector {
+  fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+    if let Some(namespace) = &self.namespace {
+      write!(formatter, "{}", namespace)?;
+    }
+    let prefixed = !matches!(self.namespace, None | Some(NamespaceConstraint::Default(_)));
+    let is_empty = self.id.is_none()
+      && self.class.is_empty()
+      && self.attributes.is_empty()
+      && self.pseudo_classes.is_empty()
+      && self.pseudo_element.is_none();
+    match &self.tag_name {
+      Some(tag_name) => write!(formatter, "{}", tag_name)?,
+      // `*` may only be left out before other simple selectors.
+      None if prefixed || is_empty => write!(formatter, "*")?,
+      None => {}
+    }
+    if let Some(id) = &self.id {
+      write!(formatter, "#{}", id)?;
+    }
+    for class in &self.class {
+      write!(formatter, ".{}", class)?;
+    }
+    for attribute in &self.attributes {
+      write!(formatter, "{}", attribute)?;
+    }
+    for pseudo_class in &self.pseudo_classes {
+      write!(formatter, "{}", pseudo_class)?;
+    }
+    if let Some(pseudo_element) = &self.pseudo_element {
+      write!(formatter, "{}", pseudo_element)?;
+    }
+    Ok(())
+  }
+}
+
+impl fmt::Display for PseudoClass {
+  fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+    let list = |selectors: &[Selector]| {
+      let selectors: Vec<String> = selectors.iter().map(|selector| selector.to_string()).collect();
+      selectors.join(", ")
+    };
+    let nth_child = |nth: &Nth, of: &Option<Vec<Selector>>| match of {
+      Some(selectors) => format!("{} of {}", nth, list(selectors)),
+      None => nth.to_string(),
+    };
+    match self {
+      PseudoClass::FirstChild => write!(formatter, ":first-child"),
+      PseudoClass::LastChild => write!(formatter, ":last-child"),
+      PseudoClass::OnlyChild => write!(formatter, ":only-child"),
+      PseudoClass::FirstOfType => write!(formatter, ":first-of-type"),
+      PseudoClass::LastOfType => write!(formatter, ":last-of-type"),
+      PseudoClass::OnlyOfType => write!(formatter, ":only-of-type"),
+      PseudoClass::NthChild(nth, of) => write!(formatter, ":nth-child({})", nth_child(nth, of)),
+      PseudoClass::NthLastChild(nth, of) => write!(formatter, ":nth-last-child({})", nth_child(nth, of)),
+      PseudoClass::NthOfType(nth) => write!(formatter, ":nth-of-type({})", nth),
+      PseudoClass::NthLastOfType(nth) => write!(formatter, ":nth-last-of-type({})", nth),
+      PseudoClass::Empty => write!(formatter, ":empty"),
+      PseudoClass::Root => write!(formatter, ":root"),
+      PseudoClass::Hover => write!(formatter, ":hover"),
+      PseudoClass::Active => write!(formatter, ":active"),
+      PseudoClass::Focus => write!(formatter, ":focus"),
+      PseudoClass::FocusWithin => write!(formatter, ":focus-within"),
+      PseudoClass::FocusVisible => write!(formatter, ":focus-visible"),
+      PseudoClass::Checked => write!(formatter, ":checked"),
+      PseudoClass::Disabled => write!(formatter, ":disabled"),
+      PseudoClass::Visited => write!(formatter, ":visited"),
+      PseudoClass::Target => write!(formatter, ":target"),
+      PseudoClass::Not(selectors) => write!(formatter, ":not({})", list(selectors)),
+      PseudoClass::Is(selectors) => write!(formatter, ":is({})", list(selectors)),
+      PseudoClass::Where(selectors) => write!(formatter, ":where({})", list(selectors)),
+      PseudoClass::Has(relative_selectors) => {
+        let relative_selectors: Vec<String> = relative_selectors
+          .iter()
+          .map(|(combinator, selector)| format!("{}{}", combinator.to_string().trim_start(), selector))
+          .collect();
+        write!(formatter, ":has({})", relative_selectors.join(", "))
+      }
+    }
+  }
+}
+
+// https://www.w3.org/TR/css-syntax-3/#serializing-anb
+impl fmt::Display for Nth {
+  fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+    match self.a {
+      0 => return write!(formatter, "{}", self.b),
+      1 => write!(formatter, "n")?,
+      -1 => write!(formatter, "-n")?,
+      a => write!(formatter, "{}n", a)?,
+    }
+    match self.b {
+      0 => Ok(()),
+      b => write!(formatter, "{:+}", b),
+    }
+  }
+}
+
+impl fmt::Display for AttributeSelector {
+  fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+    let name = match &self.namespace {
+      Some(namespace) => format!("{}{}", namespace, self.name),
+      None => self.name.clone(),
+    };
+    let operator = match self.operator {
+      AttributeOperator::Exists => return write!(formatter, "[{}]", name),
+      AttributeOperator::Equals => "=",
+      AttributeOperator::Includes => "~=",
+      AttributeOperator::DashMatch => "|=",
+      AttributeOperator::Prefix => "^=",
+      AttributeOperator::Suffix => "$=",
+      AttributeOperator::Substring => "*=",
+    };
+    let value = self.value.replace('\\', "\\\\").replace('"', "\\\"");
+    let flag = match self.case_sensitivity {
+      CaseSensitivity::Default => "",
+      CaseSensitivity::Insensitive => " i",
+      CaseSensitivity::Sensitive => " s",
+    };
+    write!(formatter, "[{}{}\"{}\"{}]", name, operator, value, flag)
+  }
+}
+
+impl fmt::Display for ComponentValue {
+  fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      ComponentValue::Token(token) => write!(formatter, "{}", token),
+      ComponentValue::Function { name, arguments } => {
+        write!(formatter, "{}(", name)?;
+        for argument in arguments {
+          write!(formatter, "{}", argument)?;
+        }
+        write!(formatter, ")")
+      }
+      ComponentValue::Block { opening, values } => {
+        write!(formatter, "{}", opening)?;
+        for value in values {
+          write!(formatter, "{}", value)?;
+        }
+        let closing = match opening {
+          '{' => '}',
+          '[' => ']',
+          _ => ')',
+        };
+        write!(formatter, "{}", closing)
+      }
+    }
+  }
+}
+
+impl fmt::Display for UnitValue {
+  fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+    let unit = match self {
+      UnitValue::Px => "px",
+      UnitValue::Em => "em",
+      UnitValue::Rem => "rem",
+      UnitValue::Percent => "%",
+      UnitValue::Ex => "ex",
+      UnitValue::Ch => "ch",
+      UnitValue::Vw => "vw",
+      UnitValue::Vh => "vh",
+      UnitValue::Vmin => "vmin",
+      UnitValue::Vmax => "vmax",
+      UnitValue::Cm => "cm",
+      UnitValue::Mm => "mm",
+      UnitValue::Q => "Q",
+      UnitValue::In => "in",
+      UnitValue::Pt => "pt",
+      UnitValue::Pc => "pc",
+      UnitValue::Deg => "deg",
+      UnitValue::Grad => "grad",
+      UnitValue::Rad => "rad",
+      UnitValue::Turn => "turn",
+      UnitValue::S => "s",
+      UnitValue::Ms => "ms",
+      UnitValue::Dpi => "dpi",
+      UnitValue::Dpcm => "dpcm",
+      UnitValue::Dppx => "dppx",
+    };
+    write!(formatter, "{}", unit)
+  }
+}
+
+impl fmt::Display for ColorValue {
+  fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      // https://www.w3.org/TR/css-color-4/#serializing-sRGB-values
+      ColorValue::RBGColorValue(r, g, b, 255) => write!(formatter, "rgb({}, {}, {})", r, g, b),
+      ColorValue::RBGColorValue(r, g, b, a) => write!(formatter, "rgba({}, {}, {}, {})", r, g, b, serialize_alpha(*a)),
+      ColorValue::CurrentColor => write!(formatter, "currentcolor"),
+    }
+  }
+}
+
+// The shortest of 2 or 3 decimals that maps back to the same alpha byte.
+fn serialize_alpha(alpha: u8) -> String {
+  let value = alpha as f32 / 255.0;
+  let rounded = (value * 100.0).round() / 100.0;
+  if (rounded * 255.0).round() as u8 == alpha {
+    return rounded.to_string();
+  }
+  ((value * 1000.0).round() / 1000.0).to_string()
+}
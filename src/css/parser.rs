@@ -1,246 +1,882 @@
 #![allow(dead_code)]
-use crate::css;
-use crate::utils::validation::is_tag_char;
+use crate::css::{
+  self, expand_shorthand, is_color_function, parse_color, tokenize, tokenize_with_preserved_comments, ComponentValue,
+  Token,
+};
+use crate::diagnostics::Diagnostics;
 // ==============================
 // https://www.w3.org/TR/css-syntax-3/#tokenizing-and-parsing
+// https://www.w3.org/TR/css-syntax-3/#error-handling
+// Invalid input never aborts parsing: the invalid rule, declaration or at-rule is dropped and
+// reported in `diagnostics`.
 //
 
 pub struct CSSParser {
-  input: String,
+  tokens: Vec<Token>,
   cursor: usize,
+  // preserved comments, by the index of the token they come before.
+  comments: Vec<(usize, String)>,
+  pub diagnostics: Diagnostics,
+}
+
+// An at-rule such as `@media screen { ... }` or `@import "a.css";`.
+struct AtRule {
+  name: String,
+  prelude: Vec<ComponentValue>,
+  // the contents of the `{}` block, if the rule has one.
+  block: Option<Vec<ComponentValue>>,
 }
 
 impl CSSParser {
   pub fn new(input: String) -> Self {
-    CSSParser { input: input.trim().to_string(), cursor: 0 }
+    CSSParser { tokens: tokenize(&input), cursor: 0, comments: Vec::new(), diagnostics: Diagnostics::new() }
+  }
+
+  /// A parser that keeps `/*! ... */` comments, e.g. licenses, in `StyleSheet::comments`.
+  pub fn with_preserved_comments(input: String) -> Self {
+    let (tokens, comments) = tokenize_with_preserved_comments(&input);
+    CSSParser { tokens, cursor: 0, comments, diagnostics: Diagnostics::new() }
   }
 }
 
 impl CSSParser {
   pub fn parse_syle_sheet(&mut self) -> css::StyleSheet {
-    return css::StyleSheet { rules: self.parse_rules() };
+    let mut style_sheet =
+      css::StyleSheet { rules: Vec::new(), comments: Vec::new(), origin: css::Origin::Author, namespaces: Vec::new() };
+    self.parse_rules(&mut style_sheet);
+    style_sheet
   }
 
-  fn parse_rules(&mut self) -> Vec<css::Rule> {
-    let mut rules = Vec::new();
-    while !self.is_end() {
-      rules.push(self.parse_rule());
+  /// Parse the contents of a `style` attribute, a list of declarations without braces.
+  pub fn parse_declaration_list(&mut self) -> Vec<css::Declaration> {
+    let mut values = Vec::new();
+    while let Some(value) = self.consume_component_value() {
+      values.push(value);
     }
-    rules
+    self.parse_declarations(&values)
   }
-  // Parse a rule set: `<selectors> { <declarations> }`.
-  fn parse_rule(&mut self) -> css::Rule {
-    css::Rule { selectors: self.parse_selectors(), declarations: self.parse_declarations() }
+
+  // https://www.w3.org/TR/css-syntax-3/#consume-list-of-rules
+  fn parse_rules(&mut self, style_sheet: &mut css::StyleSheet) {
+    while self.peek().is_some() {
+      self.attach_comments(self.cursor, style_sheet);
+      match self.peek().unwrap() {
+        Token::Whitespace | Token::CDO | Token::CDC => self.cursor += 1,
+        // todo: support more at-rules; for now the others are skipped.
+        Token::AtKeyword(_) => match self.consume_at_rule() {
+          Some(at_rule) if at_rule.name.eq_ignore_ascii_case("namespace") => {
+            self.parse_namespace_rule(&at_rule, style_sheet)
+          }
+          Some(at_rule) => self
+            .diagnostics
+            .add_warning(format!("Skipped unsupported at-rule '@{}'", at_rule.name)),
+          None => {}
+        },
+        _ => style_sheet.rules.extend(self.parse_rule(&style_sheet.namespaces)),
+      }
+    }
+    self.attach_comments(self.tokens.len(), style_sheet);
   }
-  // simple selector, e.g.: `type#id.class1.class2.class3`
-  fn parse_simple_selector(&mut self) -> css::SimpleSelector {
-    let mut simple_selector = css::SimpleSelector::new();
-    while !self.is_end() {
-      match self.peek_one() {
-        '.' => {
-          self.consume_expect(".");
-          simple_selector.class.push(self.parse_identifier());
-        }
-        '#' => {
-          self.consume_expect("#");
-          simple_selector.id = Some(self.parse_identifier());
-        }
-        character if is_tag_char(character) => {
-          simple_selector.tag_name = Some(self.parse_identifier());
+
+  // Move preserved comments up to token `end` to the style sheet, before its next rule. Comments
+  // inside a rule end up after it.
+  fn attach_comments(&mut self, end: usize, style_sheet: &mut css::StyleSheet) {
+    let count = self.comments.iter().take_while(|(index, _)| *index <= end).count();
+    let rule_index = style_sheet.rules.len();
+    let comments = self.comments.drain(..count);
+    style_sheet
+      .comments
+      .extend(comments.map(|(_, text)| css::Comment { text, rule_index }));
+  }
+
+  // `@namespace <prefix>? <url>;`, which must come before the style rules.
+  // https://www.w3.org/TR/css-namespaces-3/#syntax
+  fn parse_namespace_rule(&mut self, at_rule: &AtRule, style_sheet: &mut css::StyleSheet) {
+    let prelude = trim_whitespace(&at_rule.prelude);
+    let invalid = |reason: &str| {
+      format!(
+        "Dropped invalid @namespace rule '@namespace {}': {}",
+        serialize(prelude),
+        reason
+      )
+    };
+    if !style_sheet.rules.is_empty() {
+      return self.diagnostics.add_error(invalid("after style rules"));
+    }
+    if at_rule.block.is_some() {
+      return self.diagnostics.add_error(invalid("unexpected block"));
+    }
+    let (prefix, url) = match prelude {
+      [url] => (None, url),
+      [ComponentValue::Token(Token::Ident(prefix)), rest @ ..] => match trim_whitespace(rest) {
+        [url] => (Some(prefix.clone()), url),
+        _ => return self.diagnostics.add_error(invalid("expected a prefix and a URL")),
+      },
+      _ => return self.diagnostics.add_error(invalid("expected a prefix and a URL")),
+    };
+    let url = match url {
+      ComponentValue::Token(Token::String(url) | Token::Url(url)) => url.clone(),
+      ComponentValue::Function { name, arguments } if name.eq_ignore_ascii_case("url") => {
+        match trim_whitespace(arguments) {
+          [ComponentValue::Token(Token::String(url))] => url.clone(),
+          _ => return self.diagnostics.add_error(invalid("expected a URL")),
         }
-        _ => break,
       }
+      _ => return self.diagnostics.add_error(invalid("expected a URL")),
+    };
+    // a later rule for the same prefix replaces the earlier one.
+    style_sheet.namespaces.retain(|namespace| namespace.prefix != prefix);
+    style_sheet.namespaces.push(css::NamespaceRule { prefix, url });
+  }
+
+  // Parse a rule set: `<selectors> { <declarations> }`. Rules with invalid selectors, including
+  // those with a namespace prefix missing from `namespaces`, are dropped.
+  fn parse_rule(&mut self, namespaces: &[css::NamespaceRule]) -> Option<css::Rule> {
+    let Some((prelude, block)) = self.consume_qualified_rule() else {
+      self
+        .diagnostics
+        .add_error("Unexpected end of input in rule prelude".to_string());
+      return None;
+    };
+    let Some(selectors) = parse_selector_list(&prelude, namespaces) else {
+      let selectors = serialize(trim_whitespace(&prelude));
+      self
+        .diagnostics
+        .add_error(format!("Dropped rule with invalid selector '{}'", selectors));
+      return None;
+    };
+    Some(css::Rule { selectors, declarations: self.parse_declarations(&block) })
+  }
+
+  // The prelude and block of a rule, or `None` if the input ends before the block.
+  // https://www.w3.org/TR/css-syntax-3/#consume-qualified-rule
+  fn consume_qualified_rule(&mut self) -> Option<(Vec<ComponentValue>, Vec<ComponentValue>)> {
+    let mut prelude = Vec::new();
+    loop {
+      if self.peek()? == &Token::OpenCurly {
+        self.cursor += 1;
+        return Some((prelude, self.consume_block_contents(&Token::CloseCurly)));
+      }
+      prelude.push(self.consume_component_value()?);
     }
-    simple_selector
   }
 
-  fn parse_selectors(&mut self) -> Vec<css::Selector> {
-    let mut selectors = Vec::new();
-    while !self.is_end() {
-      selectors.push(self.parse_selector());
-      self.skip_whitespace();
-      match self.peek_one() {
-        ',' => {
-          self.consume_expect(",");
-          self.skip_whitespace();
+  // https://www.w3.org/TR/css-syntax-3/#consume-at-rule
+  fn consume_at_rule(&mut self) -> Option<AtRule> {
+    let Some(Token::AtKeyword(name)) = self.consume() else {
+      return None;
+    };
+    let mut at_rule = AtRule { name, prelude: Vec::new(), block: None };
+    while let Some(token) = self.peek() {
+      match token {
+        Token::Semicolon => {
+          self.cursor += 1;
+          break;
         }
-        // start a declaration block
-        '{' => break,
-        character => {
-          panic!("Unexpected character '{}' in selector list", character);
+        Token::OpenCurly => {
+          self.cursor += 1;
+          at_rule.block = Some(self.consume_block_contents(&Token::CloseCurly));
+          break;
         }
+        _ => at_rule.prelude.extend(self.consume_component_value()),
       }
     }
-    // return selectors with highest specificity first, for use in matching.
-    selectors.sort_by(|left, right| right.specificity().cmp(&left.specificity()));
-    selectors
+    Some(at_rule)
   }
 
-  /// Parse a list of declarations enclosed in `{ ... }`.
-  fn parse_declarations(&mut self) -> Vec<css::Declaration> {
-    self.consume_expect("{");
-    let mut declarations = Vec::new();
-    while !self.is_end() && !self.starts_with("}") {
-      self.skip_whitespace();
-      declarations.push(self.parse_declaration());
-      self.skip_whitespace();
-    }
-    self.consume_expect("}");
-    declarations
-  }
-  /// Parse one `<property>: <value>;` declaration.
-  fn parse_declaration(&mut self) -> css::Declaration {
-    let property_name = self.parse_identifier();
-    self.skip_whitespace();
-    self.consume_expect(":");
-    self.skip_whitespace();
-    let value = self.parse_value();
-    self.skip_whitespace();
-    self.consume_expect(";");
-    css::Declaration { name: property_name, value }
+  // https://www.w3.org/TR/css-syntax-3/#consume-component-value
+  fn consume_component_value(&mut self) -> Option<ComponentValue> {
+    let value = match self.consume()? {
+      Token::OpenCurly => {
+        ComponentValue::Block { opening: '{', values: self.consume_block_contents(&Token::CloseCurly) }
+      }
+      Token::OpenSquare => {
+        ComponentValue::Block { opening: '[', values: self.consume_block_contents(&Token::CloseSquare) }
+      }
+      Token::OpenParen => {
+        ComponentValue::Block { opening: '(', values: self.consume_block_contents(&Token::CloseParen) }
+      }
+      Token::Function(name) => {
+        ComponentValue::Function { name, arguments: self.consume_block_contents(&Token::CloseParen) }
+      }
+      token => ComponentValue::Token(token),
+    };
+    Some(value)
   }
 
-  fn parse_value(&mut self) -> css::DeclarationValue {
-    // --- color
-    if self.peek_one() == '#' || self.peek_many(4) == "rgb(" || self.peek_many(5) == "rgba(" {
-      return self.parse_color();
+  // Component values up to the `closing` token, which is consumed. Unclosed blocks end with the input.
+  fn consume_block_contents(&mut self, closing: &Token) -> Vec<ComponentValue> {
+    let mut values = Vec::new();
+    while let Some(token) = self.peek() {
+      if token == closing {
+        self.cursor += 1;
+        return values;
+      }
+      values.extend(self.consume_component_value());
     }
-    // ---
-    let value = self.parse_identifier();
-    return css::DeclarationValue::Keyword(value);
+    self
+      .diagnostics
+      .add_warning(format!("Unclosed block, expected '{}'", closing));
+    values
   }
 
-  pub fn parse_hex_color(&mut self) -> css::ColorValue {
-    self.consume_expect("#");
-    let hex_color = self.consume_while(|character| character != ';');
-    return css::ColorValue::HexColorValue(hex_color);
+  // https://www.w3.org/TR/css-syntax-3/#consume-list-of-declarations
+  fn parse_declarations(&mut self, values: &[ComponentValue]) -> Vec<css::Declaration> {
+    let mut declarations = Vec::new();
+    let mut index = 0;
+    while index < values.len() {
+      match &values[index] {
+        ComponentValue::Token(Token::Whitespace | Token::Semicolon) => index += 1,
+        // nested at-rules run up to a `;` or the end of their block.
+        ComponentValue::Token(Token::AtKeyword(name)) => {
+          self
+            .diagnostics
+            .add_warning(format!("Skipped unsupported at-rule '@{}' in declarations", name));
+          let end = values[index..].iter().position(|value| {
+            matches!(
+              value,
+              ComponentValue::Token(Token::Semicolon) | ComponentValue::Block { opening: '{', .. }
+            )
+          });
+          index = end.map(|end| index + end + 1).unwrap_or(values.len());
+        }
+        // invalid declarations are dropped up to the next `;`.
+        _ => {
+          let end = values[index..]
+            .iter()
+            .position(|value| *value == ComponentValue::Token(Token::Semicolon))
+            .map(|end| index + end)
+            .unwrap_or(values.len());
+          match parse_declaration(&values[index..end]) {
+            Ok(longhands) => declarations.extend(longhands),
+            Err(message) => self.diagnostics.add_error(message),
+          }
+          index = end;
+        }
+      }
+    }
+    declarations
   }
 
-  fn parrse_rgb_color(&mut self) -> css::ColorValue {
-    self.consume_expect("#");
-    let r = self.parse_hex_pair();
-    let g = self.parse_hex_pair();
-    let b = self.parse_hex_pair();
-    return css::ColorValue::RBGColorValue(r, g, b, 255);
+  fn peek(&self) -> Option<&Token> {
+    self.tokens.get(self.cursor)
   }
 
-  fn parse_value_length(&mut self) -> css::DeclarationValue {
-    css::DeclarationValue::Length(self.parse_float(), self.parse_unit())
+  fn consume(&mut self) -> Option<Token> {
+    let token = self.tokens.get(self.cursor).cloned()?;
+    self.cursor += 1;
+    Some(token)
   }
+}
 
-  fn parse_unit(&mut self) -> css::UnitValue {
-    let unit = self.parse_identifier();
-    match &*unit.to_ascii_lowercase() {
-      "px" => css::UnitValue::Px,
-      "em" => css::UnitValue::Em,
-      "rem" => css::UnitValue::Rem,
-      _ => panic!("Unknown unit '{}'", unit),
-    }
+/// Parse one `<property>: <value>` declaration, without its `;`. Shorthands are expanded to their
+/// longhands.
+fn parse_declaration(values: &[ComponentValue]) -> Result<Vec<css::Declaration>, String> {
+  let values = trim_whitespace(values);
+  let invalid = |reason: &str| format!("Dropped invalid declaration '{}': {}", serialize(values), reason);
+  let [ComponentValue::Token(Token::Ident(name)), rest @ ..] = values else {
+    return Err(invalid("expected a property name"));
+  };
+  let [ComponentValue::Token(Token::Colon), value @ ..] = trim_whitespace(rest) else {
+    return Err(invalid("expected ':'"));
+  };
+  let (value, important) = split_important(trim_whitespace(value));
+  if value.is_empty() {
+    return Err(invalid("empty value"));
   }
+  let value = parse_value(name, value).map_err(|reason| invalid(&reason))?;
+  let mut declarations = expand_shorthand(name, value).map_err(|reason| invalid(&reason))?;
+  for declaration in declarations.iter_mut() {
+    declaration.important = important;
+  }
+  Ok(declarations)
+}
 
-  fn parse_float(&mut self) -> f32 {
-    let float = self.consume_while(|character| match character {
-      '0'..='9' | '.' => true,
-      _ => false,
-    });
-    float.parse().unwrap()
+// The value without a trailing `!important`, and whether it had one.
+// https://www.w3.org/TR/css-syntax-3/#consume-declaration
+fn split_important(values: &[ComponentValue]) -> (&[ComponentValue], bool) {
+  let [rest @ .., ComponentValue::Token(Token::Ident(important))] = values else {
+    return (values, false);
+  };
+  if !important.eq_ignore_ascii_case("important") {
+    return (values, false);
   }
+  match trim_whitespace(rest) {
+    [rest @ .., ComponentValue::Token(Token::Delim('!'))] => (trim_whitespace(rest), true),
+    _ => (values, false),
+  }
+}
 
-  // todo: support other color formats, e.g. hex, rgba, hsla...
-  fn parse_color(&mut self) -> css::DeclarationValue {
-    match self.peek_one() {
-      '#' => {
-        let value = self.parse_hex_color();
-        return css::DeclarationValue::ColorValue(value);
+fn parse_value(name: &str, values: &[ComponentValue]) -> Result<css::DeclarationValue, String> {
+  match invalid_token(values) {
+    Some(Token::BadString) => return Err("unterminated string".to_string()),
+    Some(Token::BadUrl) => return Err("invalid url()".to_string()),
+    Some(token) => return Err(format!("unexpected '{}'", token)),
+    None => {}
+  }
+  if values.contains(&ComponentValue::Token(Token::Delim('!'))) {
+    return Err("unexpected '!'".to_string());
+  }
+  match &*name.to_ascii_lowercase() {
+    "content" => {
+      if let Some(items) = parse_content(values)? {
+        return Ok(css::DeclarationValue::Content(items));
       }
-      _ => {
-        let value = self.parse_identifier();
-        return css::DeclarationValue::Keyword(value);
+    }
+    "quotes" => {
+      if let Some(strings) = parse_quotes(values)? {
+        return Ok(css::DeclarationValue::Content(strings));
       }
     }
+    _ => {}
   }
-
-  /// Parse two hexadecimal digits.
-  fn parse_hex_pair(&mut self) -> u8 {
-    let value = &self.input[self.cursor..self.cursor + 2];
-    self.cursor += 2;
-    u8::from_str_radix(value, 16).unwrap()
+  if let [value] = values {
+    return parse_component(name, value);
   }
-  fn parse_length(&mut self) -> f32 {
-    let value = self.parse_number();
-    value.parse::<f32>().unwrap()
+  // a list, with `,` and `/` kept as separators.
+  let mut list = Vec::new();
+  for value in values {
+    let item = match value {
+      ComponentValue::Token(Token::Whitespace) => continue,
+      ComponentValue::Token(Token::Comma) => css::DeclarationValue::Separator(','),
+      ComponentValue::Token(Token::Delim('/')) => css::DeclarationValue::Separator('/'),
+      value => parse_component(name, value)?,
+    };
+    list.push(item);
   }
+  Ok(css::DeclarationValue::List(list))
+}
 
-  fn parse_number(&mut self) -> String {
-    let mut number = String::new();
-    while !self.is_end() {
-      match self.peek_one() {
-        '0'..='9' => number.push(self.consume()),
-        _ => break,
-      }
+fn parse_component(name: &str, value: &ComponentValue) -> Result<css::DeclarationValue, String> {
+  let value = match value {
+    // --- color
+    ComponentValue::Token(Token::Hash { .. }) => css::DeclarationValue::ColorValue(parse_color(value)?),
+    ComponentValue::Function { name, .. } if is_color_function(name) => {
+      css::DeclarationValue::ColorValue(parse_color(value)?)
     }
-    number
-  }
+    // color keywords only in color properties; elsewhere `red` may be e.g. an animation name.
+    ComponentValue::Token(Token::Ident(_)) if is_color_property(name) && parse_color(value).is_ok() => {
+      css::DeclarationValue::ColorValue(parse_color(value)?)
+    }
+    // ---
+    ComponentValue::Token(token @ (Token::Number { .. } | Token::Percentage(_) | Token::Dimension { .. })) => {
+      parse_numeric(token)?
+    }
+    ComponentValue::Token(Token::Ident(keyword)) => css::DeclarationValue::Keyword(keyword.clone()),
+    // strings, urls and other functions are kept as written.
+    value => css::DeclarationValue::Keyword(value.to_string()),
+  };
+  Ok(value)
+}
 
-  // todo: support more complex selectors.
-  fn parse_selector(&mut self) -> css::Selector {
-    let simple_selector = self.parse_simple_selector();
-    css::Selector::Simple(simple_selector)
+// The items of a `content` value, or `None` for a single keyword such as `none` or `normal`.
+// https://www.w3.org/TR/css-content-3/#content-property
+fn parse_content(values: &[ComponentValue]) -> Result<Option<Vec<css::ContentItem>>, String> {
+  if let [ComponentValue::Token(Token::Ident(keyword))] = values {
+    let keyword = keyword.to_ascii_lowercase();
+    if keyword == "normal" || keyword == "none" || css::CSS_WIDE_KEYWORDS.contains(&&*keyword) {
+      return Ok(None);
+    }
   }
-
-  fn parse_identifier(&mut self) -> String {
-    return self.consume_while(is_tag_char);
+  let mut items = Vec::new();
+  for value in values {
+    let unexpected = || format!("unexpected '{}'", value);
+    let item = match value {
+      ComponentValue::Token(Token::Whitespace) => continue,
+      ComponentValue::Token(Token::String(string)) => css::ContentItem::String(string.clone()),
+      ComponentValue::Token(Token::Ident(keyword)) => match &*keyword.to_ascii_lowercase() {
+        "open-quote" => css::ContentItem::OpenQuote,
+        "close-quote" => css::ContentItem::CloseQuote,
+        "no-open-quote" => css::ContentItem::NoOpenQuote,
+        "no-close-quote" => css::ContentItem::NoCloseQuote,
+        _ => return Err(unexpected()),
+      },
+      ComponentValue::Function { name, arguments } => {
+        let arguments = arguments.split(|value| *value == ComponentValue::Token(Token::Comma));
+        let arguments: Vec<_> = arguments.map(trim_whitespace).collect();
+        match (&*name.to_ascii_lowercase(), arguments.as_slice()) {
+          ("attr", [[ComponentValue::Token(Token::Ident(name))]]) => css::ContentItem::Attr(name.clone()),
+          ("counter", [[ComponentValue::Token(Token::Ident(name))], style @ ..]) => {
+            css::ContentItem::Counter(name.clone(), parse_counter_style(style).ok_or_else(unexpected)?)
+          }
+          (
+            "counters",
+            [[ComponentValue::Token(Token::Ident(name))], [ComponentValue::Token(Token::String(separator))], style @ ..],
+          ) => css::ContentItem::Counters(
+            name.clone(),
+            separator.clone(),
+            parse_counter_style(style).ok_or_else(unexpected)?,
+          ),
+          _ => return Err(unexpected()),
+        }
+      }
+      _ => return Err(unexpected()),
+    };
+    items.push(item);
   }
+  Ok(Some(items))
+}
 
-  fn skip_whitespace(&mut self) {
-    self.consume_while(char::is_whitespace);
+// The optional style argument of `counter()` and `counters()`, `decimal` by default.
+fn parse_counter_style(arguments: &[&[ComponentValue]]) -> Option<String> {
+  match arguments {
+    [] => Some("decimal".to_string()),
+    [[ComponentValue::Token(Token::Ident(style))]] => Some(style.to_ascii_lowercase()),
+    _ => None,
   }
+}
 
-  fn consume_while<F: Fn(char) -> bool>(&mut self, test: F) -> String {
-    let start_cursor = self.cursor;
-    while !self.is_end() && test(self.peek_one()) {
-      self.advance_one()
+// The strings of a `quotes` value, or `None` for `auto` or `none`.
+// https://www.w3.org/TR/css-content-3/#quotes-property
+fn parse_quotes(values: &[ComponentValue]) -> Result<Option<Vec<css::ContentItem>>, String> {
+  if let [ComponentValue::Token(Token::Ident(_))] = values {
+    return Ok(None);
+  }
+  let mut strings = Vec::new();
+  for value in values {
+    match value {
+      ComponentValue::Token(Token::Whitespace) => continue,
+      ComponentValue::Token(Token::String(string)) => strings.push(css::ContentItem::String(string.clone())),
+      value => return Err(format!("unexpected '{}'", value)),
     }
-    return self.input[start_cursor..self.cursor].to_string();
   }
-
-  fn is_end(&self) -> bool {
-    self.cursor >= self.input.len()
+  if strings.len() % 2 != 0 {
+    return Err("expected pairs of strings".to_string());
   }
+  Ok(Some(strings))
+}
+
+// A number, percentage or dimension; dimensions must have a known unit.
+fn parse_numeric(token: &Token) -> Result<css::DeclarationValue, String> {
+  let value = match token {
+    Token::Number { value, is_integer: true } => css::DeclarationValue::Integer(*value as i32),
+    Token::Number { value, .. } => css::DeclarationValue::Number(*value),
+    Token::Percentage(value) => css::DeclarationValue::Length(*value, css::UnitValue::Percent),
+    Token::Dimension { value, unit, .. } => match css::UnitValue::parse(unit) {
+      Some(unit) => css::DeclarationValue::Length(*value, unit),
+      None => return Err(format!("unknown unit '{}'", unit)),
+    },
+    token => return Err(format!("expected a number, got '{}'", token)),
+  };
+  Ok(value)
+}
+
+// Tokens that are never valid in a declaration value, at any depth.
+fn invalid_token(values: &[ComponentValue]) -> Option<&Token> {
+  values.iter().find_map(|value| match value {
+    ComponentValue::Token(
+      token @ (Token::BadString | Token::BadUrl | Token::CloseCurly | Token::CloseParen | Token::CloseSquare),
+    ) => Some(token),
+    ComponentValue::Token(_) => None,
+    ComponentValue::Function { arguments: values, .. } | ComponentValue::Block { values, .. } => invalid_token(values),
+  })
+}
+
+fn is_color_property(name: &str) -> bool {
+  let name = name.to_ascii_lowercase();
+  name == "color" || name.ends_with("-color") || matches!(&*name, "fill" | "stroke")
+}
+
+// Comma-separated selectors in source order; invalid if any of them is.
+fn parse_selector_list(values: &[ComponentValue], namespaces: &[css::NamespaceRule]) -> Option<Vec<css::Selector>> {
+  let parts = values.split(|value| *value == ComponentValue::Token(Token::Comma));
+  parts
+    .map(|part| parse_selector(trim_whitespace(part), namespaces))
+    .collect()
+}
+
+// Comma-separated selectors without the invalid ones, as in `:is()` and `:where()`.
+// https://www.w3.org/TR/selectors-4/#typedef-forgiving-selector-list
+fn parse_forgiving_selector_list(values: &[ComponentValue], namespaces: &[css::NamespaceRule]) -> Vec<css::Selector> {
+  let parts = values.split(|value| *value == ComponentValue::Token(Token::Comma));
+  parts
+    .filter_map(|part| parse_selector(trim_whitespace(part), namespaces))
+    .collect()
+}
+
+// Selectors relative to the element, e.g. `> img, + p` in `:has(> img, + p)`.
+// https://www.w3.org/TR/selectors-4/#relative
+fn parse_relative_selector_list(
+  values: &[ComponentValue],
+  namespaces: &[css::NamespaceRule],
+) -> Option<Vec<(css::Combinator, css::Selector)>> {
+  let parts = values.split(|value| *value == ComponentValue::Token(Token::Comma));
+  let parts = parts.map(|part| match trim_whitespace(part) {
+    [first, rest @ ..] if combinator(first).is_some() => {
+      Some((combinator(first)?, parse_selector(trim_whitespace(rest), namespaces)?))
+    }
+    part => Some((css::Combinator::Descendant, parse_selector(part, namespaces)?)),
+  });
+  parts.collect()
+}
 
-  fn peek_one(&self) -> char {
-    self.input[self.cursor..].chars().next().unwrap()
+// compound selectors joined by combinators, e.g. `div.test > p`.
+// https://www.w3.org/TR/selectors-4/#complex
+fn parse_selector(values: &[ComponentValue], namespaces: &[css::NamespaceRule]) -> Option<css::Selector> {
+  let mut compounds = Vec::new();
+  let mut index = 0;
+  loop {
+    let end = values[index..]
+      .iter()
+      .position(|value| *value == ComponentValue::Token(Token::Whitespace) || combinator(value).is_some())
+      .map_or(values.len(), |end| index + end);
+    let compound = parse_simple_selector(&values[index..end], namespaces)?;
+    index = end;
+    if index == values.len() {
+      if compounds.is_empty() {
+        return Some(css::Selector::Simple(compound));
+      }
+      return Some(css::Selector::Complex { compounds, subject: compound });
+    }
+    // whitespace, optionally around a `>`, `+` or `~`.
+    let skip_whitespace = |index: usize| {
+      let whitespace = values[index..]
+        .iter()
+        .take_while(|value| **value == ComponentValue::Token(Token::Whitespace));
+      index + whitespace.count()
+    };
+    index = skip_whitespace(index);
+    let mut next = css::Combinator::Descendant;
+    if let Some(combinator) = combinator(&values[index]) {
+      next = combinator;
+      index = skip_whitespace(index + 1);
+    }
+    // only the subject can have a pseudo-element.
+    if compound.pseudo_element.is_some() {
+      return None;
+    }
+    compounds.push((compound, next));
   }
+}
 
-  fn peek_many(&self, count: usize) -> String {
-    self.input[self.cursor..].chars().take(count).collect()
+fn combinator(value: &ComponentValue) -> Option<css::Combinator> {
+  match value {
+    ComponentValue::Token(Token::Delim('>')) => Some(css::Combinator::Child),
+    ComponentValue::Token(Token::Delim('+')) => Some(css::Combinator::NextSibling),
+    ComponentValue::Token(Token::Delim('~')) => Some(css::Combinator::SubsequentSibling),
+    _ => None,
   }
+}
 
-  fn starts_with(&self, s: &str) -> bool {
-    self.input[self.cursor..].starts_with(s)
+// simple selector, e.g.: `type#id.class1.class2.class3`
+fn parse_simple_selector(values: &[ComponentValue], namespaces: &[css::NamespaceRule]) -> Option<css::SimpleSelector> {
+  if values.is_empty() {
+    return None;
+  }
+  let mut simple_selector = css::SimpleSelector::new();
+  let mut index = 0;
+  // the type or universal selector, e.g. `p`, `*`, `svg|rect` or `*|*`.
+  if let Some((prefix, name, length)) = parse_qualified_name(values) {
+    simple_selector.namespace = resolve_namespace(prefix, namespaces)?;
+    simple_selector.tag_name = (name != "*").then(|| name.to_string());
+    index = length;
+  }
+  // the default namespace also applies when the type selector is left out, as in `.note`.
+  if simple_selector.namespace.is_none() {
+    simple_selector.namespace = resolve_namespace(None, namespaces)?;
+  }
+  while index < values.len() {
+    // nothing can follow a pseudo-element.
+    if simple_selector.pseudo_element.is_some() {
+      return None;
+    }
+    match (&values[index], values.get(index + 1)) {
+      (ComponentValue::Token(Token::Hash { value, is_id: true }), _) => {
+        simple_selector.id = Some(value.clone());
+      }
+      (ComponentValue::Token(Token::Delim('.')), Some(ComponentValue::Token(Token::Ident(class)))) => {
+        simple_selector.class.push(class.clone());
+        index += 1;
+      }
+      (ComponentValue::Block { opening: '[', values }, _) => {
+        simple_selector
+          .attributes
+          .push(parse_attribute_selector(values, namespaces)?);
+      }
+      (ComponentValue::Token(Token::Colon), Some(ComponentValue::Token(Token::Colon))) => {
+        simple_selector.pseudo_element = Some(parse_pseudo_element(values.get(index + 2)?)?);
+        index += 2;
+      }
+      // `:before`, `:after`, `:first-line` and `:first-letter` are also written with one colon.
+      (ComponentValue::Token(Token::Colon), Some(value)) if is_legacy_pseudo_element(value) => {
+        simple_selector.pseudo_element = parse_pseudo_element(value);
+        index += 1;
+      }
+      (ComponentValue::Token(Token::Colon), Some(value)) => {
+        simple_selector
+          .pseudo_classes
+          .push(parse_pseudo_class(value, namespaces)?);
+        index += 1;
+      }
+      _ => return None,
+    }
+    index += 1;
   }
+  Some(simple_selector)
+}
 
-  fn advance_one(&mut self) {
-    self.cursor += 1;
+// `name`, `prefix|name`, `*|name` or `|name` at the start of `values`, where the name can be `*`:
+// the prefix, the name and the number of values they take.
+// https://www.w3.org/TR/selectors-4/#type-nmsp
+fn parse_qualified_name(values: &[ComponentValue]) -> Option<(Option<&str>, &str, usize)> {
+  fn name(value: &ComponentValue) -> Option<&str> {
+    match value {
+      ComponentValue::Token(Token::Ident(name)) => Some(name.as_str()),
+      ComponentValue::Token(Token::Delim('*')) => Some("*"),
+      _ => None,
+    }
+  }
+  match values {
+    [ComponentValue::Token(Token::Delim('|')), local_name, ..] => Some((Some(""), name(local_name)?, 2)),
+    [prefix, ComponentValue::Token(Token::Delim('|')), local_name, ..] if name(local_name).is_some() => {
+      Some((Some(name(prefix)?), name(local_name)?, 3))
+    }
+    [local_name, ..] => Some((None, name(local_name)?, 1)),
+    [] => None,
   }
+}
+
+// The namespace of a type selector with `prefix` (`""` for `|`), or `None` for any namespace.
+// Invalid if the prefix isn't declared.
+fn resolve_namespace(
+  prefix: Option<&str>,
+  namespaces: &[css::NamespaceRule],
+) -> Option<Option<css::NamespaceConstraint>> {
+  let url = |prefix: Option<&str>| {
+    let namespace = namespaces
+      .iter()
+      .find(|namespace| namespace.prefix.as_deref() == prefix);
+    namespace.map(|namespace| namespace.url.clone())
+  };
+  let namespace = match prefix {
+    Some("*") => Some(css::NamespaceConstraint::Any),
+    Some("") => Some(css::NamespaceConstraint::None),
+    Some(prefix) => Some(css::NamespaceConstraint::Prefix(prefix.to_string(), url(Some(prefix))?)),
+    None => url(None).map(css::NamespaceConstraint::Default),
+  };
+  Some(namespace)
+}
 
-  fn advance_many(&mut self, count: usize) {
-    self.cursor += count;
+// `[name]` or `[name <operator> value <flag>?]`, without the brackets. The name can have a
+// namespace prefix, but no default namespace.
+// https://www.w3.org/TR/selectors-4/#attribute-selectors
+fn parse_attribute_selector(
+  values: &[ComponentValue],
+  namespaces: &[css::NamespaceRule],
+) -> Option<css::AttributeSelector> {
+  let values = trim_whitespace(values);
+  let (prefix, name, length) = parse_qualified_name(values)?;
+  if name == "*" {
+    return None;
   }
+  let namespace = match prefix {
+    Some(prefix) => resolve_namespace(Some(prefix), namespaces)?,
+    None => None,
+  };
+  let rest = &values[length..];
+  let mut attribute_selector = css::AttributeSelector {
+    name: name.to_string(),
+    namespace,
+    operator: css::AttributeOperator::Exists,
+    value: String::new(),
+    case_sensitivity: css::CaseSensitivity::Default,
+  };
+  let (operator, rest) = match trim_whitespace(rest) {
+    [] => return Some(attribute_selector),
+    [ComponentValue::Token(Token::Delim('=')), rest @ ..] => (css::AttributeOperator::Equals, rest),
+    [ComponentValue::Token(Token::Delim(delimiter)), ComponentValue::Token(Token::Delim('=')), rest @ ..] => {
+      let operator = match delimiter {
+        '~' => css::AttributeOperator::Includes,
+        '|' => css::AttributeOperator::DashMatch,
+        '^' => css::AttributeOperator::Prefix,
+        '$' => css::AttributeOperator::Suffix,
+        '*' => css::AttributeOperator::Substring,
+        _ => return None,
+      };
+      (operator, rest)
+    }
+    _ => return None,
+  };
+  let rest: Vec<&ComponentValue> = rest
+    .iter()
+    .filter(|value| **value != ComponentValue::Token(Token::Whitespace))
+    .collect();
+  let (value, flag) = match rest.as_slice() {
+    [ComponentValue::Token(Token::Ident(value) | Token::String(value))] => (value, None),
+    [ComponentValue::Token(Token::Ident(value) | Token::String(value)), ComponentValue::Token(Token::Ident(flag))] => {
+      (value, Some(flag.to_ascii_lowercase()))
+    }
+    _ => return None,
+  };
+  attribute_selector.operator = operator;
+  attribute_selector.value = value.clone();
+  attribute_selector.case_sensitivity = match flag.as_deref() {
+    None => css::CaseSensitivity::Default,
+    Some("i") => css::CaseSensitivity::Insensitive,
+    Some("s") => css::CaseSensitivity::Sensitive,
+    Some(_) => return None,
+  };
+  Some(attribute_selector)
+}
+
+// `::name`, without the colons.
+// https://www.w3.org/TR/css-pseudo-4/
+fn parse_pseudo_element(value: &ComponentValue) -> Option<css::PseudoElement> {
+  let ComponentValue::Token(Token::Ident(name)) = value else {
+    return None;
+  };
+  let pseudo_element = match &*name.to_ascii_lowercase() {
+    "before" => css::PseudoElement::Before,
+    "after" => css::PseudoElement::After,
+    "marker" => css::PseudoElement::Marker,
+    "first-line" => css::PseudoElement::FirstLine,
+    "first-letter" => css::PseudoElement::FirstLetter,
+    _ => return None,
+  };
+  Some(pseudo_element)
+}
+
+fn is_legacy_pseudo_element(value: &ComponentValue) -> bool {
+  let is_marker = matches!(value, ComponentValue::Token(Token::Ident(name)) if name.eq_ignore_ascii_case("marker"));
+  parse_pseudo_element(value).is_some() && !is_marker
+}
+
+// `:name` or `:name(arguments)`, without the colon.
+fn parse_pseudo_class(value: &ComponentValue, namespaces: &[css::NamespaceRule]) -> Option<css::PseudoClass> {
+  // the selectors in the arguments match elements, so they can't have pseudo-elements.
+  let is_element_selector = |selector: &css::Selector| selector.pseudo_element().is_none();
+  let selector_list = |values: &[ComponentValue]| {
+    let selectors = parse_selector_list(values, namespaces)?;
+    selectors.iter().all(is_element_selector).then_some(selectors)
+  };
+  let forgiving_selector_list = |values: &[ComponentValue]| {
+    let selectors = parse_forgiving_selector_list(values, namespaces).into_iter();
+    selectors.filter(is_element_selector).collect()
+  };
+  let pseudo_class = match value {
+    ComponentValue::Token(Token::Ident(name)) => match &*name.to_ascii_lowercase() {
+      "first-child" => css::PseudoClass::FirstChild,
+      "last-child" => css::PseudoClass::LastChild,
+      "only-child" => css::PseudoClass::OnlyChild,
+      "first-of-type" => css::PseudoClass::FirstOfType,
+      "last-of-type" => css::PseudoClass::LastOfType,
+      "only-of-type" => css::PseudoClass::OnlyOfType,
+      "empty" => css::PseudoClass::Empty,
+      "root" => css::PseudoClass::Root,
+      "hover" => css::PseudoClass::Hover,
+      "active" => css::PseudoClass::Active,
+      "focus" => css::PseudoClass::Focus,
+      "focus-within" => css::PseudoClass::FocusWithin,
+      "focus-visible" => css::PseudoClass::FocusVisible,
+      "checked" => css::PseudoClass::Checked,
+      "disabled" => css::PseudoClass::Disabled,
+      "visited" => css::PseudoClass::Visited,
+      "target" => css::PseudoClass::Target,
+      _ => return None,
+    },
+    ComponentValue::Function { name, arguments } => match &*name.to_ascii_lowercase() {
+      "nth-child" | "nth-last-child" => {
+        // `An+B` or `An+B of <selectors>`
+        let of = arguments
+          .iter()
+          .position(|value| matches!(value, ComponentValue::Token(Token::Ident(of)) if of.eq_ignore_ascii_case("of")));
+        let (nth, selectors) = match of {
+          Some(of) => (parse_nth(&arguments[..of])?, Some(selector_list(&arguments[of + 1..])?)),
+          None => (parse_nth(arguments)?, None),
+        };
+        match name.eq_ignore_ascii_case("nth-child") {
+          true => css::PseudoClass::NthChild(nth, selectors),
+          false => css::PseudoClass::NthLastChild(nth, selectors),
+        }
+      }
+      "nth-of-type" => css::PseudoClass::NthOfType(parse_nth(arguments)?),
+      "nth-last-of-type" => css::PseudoClass::NthLastOfType(parse_nth(arguments)?),
+      "not" => css::PseudoClass::Not(selector_list(arguments)?),
+      "is" => css::PseudoClass::Is(forgiving_selector_list(arguments)),
+      "where" => css::PseudoClass::Where(forgiving_selector_list(arguments)),
+      "has" => {
+        let relative_selectors = parse_relative_selector_list(arguments, namespaces)?;
+        let is_element_selector = relative_selectors
+          .iter()
+          .all(|(_, selector)| is_element_selector(selector));
+        css::PseudoClass::Has(is_element_selector.then_some(relative_selectors)?)
+      }
+      _ => return None,
+    },
+    _ => return None,
+  };
+  Some(pseudo_class)
+}
 
-  fn consume(&mut self) -> char {
-    let mut iter = self.input[self.cursor..].char_indices();
-    let (_, cur_char) = iter.next().unwrap();
-    let (next_cursor, _) = iter.next().unwrap_or((1, ' '));
-    self.cursor += next_cursor;
-    cur_char
+// `odd`, `even`, `B`, `An` or `An+B`, where the tokens split `An+B` in several ways, e.g.
+// `2n+1` is a dimension and a number, `2n-1` a dimension with the unit `n-1` and `-n+1` an ident
+// and a number. The tokens are written back out as text, which is then parsed.
+// https://www.w3.org/TR/css-syntax-3/#anb-microsyntax
+fn parse_nth(values: &[ComponentValue]) -> Option<css::Nth> {
+  let mut text = String::new();
+  let mut after_sign = false;
+  for value in trim_whitespace(values) {
+    match value {
+      ComponentValue::Token(Token::Whitespace) => continue,
+      ComponentValue::Token(Token::Ident(ident)) => text.push_str(ident),
+      ComponentValue::Token(Token::Delim(sign @ ('+' | '-'))) => text.push(*sign),
+      ComponentValue::Token(Token::Number { value, is_integer: true }) if after_sign => {
+        text.push_str(&value.abs().to_string())
+      }
+      ComponentValue::Token(Token::Number { value, is_integer: true }) => text.push_str(&format!("{:+}", value)),
+      ComponentValue::Token(Token::Dimension { value, is_integer: true, unit }) => {
+        text.push_str(&format!("{}{}", value, unit))
+      }
+      _ => return None,
+    }
+    after_sign = matches!(value, ComponentValue::Token(Token::Delim('+' | '-')));
   }
+  let text = text.to_ascii_lowercase();
+  let nth = match &*text {
+    "odd" => css::Nth { a: 2, b: 1 },
+    "even" => css::Nth { a: 2, b: 0 },
+    text => match text.split_once('n') {
+      None => css::Nth { a: 0, b: text.parse().ok()? },
+      Some((a, b)) => {
+        let a = match a {
+          "" | "+" => 1,
+          "-" => -1,
+          a => a.parse().ok()?,
+        };
+        let b = match b {
+          "" => 0,
+          b if b.starts_with(['+', '-']) => b.parse().ok()?,
+          _ => return None,
+        };
+        css::Nth { a, b }
+      }
+    },
+  };
+  Some(nth)
+}
+
+fn trim_whitespace(values: &[ComponentValue]) -> &[ComponentValue] {
+  let is_whitespace = |value: &ComponentValue| *value == ComponentValue::Token(Token::Whitespace);
+  let start = values
+    .iter()
+    .position(|value| !is_whitespace(value))
+    .unwrap_or(values.len());
+  let end = values
+    .iter()
+    .rposition(|value| !is_whitespace(value))
+    .map_or(start, |end| end + 1);
+  &values[start..end]
+}
 
-  fn consume_expect(&mut self, text: &str) {
-    if &self.peek_many(text.len()) == text {
-      self.advance_many(text.len());
-    } else {
-      panic!("Expected '{}' but got '{}'", text, &self.peek_many(text.len()));
+// Values as written, with runs of whitespace (e.g. around a comment) collapsed to one space.
+fn serialize(values: &[ComponentValue]) -> String {
+  let whitespace = ComponentValue::Token(Token::Whitespace);
+  let mut output = String::new();
+  for (index, value) in values.iter().enumerate() {
+    if *value == whitespace && index > 0 && values[index - 1] == whitespace {
+      continue;
     }
+    output.push_str(&value.to_string());
   }
+  output
 }
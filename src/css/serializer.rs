@@ -0,0 +1,58 @@
+#![allow(dead_code)]
+use crate::css;
+// ==============================
+// https://www.w3.org/TR/cssom-1/#serializing-css-values
+//
+
+/// Serialize a style sheet back to CSS, one rule per line, with its preserved comments.
+pub fn serialize_style_sheet(style_sheet: &css::StyleSheet) -> String {
+  let mut output = String::new();
+  // the prefixes must be declared before the selectors that use them.
+  for namespace in &style_sheet.namespaces {
+    output.push_str(&serialize_namespace_rule(namespace));
+    output.push('\n');
+  }
+  for (index, rule) in style_sheet.rules.iter().enumerate() {
+    serialize_comments(style_sheet, index, &mut output);
+    output.push_str(&serialize_rule(rule));
+    output.push('\n');
+  }
+  serialize_comments(style_sheet, style_sheet.rules.len(), &mut output);
+  output
+}
+
+pub fn serialize_rule(rule: &css::Rule) -> String {
+  let selectors: Vec<String> = rule.selectors.iter().map(|selector| selector.to_string()).collect();
+  let declarations = serialize_declarations(&rule.declarations);
+  if declarations.is_empty() {
+    return format!("{} {{ }}", selectors.join(", "));
+  }
+  format!("{} {{ {} }}", selectors.join(", "), declarations)
+}
+
+pub fn serialize_namespace_rule(namespace: &css::NamespaceRule) -> String {
+  let url = css::Token::String(namespace.url.clone());
+  match &namespace.prefix {
+    Some(prefix) => format!("@namespace {} {};", prefix, url),
+    None => format!("@namespace {};", url),
+  }
+}
+
+/// Declarations as in a `style` attribute, e.g. `color: red; margin: 0;`.
+pub fn serialize_declarations(declarations: &[css::Declaration]) -> String {
+  let declarations: Vec<String> = declarations
+    .iter()
+    .map(|declaration| match declaration.important {
+      true => format!("{}: {} !important;", declaration.name, declaration.value),
+      false => format!("{}: {};", declaration.name, declaration.value),
+    })
+    .collect();
+  declarations.join(" ")
+}
+
+fn serialize_comments(style_sheet: &css::StyleSheet, rule_index: usize, output: &mut String) {
+  let comments = style_sheet.comments.iter();
+  for comment in comments.filter(|comment| comment.rule_index == rule_index) {
+    output.push_str(&format!("/*{}*/\n", comment.text));
+  }
+}
@@ -0,0 +1,501 @@
+#![allow(dead_code)]
+use crate::css::{ColorValue, Declaration, DeclarationValue, UnitValue};
+// ==============================
+// https://www.w3.org/TR/css-cascade-4/#shorthand
+// Shorthands are replaced by their longhands when parsed, so `margin: 0 auto` becomes
+// `margin-top: 0; margin-right: auto; margin-bottom: 0; margin-left: auto`. Longhands left out of
+// a shorthand are set to their initial value.
+//
+
+const SIDES: [&str; 4] = ["top", "right", "bottom", "left"];
+const CORNERS: [&str; 4] = ["top-left", "top-right", "bottom-right", "bottom-left"];
+
+// https://www.w3.org/TR/css-values-4/#common-keywords
+pub const CSS_WIDE_KEYWORDS: [&str; 5] = ["inherit", "initial", "unset", "revert", "revert-layer"];
+
+const BORDER_WIDTHS: [&str; 3] = ["thin", "medium", "thick"];
+const BORDER_STYLES: [&str; 10] = [
+  "none", "hidden", "dotted", "dashed", "solid", "double", "groove", "ridge", "inset", "outset",
+];
+
+const BACKGROUND_REPEATS: [&str; 4] = ["repeat", "space", "round", "no-repeat"];
+const BACKGROUND_ATTACHMENTS: [&str; 3] = ["scroll", "fixed", "local"];
+const BACKGROUND_BOXES: [&str; 3] = ["border-box", "padding-box", "content-box"];
+const BACKGROUND_POSITIONS: [&str; 5] = ["left", "center", "right", "top", "bottom"];
+
+const FONT_STRETCHES: [&str; 8] = [
+  "ultra-condensed",
+  "extra-condensed",
+  "condensed",
+  "semi-condensed",
+  "semi-expanded",
+  "expanded",
+  "extra-expanded",
+  "ultra-expanded",
+];
+const FONT_SIZES: [&str; 11] = [
+  "xx-small",
+  "x-small",
+  "small",
+  "medium",
+  "large",
+  "x-large",
+  "xx-large",
+  "xxx-large",
+  "larger",
+  "smaller",
+  "math",
+];
+// https://www.w3.org/TR/css-fonts-4/#font-prop
+const SYSTEM_FONTS: [&str; 6] = ["caption", "icon", "menu", "message-box", "small-caption", "status-bar"];
+
+/// The longhands of shorthand `name` with their values from `value`, or `name: value` itself for
+/// other properties. Declarations are returned as normal, not important.
+pub fn expand_shorthand(name: &str, value: DeclarationValue) -> Result<Vec<Declaration>, String> {
+  let shorthand = name.to_ascii_lowercase();
+  let Some(longhands) = longhands(&shorthand) else {
+    return Ok(vec![Declaration { name: name.to_string(), value, important: false }]);
+  };
+  // `var()` is only substituted at computed-value time, and system fonts aren't known here.
+  if contains_var(&value) || shorthand == "font" && is_keyword(&value, &SYSTEM_FONTS) {
+    return Ok(vec![Declaration { name: shorthand, value, important: false }]);
+  }
+  let values = match &value {
+    value if is_keyword(value, &CSS_WIDE_KEYWORDS) => vec![value.clone(); longhands.len()],
+    value => {
+      let items = match value {
+        DeclarationValue::List(items) => items.as_slice(),
+        value => std::slice::from_ref(value),
+      };
+      match &*shorthand {
+        "margin" | "inset" => expand_box(items, |value| is_length(value, &["auto"]))?,
+        "padding" => expand_box(items, |value| is_length(value, &[]))?,
+        "border-width" => expand_box(items, |value| is_length(value, &BORDER_WIDTHS))?,
+        "border-style" => expand_box(items, |value| is_keyword(value, &BORDER_STYLES))?,
+        "border-color" => {
+          let colors = items.iter().map(|item| to_color(item).ok_or_else(|| unexpected(item)));
+          expand_box(&colors.collect::<Result<Vec<_>, _>>()?, |_| true)?
+        }
+        "border-radius" => expand_border_radius(items)?,
+        "border" => expand_border_side(items)?
+          .into_iter()
+          .flat_map(|value| vec![value; SIDES.len()])
+          .collect(),
+        "border-top" | "border-right" | "border-bottom" | "border-left" => expand_border_side(items)?,
+        "background" => expand_background(items)?,
+        "font" => expand_font(items)?,
+        "flex" => expand_flex(items)?,
+        "gap" => match items {
+          [gap] if is_length(gap, &["normal"]) => vec![gap.clone(), gap.clone()],
+          [row, column] if is_length(row, &["normal"]) && is_length(column, &["normal"]) => {
+            vec![row.clone(), column.clone()]
+          }
+          _ => return Err("expected 1 or 2 gaps".to_string()),
+        },
+        _ => unreachable!(),
+      }
+    }
+  };
+  let declarations = longhands.into_iter().zip(values);
+  Ok(
+    declarations
+      .map(|(name, value)| Declaration { name, value, important: false })
+      .collect(),
+  )
+}
+
+// The longhands of `shorthand`, in the order its expansion returns their values.
+fn longhands(shorthand: &str) -> Option<Vec<String>> {
+  let longhands = match shorthand {
+    "margin" | "padding" => SIDES.iter().map(|side| format!("{}-{}", shorthand, side)).collect(),
+    "inset" => SIDES.iter().map(|side| side.to_string()).collect(),
+    "border-width" | "border-style" | "border-color" => {
+      let property = &shorthand["border-".len()..];
+      SIDES
+        .iter()
+        .map(|side| format!("border-{}-{}", side, property))
+        .collect()
+    }
+    "border-radius" => CORNERS
+      .iter()
+      .map(|corner| format!("border-{}-radius", corner))
+      .collect(),
+    "border" => ["width", "style", "color"]
+      .iter()
+      .flat_map(|property| SIDES.iter().map(move |side| format!("border-{}-{}", side, property)))
+      .collect(),
+    "border-top" | "border-right" | "border-bottom" | "border-left" => ["width", "style", "color"]
+      .iter()
+      .map(|property| format!("{}-{}", shorthand, property))
+      .collect(),
+    "background" => [
+      "background-color",
+      "background-image",
+      "background-position",
+      "background-size",
+      "background-repeat",
+      "background-attachment",
+      "background-origin",
+      "background-clip",
+    ]
+    .iter()
+    .map(|name| name.to_string())
+    .collect(),
+    "font" => [
+      "font-style",
+      "font-variant",
+      "font-weight",
+      "font-stretch",
+      "font-size",
+      "line-height",
+      "font-family",
+    ]
+    .iter()
+    .map(|name| name.to_string())
+    .collect(),
+    "flex" => ["flex-grow", "flex-shrink", "flex-basis"]
+      .iter()
+      .map(|name| name.to_string())
+      .collect(),
+    "gap" => vec!["row-gap".to_string(), "column-gap".to_string()],
+    _ => return None,
+  };
+  Some(longhands)
+}
+
+// top, right, bottom and left from 1 to 4 values.
+// https://www.w3.org/TR/css-box-4/#margin-shorthand
+fn expand_box(
+  values: &[DeclarationValue],
+  is_valid: impl Fn(&DeclarationValue) -> bool,
+) -> Result<Vec<DeclarationValue>, String> {
+  if let Some(value) = values.iter().find(|value| !is_valid(value)) {
+    return Err(unexpected(value));
+  }
+  let sides = match values {
+    [all] => [all, all, all, all],
+    [vertical, horizontal] => [vertical, horizontal, vertical, horizontal],
+    [top, horizontal, bottom] => [top, horizontal, bottom, horizontal],
+    [top, right, bottom, left] => [top, right, bottom, left],
+    _ => return Err("expected 1 to 4 values".to_string()),
+  };
+  Ok(sides.into_iter().cloned().collect())
+}
+
+// https://www.w3.org/TR/css-backgrounds-3/#border-radius
+fn expand_border_radius(values: &[DeclarationValue]) -> Result<Vec<DeclarationValue>, String> {
+  let mut parts = values.split(|value| *value == DeclarationValue::Separator('/'));
+  let horizontal = expand_box(parts.next().unwrap_or_default(), |value| is_length(value, &[]))?;
+  let Some(vertical) = parts.next() else {
+    return Ok(horizontal);
+  };
+  if parts.next().is_some() {
+    return Err("unexpected '/'".to_string());
+  }
+  let vertical = expand_box(vertical, |value| is_length(value, &[]))?;
+  let corners = horizontal.into_iter().zip(vertical);
+  Ok(
+    corners
+      .map(|(horizontal, vertical)| DeclarationValue::List(vec![horizontal, vertical]))
+      .collect(),
+  )
+}
+
+// width, style and color in any order.
+// https://www.w3.org/TR/css-backgrounds-3/#border-shorthands
+fn expand_border_side(values: &[DeclarationValue]) -> Result<Vec<DeclarationValue>, String> {
+  let (mut width, mut style, mut color) = (None, None, None);
+  for value in values {
+    if width.is_none() && is_length(value, &BORDER_WIDTHS) {
+      width = Some(value.clone());
+    } else if style.is_none() && is_keyword(value, &BORDER_STYLES) {
+      style = Some(value.clone());
+    } else if let Some(value) = to_color(value).filter(|_| color.is_none()) {
+      color = Some(value);
+    } else {
+      return Err(unexpected(value));
+    }
+  }
+  Ok(vec![
+    width.unwrap_or_else(|| keyword("medium")),
+    style.unwrap_or_else(|| keyword("none")),
+    color.unwrap_or(DeclarationValue::ColorValue(ColorValue::CurrentColor)),
+  ])
+}
+
+// Layers are separated by commas; only the last one may have a color. Each longhand but
+// `background-color` gets a comma-separated value per layer.
+// https://www.w3.org/TR/css-backgrounds-3/#background
+fn expand_background(values: &[DeclarationValue]) -> Result<Vec<DeclarationValue>, String> {
+  let layers: Vec<&[DeclarationValue]> = values
+    .split(|value| *value == DeclarationValue::Separator(','))
+    .collect();
+  let mut color = DeclarationValue::ColorValue(ColorValue::RBGColorValue(0, 0, 0, 0));
+  let mut longhands: Vec<Vec<DeclarationValue>> = vec![Vec::new(); 7];
+  for (index, layer) in layers.iter().enumerate() {
+    let is_last = index + 1 == layers.len();
+    let (layer_color, layer_values) = background_layer(layer, is_last)?;
+    if let Some(layer_color) = layer_color {
+      color = layer_color;
+    }
+    for (longhand, value) in longhands.iter_mut().zip(layer_values) {
+      if !longhand.is_empty() {
+        longhand.push(DeclarationValue::Separator(','));
+      }
+      longhand.push(value);
+    }
+  }
+  let mut values = vec![color];
+  values.extend(longhands.into_iter().map(list));
+  Ok(values)
+}
+
+// The color and the image, position, size, repeat, attachment, origin and clip of one layer.
+fn background_layer(
+  values: &[DeclarationValue],
+  is_last: bool,
+) -> Result<(Option<DeclarationValue>, Vec<DeclarationValue>), String> {
+  if values.is_empty() {
+    return Err("empty background layer".to_string());
+  }
+  let (mut color, mut image, mut position, mut size) = (None, None, None, None);
+  let (mut repeat, mut attachment, mut boxes) = (None, None, Vec::new());
+  let mut index = 0;
+  while index < values.len() {
+    let value = &values[index];
+    let is_position = |value: &DeclarationValue| is_length(value, &BACKGROUND_POSITIONS);
+    if repeat.is_none() && is_keyword(value, &["repeat-x", "repeat-y"]) {
+      repeat = Some(value.clone());
+    } else if repeat.is_none() && is_keyword(value, &BACKGROUND_REPEATS) {
+      let count = take_while(&values[index..], 2, |value| is_keyword(value, &BACKGROUND_REPEATS));
+      repeat = Some(list(values[index..index + count].to_vec()));
+      index += count - 1;
+    } else if attachment.is_none() && is_keyword(value, &BACKGROUND_ATTACHMENTS) {
+      attachment = Some(value.clone());
+    } else if boxes.len() < 2 && is_keyword(value, &BACKGROUND_BOXES) {
+      boxes.push(value.clone());
+    } else if image.is_none() && (is_keyword(value, &["none"]) || is_function(value)) {
+      image = Some(value.clone());
+    } else if position.is_none() && is_position(value) {
+      let count = take_while(&values[index..], 4, is_position);
+      position = Some(list(values[index..index + count].to_vec()));
+      index += count;
+      // `<position> / <size>`
+      if values.get(index) == Some(&DeclarationValue::Separator('/')) {
+        let sizes = &values[index + 1..];
+        let count = match sizes.first() {
+          Some(value) if is_keyword(value, &["cover", "contain"]) => 1,
+          _ => take_while(sizes, 2, |value| is_length(value, &["auto"])),
+        };
+        if count == 0 {
+          return Err("expected a background size after '/'".to_string());
+        }
+        size = Some(list(sizes[..count].to_vec()));
+        index += count + 1;
+      }
+      continue;
+    } else if let Some(value) = to_color(value).filter(|_| is_last && color.is_none()) {
+      color = Some(value);
+    } else {
+      return Err(unexpected(value));
+    }
+    index += 1;
+  }
+  let zero = DeclarationValue::Length(0.0, UnitValue::Percent);
+  let origin = boxes.first().cloned().unwrap_or_else(|| keyword("padding-box"));
+  // one box sets both the origin and the clip.
+  let clip = boxes
+    .get(1)
+    .or(boxes.first())
+    .cloned()
+    .unwrap_or_else(|| keyword("border-box"));
+  let values = vec![
+    image.unwrap_or_else(|| keyword("none")),
+    position.unwrap_or_else(|| DeclarationValue::List(vec![zero.clone(), zero])),
+    size.unwrap_or_else(|| keyword("auto")),
+    repeat.unwrap_or_else(|| keyword("repeat")),
+    attachment.unwrap_or_else(|| keyword("scroll")),
+    origin,
+    clip,
+  ];
+  Ok((color, values))
+}
+
+// `[style || variant || weight || stretch]? size [/ line-height]? family`
+// https://www.w3.org/TR/css-fonts-4/#font-prop
+fn expand_font(values: &[DeclarationValue]) -> Result<Vec<DeclarationValue>, String> {
+  let (mut style, mut variant, mut weight, mut stretch) = (None, None, None, None);
+  let mut index = 0;
+  while index < values.len().min(4) {
+    let value = &values[index];
+    // `normal` may stand for any of the four, which default to it anyway.
+    if is_keyword(value, &["normal"]) {
+      index += 1;
+      continue;
+    }
+    if style.is_none() && is_keyword(value, &["italic", "oblique"]) {
+      style = Some(value.clone());
+    } else if variant.is_none() && is_keyword(value, &["small-caps"]) {
+      variant = Some(value.clone());
+    } else if weight.is_none() && is_font_weight(value) {
+      weight = Some(value.clone());
+    } else if stretch.is_none() && is_keyword(value, &FONT_STRETCHES) {
+      stretch = Some(value.clone());
+    } else {
+      break;
+    }
+    index += 1;
+  }
+  let size = match values.get(index) {
+    Some(size) if is_length(size, &FONT_SIZES) => size.clone(),
+    Some(value) => return Err(unexpected(value)),
+    None => return Err("expected a font size".to_string()),
+  };
+  index += 1;
+  let mut line_height = None;
+  if values.get(index) == Some(&DeclarationValue::Separator('/')) {
+    line_height = match values.get(index + 1) {
+      Some(value @ (DeclarationValue::Number(_) | DeclarationValue::Integer(_))) => Some(value.clone()),
+      Some(value) if is_length(value, &["normal"]) => Some(value.clone()),
+      _ => return Err("expected a line height after '/'".to_string()),
+    };
+    index += 2;
+  }
+  let family = &values[index..];
+  if family.is_empty() {
+    return Err("expected a font family".to_string());
+  }
+  let is_family =
+    |value: &DeclarationValue| matches!(value, DeclarationValue::Keyword(_) | DeclarationValue::Separator(','));
+  if let Some(value) = family.iter().find(|value| !is_family(value)) {
+    return Err(unexpected(value));
+  }
+  let normal = || keyword("normal");
+  Ok(vec![
+    style.unwrap_or_else(normal),
+    variant.unwrap_or_else(normal),
+    weight.unwrap_or_else(normal),
+    stretch.unwrap_or_else(normal),
+    size,
+    line_height.unwrap_or_else(normal),
+    list(family.to_vec()),
+  ])
+}
+
+// `none | [ <grow> <shrink>? || <basis> ]`
+// https://www.w3.org/TR/css-flexbox-1/#flex-property
+fn expand_flex(values: &[DeclarationValue]) -> Result<Vec<DeclarationValue>, String> {
+  let auto = keyword("auto");
+  match values {
+    [value] if is_keyword(value, &["none"]) => {
+      return Ok(vec![DeclarationValue::Integer(0), DeclarationValue::Integer(0), auto]);
+    }
+    [value] if is_keyword(value, &["auto"]) => {
+      return Ok(vec![DeclarationValue::Integer(1), DeclarationValue::Integer(1), auto]);
+    }
+    _ => {}
+  }
+  let mut factors = Vec::new();
+  let mut basis = None;
+  let mut after_factor = false;
+  for value in values {
+    // the grow and shrink factors are next to each other.
+    let is_factor = is_number(value) && factors.len() < 2 && (factors.is_empty() || after_factor);
+    if is_factor {
+      factors.push(value.clone());
+    } else if basis.is_none() && is_length(value, &["auto", "content"]) {
+      basis = Some(value.clone());
+    } else {
+      return Err(unexpected(value));
+    }
+    after_factor = is_factor;
+  }
+  Ok(vec![
+    factors.first().cloned().unwrap_or(DeclarationValue::Integer(1)),
+    factors.get(1).cloned().unwrap_or(DeclarationValue::Integer(1)),
+    // the basis is `0` when only factors are given.
+    basis.unwrap_or(DeclarationValue::Length(0.0, UnitValue::Percent)),
+  ])
+}
+
+fn keyword(keyword: &str) -> DeclarationValue {
+  DeclarationValue::Keyword(keyword.to_string())
+}
+
+// One value as itself, several as a list.
+fn list(mut values: Vec<DeclarationValue>) -> DeclarationValue {
+  match values.len() {
+    1 => values.remove(0),
+    _ => DeclarationValue::List(values),
+  }
+}
+
+// The number of values at the start of `values`, up to `max`, that match `predicate`.
+fn take_while(values: &[DeclarationValue], max: usize, predicate: impl Fn(&DeclarationValue) -> bool) -> usize {
+  values.iter().take(max).take_while(|value| predicate(value)).count()
+}
+
+fn is_keyword(value: &DeclarationValue, keywords: &[&str]) -> bool {
+  match value {
+    DeclarationValue::Keyword(keyword) => keywords.contains(&&*keyword.to_ascii_lowercase()),
+    _ => false,
+  }
+}
+
+// A function other than a color, e.g. `url()` or `linear-gradient()`.
+fn is_function(value: &DeclarationValue) -> bool {
+  matches!(value, DeclarationValue::Keyword(keyword) if keyword.ends_with(')'))
+}
+
+// A length, percentage, unitless zero, math function or one of `keywords`.
+fn is_length(value: &DeclarationValue, keywords: &[&str]) -> bool {
+  match value {
+    DeclarationValue::Length(_, unit) => unit.is_length() || *unit == UnitValue::Percent,
+    DeclarationValue::Integer(0) => true,
+    DeclarationValue::Number(number) => *number == 0.0,
+    DeclarationValue::Keyword(keyword) => {
+      let keyword = keyword.to_ascii_lowercase();
+      let is_math = ["calc(", "min(", "max(", "clamp("]
+        .iter()
+        .any(|function| keyword.starts_with(function));
+      is_math || keywords.contains(&&*keyword)
+    }
+    _ => false,
+  }
+}
+
+fn is_number(value: &DeclarationValue) -> bool {
+  match value {
+    DeclarationValue::Integer(number) => *number >= 0,
+    DeclarationValue::Number(number) => *number >= 0.0,
+    _ => false,
+  }
+}
+
+fn is_font_weight(value: &DeclarationValue) -> bool {
+  match value {
+    DeclarationValue::Integer(weight) => (1..=1000).contains(weight),
+    DeclarationValue::Number(weight) => (1.0..=1000.0).contains(weight),
+    value => is_keyword(value, &["bold", "bolder", "lighter"]),
+  }
+}
+
+fn to_color(value: &DeclarationValue) -> Option<DeclarationValue> {
+  match value {
+    DeclarationValue::ColorValue(_) => Some(value.clone()),
+    DeclarationValue::Keyword(name) => ColorValue::from_name(name).map(DeclarationValue::ColorValue),
+    _ => None,
+  }
+}
+
+fn contains_var(value: &DeclarationValue) -> bool {
+  match value {
+    DeclarationValue::Keyword(keyword) => keyword.to_ascii_lowercase().contains("var("),
+    DeclarationValue::List(values) => values.iter().any(contains_var),
+    _ => false,
+  }
+}
+
+fn unexpected(value: &DeclarationValue) -> String {
+  format!("unexpected '{}'", value)
+}
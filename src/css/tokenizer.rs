@@ -0,0 +1,468 @@
+#![allow(dead_code)]
+use std::fmt;
+// ==============================
+// https://www.w3.org/TR/css-syntax-3/#tokenization
+//
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum Token {
+  Ident(String),
+  // the name of `name(`, without the parenthesis.
+  Function(String),
+  // the name of `@name`, without the `@`.
+  AtKeyword(String),
+  // `is_id` when the value is a valid identifier, e.g. `#main` but not `#123`.
+  Hash { value: String, is_id: bool },
+  String(String),
+  // a string broken by a newline.
+  BadString,
+  // the URL of an unquoted `url(...)`.
+  Url(String),
+  BadUrl,
+  Delim(char),
+  Number { value: f32, is_integer: bool },
+  Percentage(f32),
+  Dimension { value: f32, is_integer: bool, unit: String },
+  Whitespace,
+  // `<!--`
+  CDO,
+  // `-->`
+  CDC,
+  Colon,
+  Semicolon,
+  Comma,
+  OpenSquare,
+  CloseSquare,
+  OpenParen,
+  CloseParen,
+  OpenCurly,
+  CloseCurly,
+}
+
+/// Split `input` into tokens. Comments are dropped.
+pub fn tokenize(input: &str) -> Vec<Token> {
+  let mut tokenizer = Tokenizer::new(input);
+  let mut tokens = Vec::new();
+  while let Some(token) = tokenizer.next_token() {
+    tokens.push(token);
+  }
+  tokens
+}
+
+/// Split `input` into tokens, also returning the text of `/*! ... */` comments with the index of
+/// the token they come before.
+pub fn tokenize_with_preserved_comments(input: &str) -> (Vec<Token>, Vec<(usize, String)>) {
+  let mut tokenizer = Tokenizer::new(input);
+  tokenizer.preserve_comments = true;
+  let mut tokens = Vec::new();
+  let mut comments = Vec::new();
+  loop {
+    let token = tokenizer.next_token();
+    for comment in tokenizer.preserved_comments.drain(..) {
+      comments.push((tokens.len(), comment));
+    }
+    match token {
+      Some(token) => tokens.push(token),
+      None => return (tokens, comments),
+    }
+  }
+}
+
+pub struct Tokenizer {
+  input: Vec<char>,
+  cursor: usize,
+  // keep `/*! ... */` comments, e.g. licenses, in `preserved_comments`.
+  pub preserve_comments: bool,
+  pub preserved_comments: Vec<String>,
+}
+
+impl Tokenizer {
+  pub fn new(input: &str) -> Self {
+    Tokenizer {
+      input: preprocess(input).chars().collect(),
+      cursor: 0,
+      preserve_comments: false,
+      preserved_comments: Vec::new(),
+    }
+  }
+
+  /// The next token, or `None` at the end of the input.
+  // https://www.w3.org/TR/css-syntax-3/#consume-token
+  pub fn next_token(&mut self) -> Option<Token> {
+    self.consume_comments();
+    let character = self.consume()?;
+    let token = match character {
+      character if is_whitespace(character) => {
+        while self.peek(0).is_some_and(is_whitespace) {
+          self.cursor += 1;
+        }
+        Token::Whitespace
+      }
+      '"' | '\'' => self.consume_string(character),
+      '#' => {
+        if self.peek(0).is_some_and(is_ident_char) || self.is_valid_escape(0) {
+          let is_id = self.starts_identifier(0);
+          Token::Hash { value: self.consume_ident_sequence(), is_id }
+        } else {
+          Token::Delim('#')
+        }
+      }
+      '(' => Token::OpenParen,
+      ')' => Token::CloseParen,
+      '[' => Token::OpenSquare,
+      ']' => Token::CloseSquare,
+      '{' => Token::OpenCurly,
+      '}' => Token::CloseCurly,
+      ',' => Token::Comma,
+      ':' => Token::Colon,
+      ';' => Token::Semicolon,
+      '+' | '.' if self.starts_number(-1) => {
+        self.cursor -= 1;
+        self.consume_numeric()
+      }
+      '-' if self.starts_number(-1) => {
+        self.cursor -= 1;
+        self.consume_numeric()
+      }
+      '-' if self.peek(0) == Some('-') && self.peek(1) == Some('>') => {
+        self.cursor += 2;
+        Token::CDC
+      }
+      '-' if self.starts_identifier(-1) => {
+        self.cursor -= 1;
+        self.consume_ident_like()
+      }
+      '<' if self.peek(0) == Some('!') && self.peek(1) == Some('-') && self.peek(2) == Some('-') => {
+        self.cursor += 3;
+        Token::CDO
+      }
+      '@' if self.starts_identifier(0) => Token::AtKeyword(self.consume_ident_sequence()),
+      '\\' if self.is_valid_escape(-1) => {
+        self.cursor -= 1;
+        self.consume_ident_like()
+      }
+      '0'..='9' => {
+        self.cursor -= 1;
+        self.consume_numeric()
+      }
+      character if is_ident_start_char(character) => {
+        self.cursor -= 1;
+        self.consume_ident_like()
+      }
+      character => Token::Delim(character),
+    };
+    Some(token)
+  }
+
+  // https://www.w3.org/TR/css-syntax-3/#consume-comments
+  fn consume_comments(&mut self) {
+    while self.peek(0) == Some('/') && self.peek(1) == Some('*') {
+      self.cursor += 2;
+      let start = self.cursor;
+      while self.cursor < self.input.len() && !(self.peek(0) == Some('*') && self.peek(1) == Some('/')) {
+        self.cursor += 1;
+      }
+      if self.preserve_comments && self.input.get(start) == Some(&'!') {
+        self
+          .preserved_comments
+          .push(self.input[start..self.cursor].iter().collect());
+      }
+      // an unterminated comment runs to the end of the input.
+      self.cursor = (self.cursor + 2).min(self.input.len());
+    }
+  }
+
+  // https://www.w3.org/TR/css-syntax-3/#consume-numeric-token
+  fn consume_numeric(&mut self) -> Token {
+    let (value, is_integer) = self.consume_number();
+    if self.starts_identifier(0) {
+      let unit = self.consume_ident_sequence();
+      return Token::Dimension { value, is_integer, unit };
+    }
+    if self.peek(0) == Some('%') {
+      self.cursor += 1;
+      return Token::Percentage(value);
+    }
+    Token::Number { value, is_integer }
+  }
+
+  // https://www.w3.org/TR/css-syntax-3/#consume-number
+  fn consume_number(&mut self) -> (f32, bool) {
+    let mut representation = String::new();
+    let mut is_integer = true;
+    if let Some(sign @ ('+' | '-')) = self.peek(0) {
+      representation.push(sign);
+      self.cursor += 1;
+    }
+    self.consume_digits(&mut representation);
+    if self.peek(0) == Some('.') && self.peek(1).is_some_and(|character| character.is_ascii_digit()) {
+      representation.push('.');
+      self.cursor += 1;
+      self.consume_digits(&mut representation);
+      is_integer = false;
+    }
+    if let Some('e' | 'E') = self.peek(0) {
+      let has_sign = matches!(self.peek(1), Some('+' | '-'));
+      let digit_offset = if has_sign { 2 } else { 1 };
+      if self
+        .peek(digit_offset)
+        .is_some_and(|character| character.is_ascii_digit())
+      {
+        representation.push('e');
+        if has_sign {
+          representation.push(self.input[self.cursor + 1]);
+        }
+        self.cursor += digit_offset as usize;
+        self.consume_digits(&mut representation);
+        is_integer = false;
+      }
+    }
+    (representation.parse().unwrap_or(0.0), is_integer)
+  }
+
+  fn consume_digits(&mut self, representation: &mut String) {
+    while let Some(digit) = self.peek(0).filter(|character| character.is_ascii_digit()) {
+      representation.push(digit);
+      self.cursor += 1;
+    }
+  }
+
+  // https://www.w3.org/TR/css-syntax-3/#consume-ident-like-token
+  fn consume_ident_like(&mut self) -> Token {
+    let name = self.consume_ident_sequence();
+    if self.peek(0) != Some('(') {
+      return Token::Ident(name);
+    }
+    self.cursor += 1;
+    if !name.eq_ignore_ascii_case("url") {
+      return Token::Function(name);
+    }
+    while self.peek(0).is_some_and(is_whitespace) && self.peek(1).is_some_and(is_whitespace) {
+      self.cursor += 1;
+    }
+    let next = if self.peek(0).is_some_and(is_whitespace) {
+      self.peek(1)
+    } else {
+      self.peek(0)
+    };
+    // a quoted URL is a `url(` function with a string argument.
+    if matches!(next, Some('"' | '\'')) {
+      return Token::Function(name);
+    }
+    self.consume_url()
+  }
+
+  // https://www.w3.org/TR/css-syntax-3/#consume-url-token
+  fn consume_url(&mut self) -> Token {
+    let mut url = String::new();
+    while self.peek(0).is_some_and(is_whitespace) {
+      self.cursor += 1;
+    }
+    loop {
+      let Some(character) = self.consume() else {
+        return Token::Url(url);
+      };
+      match character {
+        ')' => return Token::Url(url),
+        character if is_whitespace(character) => {
+          while self.peek(0).is_some_and(is_whitespace) {
+            self.cursor += 1;
+          }
+          match self.consume() {
+            Some(')') | None => return Token::Url(url),
+            Some(_) => return self.consume_bad_url_remnants(),
+          }
+        }
+        '"' | '\'' | '(' => return self.consume_bad_url_remnants(),
+        character if is_non_printable(character) => return self.consume_bad_url_remnants(),
+        '\\' if self.is_valid_escape(-1) => url.push(self.consume_escape()),
+        '\\' => return self.consume_bad_url_remnants(),
+        character => url.push(character),
+      }
+    }
+  }
+
+  fn consume_bad_url_remnants(&mut self) -> Token {
+    while let Some(character) = self.consume() {
+      match character {
+        ')' => break,
+        '\\' if self.is_valid_escape(-1) => {
+          self.consume_escape();
+        }
+        _ => {}
+      }
+    }
+    Token::BadUrl
+  }
+
+  // https://www.w3.org/TR/css-syntax-3/#consume-string-token
+  fn consume_string(&mut self, quote: char) -> Token {
+    let mut string = String::new();
+    loop {
+      match self.peek(0) {
+        None => return Token::String(string),
+        Some(character) if character == quote => {
+          self.cursor += 1;
+          return Token::String(string);
+        }
+        // the newline isn't consumed, it starts the next token.
+        Some('\n') => return Token::BadString,
+        Some('\\') => {
+          self.cursor += 1;
+          match self.peek(0) {
+            None => {}
+            Some('\n') => self.cursor += 1,
+            Some(_) => string.push(self.consume_escape()),
+          }
+        }
+        Some(character) => {
+          string.push(character);
+          self.cursor += 1;
+        }
+      }
+    }
+  }
+
+  // https://www.w3.org/TR/css-syntax-3/#consume-an-ident-sequence
+  fn consume_ident_sequence(&mut self) -> String {
+    let mut name = String::new();
+    loop {
+      match self.peek(0) {
+        Some(character) if is_ident_char(character) => {
+          name.push(character);
+          self.cursor += 1;
+        }
+        Some('\\') if self.is_valid_escape(0) => {
+          self.cursor += 1;
+          name.push(self.consume_escape());
+        }
+        _ => return name,
+      }
+    }
+  }
+
+  // Consume an escape after its `\`.
+  // https://www.w3.org/TR/css-syntax-3/#consume-escaped-code-point
+  fn consume_escape(&mut self) -> char {
+    let Some(character) = self.consume() else {
+      return char::REPLACEMENT_CHARACTER;
+    };
+    if !character.is_ascii_hexdigit() {
+      return character;
+    }
+    let mut hex = character.to_string();
+    while hex.len() < 6 && self.peek(0).is_some_and(|character| character.is_ascii_hexdigit()) {
+      hex.push(self.input[self.cursor]);
+      self.cursor += 1;
+    }
+    if self.peek(0).is_some_and(is_whitespace) {
+      self.cursor += 1;
+    }
+    let code_point = u32::from_str_radix(&hex, 16).unwrap_or(0);
+    match char::from_u32(code_point) {
+      Some('\0') | None => char::REPLACEMENT_CHARACTER,
+      Some(character) => character,
+    }
+  }
+
+  // Whether a `\` at `offset` starts an escape.
+  // https://www.w3.org/TR/css-syntax-3/#starts-with-a-valid-escape
+  fn is_valid_escape(&self, offset: isize) -> bool {
+    self.peek(offset) == Some('\\') && self.peek(offset + 1).is_some_and(|character| character != '\n')
+  }
+
+  // https://www.w3.org/TR/css-syntax-3/#would-start-an-identifier
+  fn starts_identifier(&self, offset: isize) -> bool {
+    match self.peek(offset) {
+      Some('-') => {
+        self
+          .peek(offset + 1)
+          .is_some_and(|character| is_ident_start_char(character) || character == '-')
+          || self.is_valid_escape(offset + 1)
+      }
+      Some('\\') => self.is_valid_escape(offset),
+      Some(character) => is_ident_start_char(character),
+      None => false,
+    }
+  }
+
+  // https://www.w3.org/TR/css-syntax-3/#starts-with-a-number
+  fn starts_number(&self, offset: isize) -> bool {
+    let is_digit = |offset: isize| self.peek(offset).is_some_and(|character| character.is_ascii_digit());
+    match self.peek(offset) {
+      Some('+' | '-') => is_digit(offset + 1) || (self.peek(offset + 1) == Some('.') && is_digit(offset + 2)),
+      Some('.') => is_digit(offset + 1),
+      Some(_) => is_digit(offset),
+      None => false,
+    }
+  }
+
+  // The code point at `offset` from the cursor; -1 is the one just consumed.
+  fn peek(&self, offset: isize) -> Option<char> {
+    let index = self.cursor as isize + offset;
+    if index < 0 {
+      return None;
+    }
+    self.input.get(index as usize).copied()
+  }
+
+  fn consume(&mut self) -> Option<char> {
+    let character = self.input.get(self.cursor).copied()?;
+    self.cursor += 1;
+    Some(character)
+  }
+}
+
+// https://www.w3.org/TR/css-syntax-3/#input-preprocessing
+fn preprocess(input: &str) -> String {
+  input
+    .replace("\r\n", "\n")
+    .replace(['\r', '\u{c}'], "\n")
+    .replace('\0', "\u{fffd}")
+}
+
+fn is_whitespace(character: char) -> bool {
+  matches!(character, ' ' | '\t' | '\n')
+}
+
+fn is_ident_start_char(character: char) -> bool {
+  character.is_ascii_alphabetic() || character == '_' || !character.is_ascii()
+}
+
+fn is_ident_char(character: char) -> bool {
+  is_ident_start_char(character) || character.is_ascii_digit() || character == '-'
+}
+
+fn is_non_printable(character: char) -> bool {
+  matches!(character, '\0'..='\u{8}' | '\u{b}' | '\u{e}'..='\u{1f}' | '\u{7f}')
+}
+
+impl fmt::Display for Token {
+  fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      Token::Ident(name) => write!(formatter, "{}", name),
+      Token::Function(name) => write!(formatter, "{}(", name),
+      Token::AtKeyword(name) => write!(formatter, "@{}", name),
+      Token::Hash { value, .. } => write!(formatter, "#{}", value),
+      Token::String(string) => write!(formatter, "\"{}\"", string.replace('\\', "\\\\").replace('"', "\\\"")),
+      Token::Url(url) => write!(formatter, "url({})", url),
+      Token::BadString | Token::BadUrl => Ok(()),
+      Token::Delim(character) => write!(formatter, "{}", character),
+      Token::Number { value, .. } => write!(formatter, "{}", value),
+      Token::Percentage(value) => write!(formatter, "{}%", value),
+      Token::Dimension { value, unit, .. } => write!(formatter, "{}{}", value, unit),
+      Token::Whitespace => write!(formatter, " "),
+      Token::CDO => write!(formatter, "<!--"),
+      Token::CDC => write!(formatter, "-->"),
+      Token::Colon => write!(formatter, ":"),
+      Token::Semicolon => write!(formatter, ";"),
+      Token::Comma => write!(formatter, ","),
+      Token::OpenSquare => write!(formatter, "["),
+      Token::CloseSquare => write!(formatter, "]"),
+      Token::OpenParen => write!(formatter, "("),
+      Token::CloseParen => write!(formatter, ")"),
+      Token::OpenCurly => write!(formatter, "{{"),
+      Token::CloseCurly => write!(formatter, "}}"),
+    }
+  }
+}
@@ -1,87 +1,280 @@
 #![allow(dead_code)]
-use crate::css::{DeclarationValue, Rule, Selector, SimpleSelector, Specificity, StyleSheet};
+use crate::css::{
+  matches, CSSParser, Declaration, DeclarationValue, ElementContext, GeneratedContent, Origin, PseudoElement, Rule,
+  Specificity, StyleSheet,
+};
 use crate::dom::{Element, Node, NodeType};
 use std::collections::HashMap;
+use std::fmt::Write;
 // ==================================
 // https://wiki.mozilla.org/Gecko:Key_Gecko_Structures_And_Invariants
 // map from CSS property names to values.
-// todo: make eayse to  suport of compound selectors, e.g. `div.test p` or `div.test > p`.
 //
-type PropertyMap = HashMap<String, DeclarationValue>;
+pub type PropertyMap = HashMap<String, DeclarationValue>;
+
+// Inherited properties that generated content depends on. Pseudo-elements and children take them
+// from their element when they don't specify them.
+const INHERITED_CONTENT_PROPERTIES: [&str; 2] = ["quotes", "list-style-type"];
 
 // A node with associated style data.
 #[derive(Debug, Clone)]
 pub struct StyledNode<'a> {
-  pub node: &'a Node, // pointer to a DOM node
+  pub node: &'a Node, // pointer to a DOM node; the element itself for its pseudo-elements
   pub specified_values: PropertyMap,
   pub children: Vec<StyledNode<'a>>,
+  // the styled pseudo-elements of an element: `::marker`, `::before`, `::after`, `::first-line`
+  // and `::first-letter`, in that order.
+  pub pseudo_elements: Vec<StyledNode<'a>>,
+  // set on the nodes of `pseudo_elements`.
+  pub pseudo_element: Option<PseudoElement>,
+  // the generated text of a `::marker`, `::before` or `::after`, which only gets a box with one.
+  pub content: Option<String>,
 }
 
-// http://www.w3.org/TR/CSS2/selector.html#pattern-matching
-fn matches(elem: &Element, selector: &Selector) -> bool {
-  match *selector {
-    Selector::Simple(ref simple_selector) => matches_simple_selector(elem, simple_selector),
-  }
+type MatchedRule<'a> = (Specificity, &'a Rule);
+
+// If `rule` matches the element, or its `pseudo_element`, return a `MatchedRule`. Otherwise
+// return `None`.
+fn match_rule<'a>(
+  context: &ElementContext,
+  rule: &'a Rule,
+  pseudo_element: Option<PseudoElement>,
+) -> Option<MatchedRule<'a>> {
+  // The rule applies with the highest specificity of its matching selectors.
+  let selector_vector = rule.selectors.iter();
+  let selectors =
+    selector_vector.filter(|selector| selector.pseudo_element() == pseudo_element && matches(context, selector));
+  selectors
+    .map(|selector| selector.specificity())
+    .max()
+    .map(|specificity| (specificity, rule))
 }
 
-fn matches_simple_selector(elem: &Element, selector: &SimpleSelector) -> bool {
-  // Check type selector
-  if selector.tag_name.iter().any(|name| elem.tag_name != *name) {
-    return false;
-  }
+// Find all CSS rules that match the given element, or its `pseudo_element`.
+fn matching_rules<'a>(
+  context: &ElementContext,
+  styleheet: &'a StyleSheet,
+  pseudo_element: Option<PseudoElement>,
+) -> Vec<MatchedRule<'a>> {
+  let rule_vector = styleheet.rules.iter();
+  rule_vector
+    .filter_map(|rule| match_rule(context, rule, pseudo_element))
+    .collect()
+}
 
-  // Check ID selector
-  if selector.id.iter().any(|id| elem.id() != *id) {
-    return false;
+// Declarations of the element's `style` attribute.
+fn inline_declarations(elem: &Element) -> Vec<Declaration> {
+  match elem.atributes.get("style") {
+    Some(style) => CSSParser::new(style.clone()).parse_declaration_list(),
+    None => Vec::new(),
   }
+}
+
+// Where a declaration ranks in the cascade: origin and importance, then whether it comes from a
+// `style` attribute, then specificity. Ties are broken by source order.
+// https://www.w3.org/TR/css-cascade-4/#cascade-sort
+type CascadePriority = (u8, bool, Specificity);
 
-  // Check class selectors
-  let elem_classes = elem.classes();
-  if selector.class.iter().any(|class| !elem_classes.contains(&**class)) {
-    return false;
+// Apply styles to a single element, or one of its pseudo-elements, returning the specified values.
+fn specified_values(
+  context: &ElementContext,
+  stylesheets: &[&StyleSheet],
+  pseudo_element: Option<PseudoElement>,
+) -> PropertyMap {
+  let mut values_map = HashMap::new();
+  let mut declarations: Vec<(CascadePriority, &Declaration)> = Vec::new();
+  for stylesheet in stylesheets {
+    for (specificity, rule) in matching_rules(context, stylesheet, pseudo_element) {
+      for declaration in &rule.declarations {
+        let precedence = stylesheet.origin.precedence(declaration.important);
+        declarations.push(((precedence, false, specificity), declaration));
+      }
+    }
   }
-  // We didn't find any non-matching selector components.
-  return true;
+  // `style` attributes are author declarations that beat every rule of the same importance.
+  let inline_declarations = match pseudo_element {
+    Some(_) => Vec::new(),
+    None => inline_declarations(context.element),
+  };
+  for declaration in &inline_declarations {
+    let precedence = Origin::Author.precedence(declaration.important);
+    declarations.push(((precedence, true, (0, 0, 0)), declaration));
+  }
+  // Go through the declarations from lowest to highest priority; the sort is stable, so later
+  // declarations win ties.
+  declarations.sort_by_key(|&(priority, _)| priority);
+  for (_, declaration) in declarations {
+    values_map.insert(declaration.name.clone(), declaration.value.clone());
+  }
+  values_map
 }
 
-type MatchedRule<'a> = (Specificity, &'a Rule);
+// Apply a stylesheet to an entire DOM tree, returning a StyledNode tree.
+pub fn style_tree<'a>(root: &'a Node, stylesheet: &'a StyleSheet) -> StyledNode<'a> {
+  cascade_style_tree(root, &[stylesheet])
+}
 
-// If `rule` matches `elem`, return a `MatchedRule`. Otherwise return `None`.
-fn match_rule<'a>(elem: &Element, rule: &'a Rule) -> Option<MatchedRule<'a>> {
-  // Find the first (highest-specificity) matching selector.
-  let mut selector_vector = rule.selectors.iter();
-  let selector = selector_vector.find(|selector| matches(elem, selector));
-  selector.map(|selector| (selector.specificity(), rule))
+// Apply stylesheets, ranked by origin and importance with later ones winning ties, and `style`
+// attributes to a DOM tree.
+pub fn cascade_style_tree<'a>(root: &'a Node, stylesheets: &[&StyleSheet]) -> StyledNode<'a> {
+  let mut cascade = Cascade { stylesheets, generated_content: GeneratedContent::new() };
+  cascade.cascade_node(root, 0, &mut Vec::new(), &PropertyMap::new())
 }
 
-// Find all CSS rules that match the given element.
-fn matching_rules<'a>(elem: &Element, styleheet: &'a StyleSheet) -> Vec<MatchedRule<'a>> {
-  let rule_vector = styleheet.rules.iter();
-  rule_vector.filter_map(|rule| match_rule(elem, rule)).collect()
+// The state of styling a document in tree order.
+struct Cascade<'s> {
+  stylesheets: &'s [&'s StyleSheet],
+  generated_content: GeneratedContent,
 }
 
-// Apply styles to a single element, returning the specified values.
-fn specified_values(elem: &Element, styleheet: &StyleSheet) -> PropertyMap {
-  let mut values_map = HashMap::new();
-  let mut rules_matched = matching_rules(elem, styleheet);
-  // Go through the rules from lowest to highest specificity.
-  rules_matched.sort_by(|&(right, _), &(left, _)| left.cmp(&right));
-  for (_, rule) in rules_matched {
-    for declaration in &rule.declarations {
-      values_map.insert(declaration.name.clone(), declaration.value.clone());
+impl<'s> Cascade<'s> {
+  // `ancestors` are those of `node`, outermost first, each with its index among its parent's
+  // children. `inherited` are the `INHERITED_CONTENT_PROPERTIES` of the parent.
+  fn cascade_node<'a>(
+    &mut self,
+    node: &'a Node,
+    index: usize,
+    ancestors: &mut Vec<(&'a Node, usize)>,
+    inherited: &PropertyMap,
+  ) -> StyledNode<'a> {
+    let Some(context) = ElementContext::new(node, index, ancestors) else {
+      return StyledNode::new(node, HashMap::new(), Vec::new());
+    };
+    let specified_values = specified_values(&context, self.stylesheets, None);
+    let is_list_item = is_keyword(specified_values.get("display"), "list-item");
+    let property = |name: &str| specified_values.get(name).cloned();
+    self.generated_content.update_counters(&property, is_list_item);
+    let inherited = inherit(&specified_values, inherited);
+
+    let scope = self.generated_content.enter();
+    let mut pseudo_elements = Vec::new();
+    if is_list_item {
+      pseudo_elements.extend(self.cascade_pseudo_element(&context, PseudoElement::Marker, &inherited));
+    }
+    pseudo_elements.extend(self.cascade_pseudo_element(&context, PseudoElement::Before, &inherited));
+    let element = context.element;
+    ancestors.push((node, index));
+    let children = node.children.iter().enumerate();
+    let children = children.map(|(index, child)| self.cascade_node(child, index, ancestors, &inherited));
+    let children = children.collect();
+    ancestors.pop();
+    // `ancestors` changed in between, so the context is made again.
+    let context = ElementContext { node, element, index, ancestors };
+    for pseudo_element in [
+      PseudoElement::After,
+      PseudoElement::FirstLine,
+      PseudoElement::FirstLetter,
+    ] {
+      pseudo_elements.extend(self.cascade_pseudo_element(&context, pseudo_element, &inherited));
     }
+    self.generated_content.leave(scope);
+
+    let mut styled_node = StyledNode::new(node, specified_values, children);
+    styled_node.pseudo_elements = pseudo_elements;
+    styled_node
+  }
+
+  // A styled pseudo-element of the element of `context`. `::before` and `::after` only exist with
+  // a `content`, while `::marker` defaults to the list marker.
+  // https://www.w3.org/TR/css-pseudo-4/#generated-content
+  fn cascade_pseudo_element<'a>(
+    &mut self,
+    context: &ElementContext<'a, '_>,
+    pseudo_element: PseudoElement,
+    inherited: &PropertyMap,
+  ) -> Option<StyledNode<'a>> {
+    let specified_values = specified_values(context, self.stylesheets, Some(pseudo_element));
+    let property = |name: &str| specified_values.get(name).or(inherited.get(name)).cloned();
+    let content = match (pseudo_element, specified_values.get("content")) {
+      (PseudoElement::Before | PseudoElement::After, Some(DeclarationValue::Content(items))) => {
+        self.generated_content.update_counters(&property, false);
+        Some(
+          self
+            .generated_content
+            .resolve(items, context.element, property("quotes").as_ref()),
+        )
+      }
+      (PseudoElement::Before | PseudoElement::After, _) => return None,
+      (PseudoElement::Marker, Some(DeclarationValue::Content(items))) => Some(self.generated_content.resolve(
+        items,
+        context.element,
+        property("quotes").as_ref(),
+      )),
+      (PseudoElement::Marker, Some(value)) if !is_keyword(Some(value), "normal") => return None,
+      (PseudoElement::Marker, _) => {
+        let list_style_type = match property("list-style-type") {
+          Some(DeclarationValue::Keyword(keyword)) => keyword.to_ascii_lowercase(),
+          _ => "disc".to_string(),
+        };
+        Some(self.generated_content.marker_text(&list_style_type)?)
+      }
+      (PseudoElement::FirstLine | PseudoElement::FirstLetter, _) if specified_values.is_empty() => return None,
+      (PseudoElement::FirstLine | PseudoElement::FirstLetter, _) => None,
+    };
+    let mut styled_node = StyledNode::new(context.node, specified_values, Vec::new());
+    styled_node.pseudo_element = Some(pseudo_element);
+    styled_node.content = content;
+    Some(styled_node)
   }
-  return values_map;
 }
 
-// Apply a stylesheet to an entire DOM tree, returning a StyledNode tree.
-pub fn style_tree<'a>(root: &'a Node, stylesheet: &'a StyleSheet) -> StyledNode<'a> {
-  let specified_values = match root.node_type {
-    NodeType::Element(ref elem) => specified_values(elem, stylesheet),
-    NodeType::Text(_) => HashMap::new(),
-  };
+// The `INHERITED_CONTENT_PROPERTIES` for the children of an element.
+fn inherit(specified_values: &PropertyMap, inherited: &PropertyMap) -> PropertyMap {
+  let mut values = inherited.clone();
+  for name in INHERITED_CONTENT_PROPERTIES {
+    if let Some(value) = specified_values.get(name) {
+      values.insert(name.to_string(), value.clone());
+    }
+  }
+  values
+}
 
-  let children = root.children.iter().map(|child| style_tree(child, stylesheet));
+fn is_keyword(value: Option<&DeclarationValue>, keyword: &str) -> bool {
+  matches!(value, Some(DeclarationValue::Keyword(value)) if value.eq_ignore_ascii_case(keyword))
+}
 
-  StyledNode { node: root, specified_values, children: children.collect() }
+impl<'a> StyledNode<'a> {
+  pub fn new(node: &'a Node, specified_values: PropertyMap, children: Vec<StyledNode<'a>>) -> Self {
+    StyledNode { node, specified_values, children, pseudo_elements: Vec::new(), pseudo_element: None, content: None }
+  }
+}
+
+/// One line per node with its specified values in property order, indented by depth.
+pub fn dump_style_tree(styled_node: &StyledNode) -> String {
+  let mut output = String::new();
+  dump_styled_node(styled_node, 0, &mut output);
+  output
+}
+
+fn dump_styled_node(styled_node: &StyledNode, depth: usize, output: &mut String) {
+  let indent = "  ".repeat(depth);
+  let element = match &styled_node.node.node_type {
+    NodeType::Text(text) => {
+      let _ = writeln!(output, "{}{:?}", indent, text);
+      return;
+    }
+    NodeType::Element(element) => element,
+  };
+  let mut label = format!("<{}>", element.tag_name);
+  if let Some(pseudo_element) = styled_node.pseudo_element {
+    label = pseudo_element.to_string();
+  }
+  if let Some(content) = &styled_node.content {
+    let _ = write!(label, " {:?}", content);
+  }
+  let mut properties: Vec<_> = styled_node.specified_values.iter().collect();
+  properties.sort_by_key(|(name, _)| *name);
+  let declarations: Vec<String> = properties
+    .iter()
+    .map(|(name, value)| format!("{}: {}", name, value))
+    .collect();
+  if declarations.is_empty() {
+    let _ = writeln!(output, "{}{}", indent, label);
+  } else {
+    let _ = writeln!(output, "{}{} {}", indent, label, declarations.join("; "));
+  }
+  // pseudo-elements come first, one level deeper like children.
+  for child in styled_node.pseudo_elements.iter().chain(&styled_node.children) {
+    dump_styled_node(child, depth + 1, output);
+  }
 }
@@ -6,12 +6,19 @@ enum DiagnosticEnum {
   Info,
 }
 
+#[derive(Debug, Clone, PartialEq)]
 pub struct Diagnostics {
   pub errors: Vec<String>,
   pub warnings: Vec<String>,
   pub infos: Vec<String>,
 }
 
+impl Default for Diagnostics {
+  fn default() -> Self {
+    Diagnostics::new()
+  }
+}
+
 impl Diagnostics {
   pub fn new() -> Self {
     Diagnostics { errors: Vec::new(), warnings: Vec::new(), infos: Vec::new() }
@@ -28,4 +35,8 @@ impl Diagnostics {
   pub fn add_info(&mut self, message: String) {
     self.infos.push(message);
   }
+
+  pub fn is_empty(&self) -> bool {
+    self.errors.is_empty() && self.warnings.is_empty() && self.infos.is_empty()
+  }
 }
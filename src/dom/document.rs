@@ -0,0 +1,187 @@
+#![allow(dead_code)]
+use crate::css::{self, CSSParser, StyledNode};
+use crate::dom::{self, Doctype, HtmlRoot, Node};
+use crate::network::{self, URL};
+// ==============================
+// https://html.spec.whatwg.org/multipage/dom.html#documents
+//
+
+#[derive(Debug, Clone)]
+pub struct Document {
+  pub root: HtmlRoot,
+  // the address the document was loaded from, if known.
+  pub url: Option<URL>,
+}
+
+// Where a stylesheet of the document comes from.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StyleSheetSource {
+  // contents of a `<style>` element
+  Style(String),
+  // `href` of a `<link rel="stylesheet">` element
+  Link(String),
+}
+
+impl Document {
+  pub fn new(root: HtmlRoot) -> Self {
+    Document { root, url: None }
+  }
+
+  pub fn with_url(root: HtmlRoot, url: URL) -> Self {
+    Document { root, url: Some(url) }
+  }
+
+  pub fn doctype(&self) -> Option<&Doctype> {
+    self.root.doctype.as_ref()
+  }
+
+  /// The root element of the document, usually `<html>`.
+  pub fn document_element(&self) -> Option<&Node> {
+    self.root.children.iter().find(|node| node.element().is_some())
+  }
+
+  pub fn head(&self) -> Option<&Node> {
+    self.document_element_child("head")
+  }
+
+  pub fn body(&self) -> Option<&Node> {
+    self.document_element_child("body")
+  }
+
+  /// Text of the first `<title>` element, with whitespace stripped and collapsed.
+  // https://html.spec.whatwg.org/multipage/dom.html#document.title
+  pub fn title(&self) -> String {
+    let title = self
+      .root
+      .descendants()
+      .into_iter()
+      .find(|node| node.is_element("title"));
+    let text = title.map(|node| node.text_content()).unwrap_or_default();
+    dom::collapse_whitespace(&text)
+  }
+
+  /// Replace the text of the `<title>` element, creating one in `<head>` if there is none.
+  pub fn set_title(&mut self, title: &str) {
+    let text = dom::create_text(title.to_string());
+    if let Some(path) = self.root.find_path(|node| node.is_element("title")) {
+      let node = self.root.node_at_mut(&path).unwrap();
+      node.children = vec![text];
+      return;
+    }
+    let Some(path) = self.document_element_child_path("head") else {
+      return;
+    };
+    let head = self.root.node_at_mut(&path).unwrap();
+    head.children.push(dom::create_element(
+      "title".to_string(),
+      dom::AtributeMapType::new(),
+      vec![text],
+    ));
+  }
+
+  /// The URL relative references resolve against: the `href` of the first `<base>` element that
+  /// has one, resolved against the document URL, or the document URL itself.
+  // https://html.spec.whatwg.org/multipage/urls-and-fetching.html#document-base-url
+  pub fn base_url(&self) -> Option<URL> {
+    let nodes = self.root.descendants();
+    let base = nodes
+      .into_iter()
+      .find(|node| node.is_element("base") && node.attribute("href").is_some());
+    let Some(href) = base.and_then(|node| node.attribute("href")) else {
+      return self.url.clone();
+    };
+    network::resolve(self.url.as_ref(), href).or_else(|| self.url.clone())
+  }
+
+  /// Resolve `reference` against the document base URL.
+  pub fn resolve_url(&self, reference: &str) -> Option<URL> {
+    network::resolve(self.base_url().as_ref(), reference)
+  }
+
+  /// The `lang` attribute of the document element.
+  pub fn lang(&self) -> Option<String> {
+    self
+      .document_element()
+      .and_then(|node| node.attribute("lang"))
+      .map(|lang| lang.to_string())
+  }
+
+  /// CSS `<style>` and `<link rel="stylesheet">` elements in tree order.
+  pub fn style_sheets(&self) -> Vec<StyleSheetSource> {
+    let mut style_sheets = Vec::new();
+    for node in self.root.descendants() {
+      if is_css_style_element(node) {
+        style_sheets.push(StyleSheetSource::Style(node.text_content()));
+      } else if node.is_element("link") && is_stylesheet_link(node) {
+        if let Some(href) = node.attribute("href") {
+          style_sheets.push(StyleSheetSource::Link(href.to_string()));
+        }
+      }
+    }
+    style_sheets
+  }
+
+  /// Parsed contents of the `<style>` elements, in tree order.
+  pub fn author_style_sheets(&self) -> Vec<css::StyleSheet> {
+    let style_elements = self
+      .root
+      .descendants()
+      .into_iter()
+      .filter(|node| is_css_style_element(node));
+    let sources = style_elements.map(|node| node.text_content());
+    sources
+      .map(|source| CSSParser::new(source).parse_syle_sheet())
+      .collect()
+  }
+
+  /// Style the document element with its `<style>` sheets and `style` attributes.
+  pub fn style_tree(&self) -> Option<StyledNode<'_>> {
+    let style_sheets = self.author_style_sheets();
+    let style_sheets: Vec<&css::StyleSheet> = style_sheets.iter().collect();
+    Some(css::cascade_style_tree(self.document_element()?, &style_sheets))
+  }
+
+  pub fn links(&self) -> Vec<dom::Link> {
+    dom::extract_links(self)
+  }
+
+  pub fn forms(&self) -> Vec<dom::Form<'_>> {
+    dom::forms(&self.root)
+  }
+
+  pub fn metadata(&self) -> dom::Metadata {
+    dom::extract_metadata(self)
+  }
+
+  pub fn outline(&self) -> dom::Outline {
+    dom::outline(&self.root)
+  }
+
+  fn document_element_child(&self, tag_name: &str) -> Option<&Node> {
+    let document_element = self.document_element()?;
+    document_element.children.iter().find(|node| node.is_element(tag_name))
+  }
+
+  fn document_element_child_path(&self, tag_name: &str) -> Option<dom::NodePath> {
+    let root_index = self.root.children.iter().position(|node| node.element().is_some())?;
+    let document_element = &self.root.children[root_index];
+    let child_index = document_element
+      .children
+      .iter()
+      .position(|node| node.is_element(tag_name))?;
+    Some(vec![root_index, child_index])
+  }
+}
+
+// `<style>` elements without a `type`, or with `text/css`; others hold some other language.
+fn is_css_style_element(node: &Node) -> bool {
+  let style_type = node.attribute("type").unwrap_or_default();
+  node.is_element("style") && (style_type.is_empty() || style_type.eq_ignore_ascii_case("text/css"))
+}
+
+fn is_stylesheet_link(node: &Node) -> bool {
+  let rel = node.attribute("rel").unwrap_or_default();
+  rel
+    .split_ascii_whitespace()
+    .any(|keyword| keyword.eq_ignore_ascii_case("stylesheet"))
+}
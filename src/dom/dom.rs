@@ -1,14 +1,15 @@
 #![allow(dead_code)]
 use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::Write;
 
-use serde::{ser::SerializeStruct, Serialize, Serializer};
+use serde::{ser::SerializeStruct, Deserialize, Deserializer, Serialize, Serializer};
 
-#[derive(Debug, Clone, serde::Serialize)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct Node {
   pub children: Vec<Node>,
   pub node_type: NodeType,
 }
-#[derive(Debug, Clone, serde::Serialize)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub enum NodeType {
   Text(String),
   Element(Element),
@@ -16,10 +17,68 @@ pub enum NodeType {
 
 pub type AtributeMapType = HashMap<String, String>;
 
+/// Child indices leading from the document root to a node, e.g. `[0, 2]` is the third child of the
+/// first root node. The empty path is the document itself.
+pub type NodePath = Vec<usize>;
+
 #[derive(Debug, Clone)]
 pub struct Element {
   pub tag_name: String,
   pub atributes: AtributeMapType,
+  // not serialized, as it isn't part of the markup.
+  pub state: ElementState,
+}
+
+/// The dynamic state of an element that user action pseudo-classes like `:hover` match. It is
+/// set from Rust, e.g. by headless tests, through the `HtmlRoot` setters.
+// https://html.spec.whatwg.org/multipage/semantics-other.html#pseudo-classes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ElementState {
+  // the element or one of its descendants is under the pointer.
+  pub hover: bool,
+  // the element or one of its descendants is being activated, e.g. pressed.
+  pub active: bool,
+  pub focus: bool,
+  // the element or one of its descendants has focus.
+  pub focus_within: bool,
+  // the element has focus and the user agent indicates it, e.g. after keyboard navigation.
+  pub focus_visible: bool,
+  pub checked: bool,
+  pub disabled: bool,
+  pub visited: bool,
+  // the element is the target of the document URL's fragment.
+  pub target: bool,
+}
+
+// Elements that can be disabled with the `disabled` attribute.
+// https://html.spec.whatwg.org/multipage/semantics-other.html#concept-element-disabled
+const DISABLEABLE_ELEMENTS: [&str; 7] = [
+  "button", "input", "select", "textarea", "optgroup", "option", "fieldset",
+];
+
+// https://infra.spec.whatwg.org/#namespaces
+pub const HTML_NAMESPACE: &str = "http://www.w3.org/1999/xhtml";
+pub const SVG_NAMESPACE: &str = "http://www.w3.org/2000/svg";
+pub const MATHML_NAMESPACE: &str = "http://www.w3.org/1998/Math/MathML";
+
+// Foreign elements whose children are HTML again.
+// https://html.spec.whatwg.org/multipage/parsing.html#html-integration-point
+const SVG_INTEGRATION_POINTS: [&str; 3] = ["foreignobject", "desc", "title"];
+const MATHML_INTEGRATION_POINTS: [&str; 5] = ["mi", "mo", "mn", "ms", "mtext"];
+
+impl ElementState {
+  /// The initial state given by the markup: `checked` for checked inputs and selected options,
+  /// `disabled` for form controls with a `disabled` attribute.
+  pub fn from_attributes(tag_name: &str, atributes: &AtributeMapType) -> Self {
+    let tag_name = tag_name.to_ascii_lowercase();
+    let checked = match &*tag_name {
+      "input" => atributes.contains_key("checked"),
+      "option" => atributes.contains_key("selected"),
+      _ => false,
+    };
+    let disabled = DISABLEABLE_ELEMENTS.contains(&&*tag_name) && atributes.contains_key("disabled");
+    ElementState { checked, disabled, ..Default::default() }
+  }
 }
 
 // This is a better implementation of Serialize for HashMap? (we use it in snapshot tests)
@@ -36,16 +95,34 @@ impl Serialize for Element {
   }
 }
 
+impl<'de> Deserialize<'de> for Element {
+  fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+  where
+    D: Deserializer<'de>,
+  {
+    // same shape as the `Serialize` impl above.
+    #[derive(serde::Deserialize)]
+    #[serde(rename = "Element")]
+    struct ElementFields {
+      tag_name: String,
+      atributes: AtributeMapType,
+    }
+    let ElementFields { tag_name, atributes } = ElementFields::deserialize(deserializer)?;
+    let state = ElementState::from_attributes(&tag_name, &atributes);
+    Ok(Element { tag_name, atributes, state })
+  }
+}
+
 // Doctype
 //
-#[derive(Debug, Clone, serde::Serialize)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct Doctype {
   pub name: String,
   pub public_id: Option<String>,
   pub system_id: Option<String>,
 }
 
-#[derive(Debug, Clone, serde::Serialize)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct HtmlRoot {
   pub doctype: Option<Doctype>,
   pub children: Vec<Node>,
@@ -55,18 +132,49 @@ pub fn create_text(text: String) -> Node {
   Node { children: Vec::new(), node_type: NodeType::Text(text) }
 }
 pub fn create_element(tag_name: String, atributes: AtributeMapType, children: Vec<Node>) -> Node {
-  Node { children, node_type: NodeType::Element(Element { tag_name, atributes }) }
+  let state = ElementState::from_attributes(&tag_name, &atributes);
+  Node { children, node_type: NodeType::Element(Element { tag_name, atributes, state }) }
 }
 
 impl Element {
-  pub fn id(&self) -> String {
-    self.atributes.get("id").unwrap().to_string()
+  /// The namespace of the element, given its parent element with the parent's namespace: HTML,
+  /// except inside `<svg>` and `<math>`, as the HTML parser would place it.
+  // https://html.spec.whatwg.org/multipage/parsing.html#parsing-main-inforeign
+  pub fn namespace(&self, parent: Option<(&Element, &'static str)>) -> &'static str {
+    let tag_name = self.tag_name.to_ascii_lowercase();
+    let namespace = match parent {
+      Some((parent, namespace)) => {
+        let parent_name = parent.tag_name.to_ascii_lowercase();
+        let integration_point = match namespace {
+          SVG_NAMESPACE => SVG_INTEGRATION_POINTS.contains(&&*parent_name),
+          MATHML_NAMESPACE => MATHML_INTEGRATION_POINTS.contains(&&*parent_name),
+          _ => true,
+        };
+        if integration_point {
+          HTML_NAMESPACE
+        } else {
+          namespace
+        }
+      }
+      None => HTML_NAMESPACE,
+    };
+    match (namespace, &*tag_name) {
+      (HTML_NAMESPACE, "svg") => SVG_NAMESPACE,
+      (HTML_NAMESPACE, "math") => MATHML_NAMESPACE,
+      _ => namespace,
+    }
+  }
+
+  pub fn id(&self) -> Option<&String> {
+    self.atributes.get("id")
   }
 
   pub fn classes(&self) -> HashSet<&str> {
     let mut classes = HashSet::new();
-    for class in self.atributes.get("class").unwrap().split(" ") {
-      classes.insert(class);
+    if let Some(class_list) = self.atributes.get("class") {
+      for class in class_list.split_ascii_whitespace() {
+        classes.insert(class);
+      }
     }
     classes
   }
@@ -74,6 +182,10 @@ impl Element {
   pub fn has_class(&self, class_name: &str) -> bool {
     self.classes().contains(class_name)
   }
+
+  pub fn attribute(&self, name: &str) -> Option<&str> {
+    self.atributes.get(name).map(|value| value.as_str())
+  }
 }
 
 impl Node {
@@ -93,4 +205,196 @@ impl Node {
   pub fn get_children(&self) -> Vec<Node> {
     self.children.clone()
   }
+
+  pub fn element(&self) -> Option<&Element> {
+    match &self.node_type {
+      NodeType::Element(element) => Some(element),
+      NodeType::Text(_) => None,
+    }
+  }
+
+  pub fn element_mut(&mut self) -> Option<&mut Element> {
+    match &mut self.node_type {
+      NodeType::Element(element) => Some(element),
+      NodeType::Text(_) => None,
+    }
+  }
+
+  pub fn is_element(&self, tag_name: &str) -> bool {
+    self
+      .element()
+      .is_some_and(|element| element.tag_name.eq_ignore_ascii_case(tag_name))
+  }
+
+  pub fn attribute(&self, name: &str) -> Option<&str> {
+    self.element().and_then(|element| element.attribute(name))
+  }
+
+  /// Concatenated text of all descendant text nodes, in tree order.
+  pub fn text_content(&self) -> String {
+    match &self.node_type {
+      NodeType::Text(text) => text.clone(),
+      NodeType::Element(_) => self.children.iter().map(|child| child.text_content()).collect(),
+    }
+  }
+
+  /// All descendants of this node in tree order (not including the node itself).
+  pub fn descendants(&self) -> Vec<&Node> {
+    let mut nodes = Vec::new();
+    collect_descendants(&self.children, &mut nodes);
+    nodes
+  }
+}
+
+/// Strip leading and trailing ASCII whitespace and collapse each run of it into a single space.
+// https://infra.spec.whatwg.org/#strip-and-collapse-ascii-whitespace
+pub(crate) fn collapse_whitespace(text: &str) -> String {
+  text.split_ascii_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn collect_descendants<'a>(children: &'a [Node], nodes: &mut Vec<&'a Node>) {
+  for child in children {
+    nodes.push(child);
+    collect_descendants(&child.children, nodes);
+  }
+}
+
+impl HtmlRoot {
+  /// Return the node at `path`, or `None` if the path leads outside the tree (or is empty).
+  pub fn node_at(&self, path: &[usize]) -> Option<&Node> {
+    let (first, rest) = path.split_first()?;
+    let mut node = self.children.get(*first)?;
+    for index in rest {
+      node = node.children.get(*index)?;
+    }
+    Some(node)
+  }
+
+  pub fn node_at_mut(&mut self, path: &[usize]) -> Option<&mut Node> {
+    let (first, rest) = path.split_first()?;
+    let mut node = self.children.get_mut(*first)?;
+    for index in rest {
+      node = node.children.get_mut(*index)?;
+    }
+    Some(node)
+  }
+
+  /// All nodes of the document in tree order.
+  pub fn descendants(&self) -> Vec<&Node> {
+    let mut nodes = Vec::new();
+    collect_descendants(&self.children, &mut nodes);
+    nodes
+  }
+
+  /// Path of the first node (in tree order) that satisfies `predicate`.
+  pub fn find_path(&self, predicate: impl Fn(&Node) -> bool) -> Option<NodePath> {
+    let mut path = Vec::new();
+    find_path_in(&self.children, &predicate, &mut path).then_some(path)
+  }
+
+  /// State of the element at `path`, for the states that any number of elements can have, e.g.
+  /// `checked`. `None` if there's no element at `path`.
+  pub fn state_mut(&mut self, path: &[usize]) -> Option<&mut ElementState> {
+    Some(&mut self.node_at_mut(path)?.element_mut()?.state)
+  }
+
+  /// Put the pointer over the element at `path`, which hovers it and its ancestors, or over
+  /// nothing with `None`.
+  pub fn set_hover(&mut self, path: Option<&[usize]>) {
+    self.designate(path, &|state, _, within| state.hover = within);
+  }
+
+  /// Activate the element at `path` and its ancestors, or nothing with `None`.
+  pub fn set_active(&mut self, path: Option<&[usize]>) {
+    self.designate(path, &|state, _, within| state.active = within);
+  }
+
+  /// Focus the element at `path`, or nothing with `None`. `focus_visible` tells whether the focus
+  /// is indicated, as it is for keyboard focus.
+  pub fn set_focus(&mut self, path: Option<&[usize]>, focus_visible: bool) {
+    self.designate(path, &|state, designated, within| {
+      state.focus = designated;
+      state.focus_visible = designated && focus_visible;
+      state.focus_within = within;
+    });
+  }
+
+  /// Make the element at `path` the target of the URL fragment, or no element with `None`.
+  pub fn set_target(&mut self, path: Option<&[usize]>) {
+    self.designate(path, &|state, designated, _| state.target = designated);
+  }
+
+  // Update a state that a single element has at a time. `update` is called for every element
+  // with whether it is the element at `path` and whether it is that element or an ancestor.
+  fn designate(&mut self, path: Option<&[usize]>, update: &dyn Fn(&mut ElementState, bool, bool)) {
+    designate_in(&mut self.children, path, update, &mut Vec::new());
+  }
+}
+
+fn designate_in(
+  nodes: &mut [Node],
+  path: Option<&[usize]>,
+  update: &dyn Fn(&mut ElementState, bool, bool),
+  current: &mut NodePath,
+) {
+  for (index, node) in nodes.iter_mut().enumerate() {
+    current.push(index);
+    if let Some(element) = node.element_mut() {
+      let designated = path == Some(current.as_slice());
+      let within = path.is_some_and(|path| path.starts_with(current));
+      update(&mut element.state, designated, within);
+    }
+    designate_in(&mut node.children, path, update, current);
+    current.pop();
+  }
+}
+
+fn find_path_in(nodes: &[Node], predicate: &impl Fn(&Node) -> bool, path: &mut NodePath) -> bool {
+  for (index, node) in nodes.iter().enumerate() {
+    path.push(index);
+    if predicate(node) || find_path_in(&node.children, predicate, path) {
+      return true;
+    }
+    path.pop();
+  }
+  false
+}
+
+/// Dump a document in the html5lib tree-construction test format: one `| `-prefixed line per
+/// node, children indented by two spaces and attributes listed in name order under their element.
+// https://github.com/html5lib/html5lib-tests/tree/master/tree-construction
+pub fn dump_dom(root: &HtmlRoot) -> String {
+  let mut output = String::new();
+  if let Some(doctype) = &root.doctype {
+    let _ = write!(output, "| <!DOCTYPE {}", doctype.name);
+    if doctype.public_id.is_some() || doctype.system_id.is_some() {
+      let public_id = doctype.public_id.as_deref().unwrap_or_default();
+      let system_id = doctype.system_id.as_deref().unwrap_or_default();
+      let _ = write!(output, " \"{}\" \"{}\"", public_id, system_id);
+    }
+    output.push_str(">\n");
+  }
+  for node in &root.children {
+    dump_node(node, 0, &mut output);
+  }
+  output
+}
+
+fn dump_node(node: &Node, depth: usize, output: &mut String) {
+  let indent = "  ".repeat(depth);
+  match &node.node_type {
+    NodeType::Text(text) => {
+      let _ = writeln!(output, "| {}\"{}\"", indent, text);
+    }
+    NodeType::Element(element) => {
+      let _ = writeln!(output, "| {}<{}>", indent, element.tag_name);
+      let ordered_attributes: BTreeMap<_, _> = element.atributes.iter().collect();
+      for (name, value) in ordered_attributes {
+        let _ = writeln!(output, "| {}  {}=\"{}\"", indent, name, value);
+      }
+    }
+  }
+  for child in &node.children {
+    dump_node(child, depth + 1, output);
+  }
 }
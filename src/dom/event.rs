@@ -0,0 +1,201 @@
+use std::collections::HashMap;
+
+use crate::dom::{HtmlRoot, Node, NodePath};
+// ==============================
+// https://dom.spec.whatwg.org/#events
+//
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventPhase {
+  None,
+  Capturing,
+  AtTarget,
+  Bubbling,
+}
+
+#[derive(Debug, Clone)]
+pub struct Event {
+  pub event_type: String,
+  pub bubbles: bool,
+  pub cancelable: bool,
+  // text inserted by an `input` event, if any.
+  pub data: Option<String>,
+  pub phase: EventPhase,
+  pub target: Option<NodePath>,
+  pub current_target: Option<NodePath>,
+  default_prevented: bool,
+  propagation_stopped: bool,
+  immediate_propagation_stopped: bool,
+  in_passive_listener: bool,
+}
+
+impl Event {
+  pub fn new(event_type: &str, bubbles: bool, cancelable: bool) -> Self {
+    Event {
+      event_type: event_type.to_string(),
+      bubbles,
+      cancelable,
+      data: None,
+      phase: EventPhase::None,
+      target: None,
+      current_target: None,
+      default_prevented: false,
+      propagation_stopped: false,
+      immediate_propagation_stopped: false,
+      in_passive_listener: false,
+    }
+  }
+
+  pub fn click() -> Self {
+    Event::new("click", true, true)
+  }
+
+  pub fn input(data: &str) -> Self {
+    let mut event = Event::new("input", true, false);
+    event.data = Some(data.to_string());
+    event
+  }
+
+  pub fn stop_propagation(&mut self) {
+    self.propagation_stopped = true;
+  }
+
+  pub fn stop_immediate_propagation(&mut self) {
+    self.propagation_stopped = true;
+    self.immediate_propagation_stopped = true;
+  }
+
+  /// Cancel the event's default action. Ignored for non-cancelable events and inside passive listeners.
+  pub fn prevent_default(&mut self) {
+    if self.cancelable && !self.in_passive_listener {
+      self.default_prevented = true;
+    }
+  }
+
+  pub fn default_prevented(&self) -> bool {
+    self.default_prevented
+  }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EventListenerOptions {
+  pub capture: bool,
+  pub once: bool,
+  pub passive: bool,
+}
+
+/// Called with the event and the node whose listener is running, the event's current target.
+pub type EventListener<'a> = Box<dyn FnMut(&mut Event, &'a Node) + 'a>;
+
+pub type ListenerId = usize;
+
+struct RegisteredListener<'a> {
+  id: ListenerId,
+  event_type: String,
+  options: EventListenerOptions,
+  callback: EventListener<'a>,
+}
+
+/// Listeners live outside the tree, keyed by node path, so `dom::Node` stays plain data. The
+/// dispatcher borrows the tree, so the tree can't be mutated and every path keeps addressing the
+/// same node for as long as the dispatcher exists.
+pub struct EventDispatcher<'a> {
+  root: &'a HtmlRoot,
+  listeners: HashMap<NodePath, Vec<RegisteredListener<'a>>>,
+  next_id: ListenerId,
+}
+
+impl<'a> EventDispatcher<'a> {
+  pub fn new(root: &'a HtmlRoot) -> Self {
+    EventDispatcher { root, listeners: HashMap::new(), next_id: 0 }
+  }
+
+  /// Register `callback` on the node at `path`, or `None` if `path` addresses no node.
+  pub fn add_event_listener(
+    &mut self,
+    path: &[usize],
+    event_type: &str,
+    options: EventListenerOptions,
+    callback: impl FnMut(&mut Event, &'a Node) + 'a,
+  ) -> Option<ListenerId> {
+    self.root.node_at(path)?;
+    let id = self.next_id;
+    self.next_id += 1;
+    let listener = RegisteredListener { id, event_type: event_type.to_string(), options, callback: Box::new(callback) };
+    self.listeners.entry(path.to_vec()).or_default().push(listener);
+    Some(id)
+  }
+
+  pub fn remove_event_listener(&mut self, id: ListenerId) -> bool {
+    for listeners in self.listeners.values_mut() {
+      if let Some(position) = listeners.iter().position(|listener| listener.id == id) {
+        listeners.remove(position);
+        return true;
+      }
+    }
+    false
+  }
+
+  /// Dispatch `event` at the node at `target`, returning `false` if a listener cancelled it.
+  // https://dom.spec.whatwg.org/#concept-event-dispatch
+  pub fn dispatch_event(&mut self, target: &[usize], event: &mut Event) -> bool {
+    event.target = Some(target.to_vec());
+    // ancestors from the outermost element down to the target's parent.
+    let ancestors: Vec<&[usize]> = (1..target.len()).map(|depth| &target[..depth]).collect();
+
+    event.phase = EventPhase::Capturing;
+    for path in ancestors.iter() {
+      if event.propagation_stopped {
+        break;
+      }
+      self.invoke(path, event, true);
+    }
+
+    if !event.propagation_stopped {
+      event.phase = EventPhase::AtTarget;
+      self.invoke(target, event, true);
+      // a capturing listener on the target can stop the non-capturing ones.
+      if !event.propagation_stopped {
+        self.invoke(target, event, false);
+      }
+    }
+
+    if event.bubbles {
+      event.phase = EventPhase::Bubbling;
+      for path in ancestors.iter().rev() {
+        if event.propagation_stopped {
+          break;
+        }
+        self.invoke(path, event, false);
+      }
+    }
+
+    event.phase = EventPhase::None;
+    event.current_target = None;
+    !event.default_prevented
+  }
+
+  // Run the listeners of one node; `capture` selects capturing or non-capturing listeners.
+  fn invoke(&mut self, path: &[usize], event: &mut Event, capture: bool) {
+    let (Some(listeners), Some(node)) = (self.listeners.get_mut(path), self.root.node_at(path)) else {
+      return;
+    };
+    event.current_target = Some(path.to_vec());
+    let mut fired_once = Vec::new();
+    for listener in listeners.iter_mut() {
+      if event.immediate_propagation_stopped {
+        break;
+      }
+      if listener.event_type != event.event_type || listener.options.capture != capture {
+        continue;
+      }
+      if listener.options.once {
+        fired_once.push(listener.id);
+      }
+      event.in_passive_listener = listener.options.passive;
+      (listener.callback)(event, node);
+      event.in_passive_listener = false;
+    }
+    listeners.retain(|listener| !fired_once.contains(&listener.id));
+  }
+}
@@ -0,0 +1,312 @@
+#![allow(dead_code)]
+use std::collections::HashMap;
+
+use crate::dom::{collapse_whitespace, HtmlRoot, Node, NodePath};
+// ==============================
+// https://html.spec.whatwg.org/multipage/forms.html
+//
+
+const LISTED_ELEMENTS: [&str; 4] = ["button", "input", "select", "textarea"];
+
+#[derive(Debug, Clone)]
+pub struct Form<'a> {
+  pub node: &'a Node,
+  pub path: NodePath,
+  // associated controls in tree order.
+  pub controls: Vec<FormControl<'a>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct FormControl<'a> {
+  pub node: &'a Node,
+  pub path: NodePath,
+  // disabled by its own attribute or by an ancestor `<fieldset disabled>`.
+  pub disabled: bool,
+  in_datalist: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FormValue {
+  Text(String),
+  // a file input; only the file name is known from the DOM.
+  File(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FormDataEntry {
+  pub name: String,
+  pub value: FormValue,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FormData {
+  pub entries: Vec<FormDataEntry>,
+}
+
+/// All `<form>` elements of the document with their associated controls. A control belongs to the
+/// form named by its `form` attribute, or otherwise to its nearest ancestor form.
+// https://html.spec.whatwg.org/multipage/form-control-infrastructure.html#form-owner
+pub fn forms(root: &HtmlRoot) -> Vec<Form<'_>> {
+  let mut walker = FormWalker { forms: Vec::new(), forms_by_id: HashMap::new(), path: Vec::new() };
+  walker.collect_forms(&root.children);
+  for (index, node) in root.children.iter().enumerate() {
+    walker.collect_controls(index, node, None, false, false);
+  }
+  walker.forms
+}
+
+/// Whether `fieldset` is a `<fieldset disabled>` that disables the controls inside its child at
+/// `child_index`, which is every child but its first `<legend>`.
+// https://html.spec.whatwg.org/multipage/form-elements.html#concept-fieldset-disabled
+pub fn fieldset_disables_child(fieldset: &Node, child_index: usize) -> bool {
+  if !fieldset.is_element("fieldset") || fieldset.attribute("disabled").is_none() {
+    return false;
+  }
+  let first_legend = fieldset.children.iter().position(|child| child.is_element("legend"));
+  first_legend != Some(child_index)
+}
+
+struct FormWalker<'a> {
+  forms: Vec<Form<'a>>,
+  forms_by_id: HashMap<&'a str, usize>,
+  path: NodePath,
+}
+
+impl<'a> FormWalker<'a> {
+  fn collect_forms(&mut self, nodes: &'a [Node]) {
+    for (index, node) in nodes.iter().enumerate() {
+      self.path.push(index);
+      if node.is_element("form") {
+        if let Some(id) = node.attribute("id") {
+          self.forms_by_id.entry(id).or_insert(self.forms.len());
+        }
+        self
+          .forms
+          .push(Form { node, path: self.path.clone(), controls: Vec::new() });
+      }
+      self.collect_forms(&node.children);
+      self.path.pop();
+    }
+  }
+
+  // `form` is the nearest ancestor form and `disabled` whether a `<fieldset disabled>` applies.
+  fn collect_controls(&mut self, index: usize, node: &'a Node, form: Option<usize>, disabled: bool, in_datalist: bool) {
+    self.path.push(index);
+    let mut form = form;
+    if node.is_element("form") {
+      form = self.forms.iter().position(|candidate| candidate.path == self.path);
+    }
+    if LISTED_ELEMENTS.iter().any(|tag_name| node.is_element(tag_name)) {
+      let owner = match node.attribute("form") {
+        Some(id) => self.forms_by_id.get(id).copied(),
+        None => form,
+      };
+      if let Some(owner) = owner {
+        let disabled = disabled || node.attribute("disabled").is_some();
+        let control = FormControl { node, path: self.path.clone(), disabled, in_datalist };
+        self.forms[owner].controls.push(control);
+      }
+    }
+    let in_datalist = in_datalist || node.is_element("datalist");
+    for (child_index, child) in node.children.iter().enumerate() {
+      let child_disabled = disabled || fieldset_disables_child(node, child_index);
+      self.collect_controls(child_index, child, form, child_disabled, in_datalist);
+    }
+    self.path.pop();
+  }
+}
+
+impl<'a> Form<'a> {
+  pub fn action(&self) -> String {
+    self.node.attribute("action").unwrap_or_default().to_string()
+  }
+
+  /// `get`, `post` or `dialog`; invalid values fall back to `get`.
+  pub fn method(&self) -> String {
+    let method = self.node.attribute("method").unwrap_or_default().to_ascii_lowercase();
+    match &*method {
+      "post" | "dialog" => method,
+      _ => "get".to_string(),
+    }
+  }
+
+  pub fn enctype(&self) -> String {
+    let enctype = self.node.attribute("enctype").unwrap_or_default().to_ascii_lowercase();
+    match &*enctype {
+      "multipart/form-data" | "text/plain" => enctype,
+      _ => "application/x-www-form-urlencoded".to_string(),
+    }
+  }
+
+  /// Construct the entry list that submitting the form with `submitter` (a button path) would send.
+  // https://html.spec.whatwg.org/multipage/form-control-infrastructure.html#constructing-the-form-data-set
+  pub fn form_data(&self, submitter: Option<&[usize]>) -> FormData {
+    let mut form_data = FormData::default();
+    for control in &self.controls {
+      if control.in_datalist || control.disabled {
+        continue;
+      }
+      let node = control.node;
+      let is_submitter = submitter == Some(control.path.as_slice());
+      let input_type = node.attribute("type").unwrap_or_default().to_ascii_lowercase();
+      if is_button(node, &input_type) && !is_submitter {
+        continue;
+      }
+      if node.is_element("input") && matches!(&*input_type, "checkbox" | "radio") && node.attribute("checked").is_none()
+      {
+        continue;
+      }
+      let name = node.attribute("name").unwrap_or_default();
+      if node.is_element("input") && input_type == "image" {
+        let prefix = if name.is_empty() {
+          String::new()
+        } else {
+          format!("{}.", name)
+        };
+        form_data.append(&format!("{}x", prefix), FormValue::Text("0".to_string()));
+        form_data.append(&format!("{}y", prefix), FormValue::Text("0".to_string()));
+        continue;
+      }
+      if name.is_empty() {
+        continue;
+      }
+      if node.is_element("select") {
+        for option in selected_options(node) {
+          form_data.append(name, FormValue::Text(option_value(option)));
+        }
+      } else if node.is_element("textarea") {
+        form_data.append(name, FormValue::Text(node.text_content()));
+      } else if node.is_element("input") && input_type == "file" {
+        form_data.append(name, FormValue::File(String::new()));
+      } else if node.is_element("input") && matches!(&*input_type, "checkbox" | "radio") {
+        form_data.append(
+          name,
+          FormValue::Text(node.attribute("value").unwrap_or("on").to_string()),
+        );
+      } else if node.is_element("input") && input_type == "hidden" && name.eq_ignore_ascii_case("_charset_") {
+        form_data.append(name, FormValue::Text("UTF-8".to_string()));
+      } else {
+        form_data.append(
+          name,
+          FormValue::Text(node.attribute("value").unwrap_or_default().to_string()),
+        );
+      }
+    }
+    form_data
+  }
+}
+
+fn is_button(node: &Node, input_type: &str) -> bool {
+  if node.is_element("button") {
+    return true;
+  }
+  node.is_element("input") && matches!(input_type, "submit" | "reset" | "button" | "image")
+}
+
+// Options with the `selected` attribute; a single-select with none selected picks its first
+// enabled option.
+// https://html.spec.whatwg.org/multipage/form-elements.html#selectedness-setting-algorithm
+fn selected_options(select: &Node) -> Vec<&Node> {
+  let options: Vec<&Node> = select
+    .descendants()
+    .into_iter()
+    .filter(|node| node.is_element("option"))
+    .collect();
+  let enabled = |option: &&Node| option.attribute("disabled").is_none();
+  let mut selected: Vec<&Node> = options
+    .iter()
+    .copied()
+    .filter(|option| option.attribute("selected").is_some())
+    .collect();
+  let is_multiple = select.attribute("multiple").is_some();
+  let display_size = select
+    .attribute("size")
+    .and_then(|size| size.parse::<usize>().ok())
+    .unwrap_or(1);
+  if !is_multiple && display_size <= 1 {
+    if selected.is_empty() {
+      selected.extend(options.iter().copied().find(enabled));
+    }
+    // only the last selected option wins in a single-select.
+    selected = selected.split_off(selected.len().saturating_sub(1));
+  }
+  selected.into_iter().filter(enabled).collect()
+}
+
+fn option_value(option: &Node) -> String {
+  match option.attribute("value") {
+    Some(value) => value.to_string(),
+    None => collapse_whitespace(&option.text_content()),
+  }
+}
+
+impl FormData {
+  pub fn append(&mut self, name: &str, value: FormValue) {
+    self.entries.push(FormDataEntry { name: name.to_string(), value });
+  }
+
+  /// Encode as `application/x-www-form-urlencoded`. Files are sent by name only.
+  // https://url.spec.whatwg.org/#concept-urlencoded-serializer
+  pub fn to_urlencoded(&self) -> String {
+    let pairs = self.entries.iter().map(|entry| {
+      let value = match &entry.value {
+        FormValue::Text(text) => text,
+        FormValue::File(filename) => filename,
+      };
+      format!(
+        "{}={}",
+        urlencode(&normalize_newlines(&entry.name)),
+        urlencode(&normalize_newlines(value))
+      )
+    });
+    pairs.collect::<Vec<_>>().join("&")
+  }
+
+  /// Encode as `multipart/form-data` using `boundary`, which must not occur in any entry.
+  // https://html.spec.whatwg.org/multipage/form-control-infrastructure.html#multipart-form-data
+  pub fn to_multipart(&self, boundary: &str) -> String {
+    let mut body = String::new();
+    for entry in &self.entries {
+      body.push_str(&format!("--{}\r\n", boundary));
+      let name = escape_multipart_name(&normalize_newlines(&entry.name));
+      match &entry.value {
+        FormValue::Text(text) => {
+          body.push_str(&format!("Content-Disposition: form-data; name=\"{}\"\r\n\r\n", name));
+          body.push_str(&normalize_newlines(text));
+        }
+        FormValue::File(filename) => {
+          let filename = escape_multipart_name(filename);
+          body.push_str(&format!(
+            "Content-Disposition: form-data; name=\"{}\"; filename=\"{}\"\r\n",
+            name, filename
+          ));
+          body.push_str("Content-Type: application/octet-stream\r\n\r\n");
+        }
+      }
+      body.push_str("\r\n");
+    }
+    body.push_str(&format!("--{}--\r\n", boundary));
+    body
+  }
+}
+
+// Convert every line break (CR, LF or CRLF) to CRLF.
+fn normalize_newlines(text: &str) -> String {
+  text.replace("\r\n", "\n").replace('\r', "\n").replace('\n', "\r\n")
+}
+
+fn urlencode(text: &str) -> String {
+  let mut encoded = String::new();
+  for byte in text.bytes() {
+    match byte {
+      b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'*' | b'-' | b'.' | b'_' => encoded.push(byte as char),
+      b' ' => encoded.push('+'),
+      _ => encoded.push_str(&format!("%{:02X}", byte)),
+    }
+  }
+  encoded
+}
+
+fn escape_multipart_name(name: &str) -> String {
+  name.replace('\n', "%0A").replace('\r', "%0D").replace('"', "%22")
+}
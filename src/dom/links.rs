@@ -0,0 +1,175 @@
+#![allow(dead_code)]
+use crate::css::{self, Token};
+use crate::dom::{Document, Node, NodePath};
+use crate::network::{self, URL};
+// ==============================
+// Outgoing references of a document, resolved against its base URL.
+//
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LinkKind {
+  // `<a href>` and `<area href>`
+  Hyperlink,
+  // `<img src>`, `srcset` candidates and `<source srcset>`
+  Image,
+  // `<script src>`
+  Script,
+  // `<link rel="stylesheet">`
+  Stylesheet,
+  // `<link rel="icon">` and `<link rel="apple-touch-icon">`
+  Icon,
+  // any other `<link>`, with its `rel`
+  Link(String),
+  // `url()` and `@import` in `<style>` elements and `style` attributes
+  StyleUrl,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Link {
+  pub kind: LinkKind,
+  // the reference as written in the document.
+  pub href: String,
+  // `href` resolved against the document base URL, if it could be.
+  pub url: Option<URL>,
+  // the element the reference was found on.
+  pub path: NodePath,
+}
+
+/// Every outgoing reference of `document` in tree order.
+pub fn extract_links(document: &Document) -> Vec<Link> {
+  let base_url = document.base_url();
+  let mut extractor = LinkExtractor { base_url, links: Vec::new(), path: Vec::new() };
+  for (index, node) in document.root.children.iter().enumerate() {
+    extractor.visit(index, node);
+  }
+  extractor.links
+}
+
+struct LinkExtractor {
+  base_url: Option<URL>,
+  links: Vec<Link>,
+  path: NodePath,
+}
+
+impl LinkExtractor {
+  fn visit(&mut self, index: usize, node: &Node) {
+    self.path.push(index);
+    if let Some(element) = node.element() {
+      match &*element.tag_name.to_ascii_lowercase() {
+        "a" | "area" => self.push_attribute(node, "href", LinkKind::Hyperlink),
+        "img" => {
+          self.push_attribute(node, "src", LinkKind::Image);
+          self.push_srcset(node);
+        }
+        "source" => self.push_srcset(node),
+        "script" => self.push_attribute(node, "src", LinkKind::Script),
+        "link" => {
+          let kind = link_kind(node.attribute("rel").unwrap_or_default());
+          self.push_attribute(node, "href", kind);
+        }
+        "style" => {
+          for href in css_urls(&node.text_content()) {
+            self.push(LinkKind::StyleUrl, &href);
+          }
+        }
+        _ => {}
+      }
+      for href in css_urls(node.attribute("style").unwrap_or_default()) {
+        self.push(LinkKind::StyleUrl, &href);
+      }
+    }
+    for (child_index, child) in node.children.iter().enumerate() {
+      self.visit(child_index, child);
+    }
+    self.path.pop();
+  }
+
+  fn push_attribute(&mut self, node: &Node, attribute: &str, kind: LinkKind) {
+    if let Some(href) = node.attribute(attribute) {
+      self.push(kind, href);
+    }
+  }
+
+  fn push_srcset(&mut self, node: &Node) {
+    for href in srcset_urls(node.attribute("srcset").unwrap_or_default()) {
+      self.push(LinkKind::Image, &href);
+    }
+  }
+
+  fn push(&mut self, kind: LinkKind, href: &str) {
+    let href = href.trim();
+    if href.is_empty() {
+      return;
+    }
+    let url = network::resolve(self.base_url.as_ref(), href);
+    self
+      .links
+      .push(Link { kind, href: href.to_string(), url, path: self.path.clone() });
+  }
+}
+
+fn link_kind(rel: &str) -> LinkKind {
+  let keywords: Vec<String> = rel
+    .split_ascii_whitespace()
+    .map(|keyword| keyword.to_ascii_lowercase())
+    .collect();
+  if keywords.iter().any(|keyword| keyword == "stylesheet") {
+    return LinkKind::Stylesheet;
+  }
+  if keywords
+    .iter()
+    .any(|keyword| keyword == "icon" || keyword == "apple-touch-icon")
+  {
+    return LinkKind::Icon;
+  }
+  LinkKind::Link(keywords.join(" "))
+}
+
+/// URLs of the image candidates in a `srcset` attribute.
+// https://html.spec.whatwg.org/multipage/images.html#parsing-a-srcset-attribute
+pub fn srcset_urls(srcset: &str) -> Vec<String> {
+  let mut urls = Vec::new();
+  let mut rest = srcset;
+  loop {
+    rest = rest.trim_start_matches(|character: char| character.is_ascii_whitespace() || character == ',');
+    if rest.is_empty() {
+      return urls;
+    }
+    let url_end = rest
+      .find(|character: char| character.is_ascii_whitespace())
+      .unwrap_or(rest.len());
+    let url = &rest[..url_end];
+    rest = &rest[url_end..];
+    // a trailing comma ends the candidate; otherwise skip the descriptors up to the next comma.
+    if url.ends_with(',') {
+      urls.push(url.trim_end_matches(',').to_string());
+    } else {
+      urls.push(url.to_string());
+      rest = rest.find(',').map(|comma| &rest[comma + 1..]).unwrap_or_default();
+    }
+  }
+}
+
+/// Values of `url(...)` functions and `@import` strings in a stylesheet or declaration list, as
+/// the CSS tokenizer sees them, so comments and strings that merely contain `url(` don't count.
+// https://www.w3.org/TR/css-values-4/#urls
+pub fn css_urls(css: &str) -> Vec<String> {
+  let tokens: Vec<Token> = css::tokenize(css)
+    .into_iter()
+    .filter(|token| *token != Token::Whitespace)
+    .collect();
+  let mut urls = Vec::new();
+  for (index, token) in tokens.iter().enumerate() {
+    let url = match (token, tokens.get(index + 1)) {
+      (Token::Url(url), _) => url,
+      // `url("...")`, and `@import "..."`; `@import url(...)` is found as a URL token or function.
+      (Token::Function(name), Some(Token::String(url))) if name.eq_ignore_ascii_case("url") => url,
+      (Token::AtKeyword(name), Some(Token::String(url))) if name.eq_ignore_ascii_case("import") => url,
+      _ => continue,
+    };
+    if !url.is_empty() {
+      urls.push(url.clone());
+    }
+  }
+  urls
+}
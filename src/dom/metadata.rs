@@ -0,0 +1,324 @@
+#![allow(dead_code)]
+use std::collections::{BTreeMap, HashMap};
+
+use crate::dom::{collapse_whitespace, Document, Node};
+use crate::network::{self, URL};
+// ==============================
+// Page metadata used for link previews.
+// https://ogp.me/
+// https://developer.x.com/en/docs/twitter-for-websites/cards/overview/markup
+// https://www.w3.org/TR/json-ld11/#embedding-json-ld-in-html-documents
+// https://html.spec.whatwg.org/multipage/microdata.html
+//
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Metadata {
+  pub description: Option<String>,
+  // `<link rel="canonical">`, resolved against the document base URL.
+  pub canonical: Option<URL>,
+  pub icons: Vec<Icon>,
+  // `content` of every `<meta name>` and `<meta property>`, by lowercased name in tree order.
+  pub meta: BTreeMap<String, Vec<String>>,
+  pub open_graph: OpenGraph,
+  pub twitter: TwitterCard,
+  pub json_ld: Vec<JsonLd>,
+  // top-level microdata items in tree order.
+  pub microdata: Vec<MicrodataItem>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Icon {
+  // the `rel` keywords, lowercased, e.g. `icon` or `apple-touch-icon`.
+  pub rel: String,
+  pub href: String,
+  pub url: Option<URL>,
+  pub sizes: Option<String>,
+  pub mime_type: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OpenGraph {
+  pub title: Option<String>,
+  pub description: Option<String>,
+  pub url: Option<String>,
+  pub site_name: Option<String>,
+  // `og:type`
+  pub kind: Option<String>,
+  pub locale: Option<String>,
+  pub images: Vec<OpenGraphImage>,
+}
+
+// `og:image` followed by its `og:image:*` structured properties.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OpenGraphImage {
+  pub url: String,
+  pub secure_url: Option<String>,
+  pub mime_type: Option<String>,
+  pub width: Option<u32>,
+  pub height: Option<u32>,
+  pub alt: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TwitterCard {
+  pub card: Option<String>,
+  pub site: Option<String>,
+  pub creator: Option<String>,
+  pub title: Option<String>,
+  pub description: Option<String>,
+  pub image: Option<String>,
+  pub image_alt: Option<String>,
+}
+
+// A `<script type="application/ld+json">` block.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsonLd {
+  pub raw: String,
+  // `None` if the block isn't valid JSON.
+  pub value: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MicrodataItem {
+  // `itemtype` URLs.
+  pub types: Vec<String>,
+  // `itemid`
+  pub id: Option<String>,
+  // values by property name, in tree order.
+  pub properties: BTreeMap<String, Vec<MicrodataValue>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MicrodataValue {
+  Text(String),
+  Item(MicrodataItem),
+}
+
+/// Collect the metadata of `document`. URLs are resolved against its base URL.
+pub fn extract_metadata(document: &Document) -> Metadata {
+  let base_url = document.base_url();
+  let resolve = |href: &str| network::resolve(base_url.as_ref(), href);
+  let mut metadata = Metadata::default();
+  let nodes = document.root.descendants();
+  for node in nodes.iter() {
+    if node.is_element("meta") {
+      let content = node.attribute("content").map(|content| content.trim().to_string());
+      let keys = [node.attribute("name"), node.attribute("property")];
+      for key in keys.into_iter().flatten() {
+        if let Some(content) = &content {
+          metadata
+            .meta
+            .entry(key.trim().to_ascii_lowercase())
+            .or_default()
+            .push(content.clone());
+        }
+      }
+    } else if node.is_element("link") {
+      let rel = rel_keywords(node);
+      let href = node.attribute("href").unwrap_or_default().trim();
+      if href.is_empty() {
+        continue;
+      }
+      if rel.iter().any(|keyword| keyword == "canonical") && metadata.canonical.is_none() {
+        metadata.canonical = resolve(href);
+      }
+      if rel
+        .iter()
+        .any(|keyword| keyword == "icon" || keyword == "apple-touch-icon")
+      {
+        metadata.icons.push(Icon {
+          rel: rel.join(" "),
+          href: href.to_string(),
+          url: resolve(href),
+          sizes: node.attribute("sizes").map(|sizes| sizes.to_string()),
+          mime_type: node.attribute("type").map(|mime_type| mime_type.to_string()),
+        });
+      }
+    } else if node.is_element("script")
+      && node
+        .attribute("type")
+        .is_some_and(|script_type| script_type.trim().eq_ignore_ascii_case("application/ld+json"))
+    {
+      let raw = node.text_content().trim().to_string();
+      let value = serde_json::from_str(&raw).ok();
+      metadata.json_ld.push(JsonLd { raw, value });
+    }
+  }
+  metadata.description = first(&metadata.meta, "description");
+  metadata.open_graph = open_graph(&nodes);
+  metadata.twitter = twitter_card(&metadata.meta);
+  metadata.microdata = MicrodataExtractor::new(base_url.clone(), &nodes).top_level_items(&nodes);
+  metadata
+}
+
+fn first(meta: &BTreeMap<String, Vec<String>>, key: &str) -> Option<String> {
+  let values = meta.get(key)?;
+  values.iter().find(|value| !value.is_empty()).cloned()
+}
+
+fn rel_keywords(node: &Node) -> Vec<String> {
+  let rel = node.attribute("rel").unwrap_or_default();
+  rel
+    .split_ascii_whitespace()
+    .map(|keyword| keyword.to_ascii_lowercase())
+    .collect()
+}
+
+// Open Graph properties are read in order so `og:image:*` attach to the preceding `og:image`.
+fn open_graph(nodes: &[&Node]) -> OpenGraph {
+  let mut open_graph = OpenGraph::default();
+  let metas = nodes.iter().filter(|node| node.is_element("meta"));
+  for meta in metas {
+    let Some(property) = meta.attribute("property").or_else(|| meta.attribute("name")) else {
+      continue;
+    };
+    let property = property.trim().to_ascii_lowercase();
+    let content = meta.attribute("content").unwrap_or_default().trim().to_string();
+    if content.is_empty() {
+      continue;
+    }
+    let set = |field: &mut Option<String>| {
+      field.get_or_insert(content.clone());
+    };
+    match &*property {
+      "og:title" => set(&mut open_graph.title),
+      "og:description" => set(&mut open_graph.description),
+      "og:url" => set(&mut open_graph.url),
+      "og:site_name" => set(&mut open_graph.site_name),
+      "og:type" => set(&mut open_graph.kind),
+      "og:locale" => set(&mut open_graph.locale),
+      "og:image" => open_graph
+        .images
+        .push(OpenGraphImage { url: content, ..OpenGraphImage::default() }),
+      _ => {
+        let Some(image) = open_graph.images.last_mut() else {
+          continue;
+        };
+        match &*property {
+          // the same URL as `og:image`.
+          "og:image:url" => image.url = content,
+          "og:image:secure_url" => image.secure_url = Some(content),
+          "og:image:type" => image.mime_type = Some(content),
+          "og:image:width" => image.width = content.parse().ok(),
+          "og:image:height" => image.height = content.parse().ok(),
+          "og:image:alt" => image.alt = Some(content),
+          _ => {}
+        }
+      }
+    }
+  }
+  open_graph
+}
+
+fn twitter_card(meta: &BTreeMap<String, Vec<String>>) -> TwitterCard {
+  TwitterCard {
+    card: first(meta, "twitter:card"),
+    site: first(meta, "twitter:site"),
+    creator: first(meta, "twitter:creator"),
+    title: first(meta, "twitter:title"),
+    description: first(meta, "twitter:description"),
+    image: first(meta, "twitter:image").or_else(|| first(meta, "twitter:image:src")),
+    image_alt: first(meta, "twitter:image:alt"),
+  }
+}
+
+struct MicrodataExtractor<'a> {
+  base_url: Option<URL>,
+  // elements by `id`, for `itemref`; the first element with an id wins.
+  ids: HashMap<&'a str, &'a Node>,
+  // items being built, to stop `itemref` cycles.
+  item_stack: Vec<&'a Node>,
+}
+
+impl<'a> MicrodataExtractor<'a> {
+  fn new(base_url: Option<URL>, nodes: &[&'a Node]) -> Self {
+    let mut ids = HashMap::new();
+    for node in nodes {
+      if let Some(id) = node.attribute("id") {
+        ids.entry(id).or_insert(*node);
+      }
+    }
+    MicrodataExtractor { base_url, ids, item_stack: Vec::new() }
+  }
+
+  // https://html.spec.whatwg.org/multipage/microdata.html#top-level-microdata-items
+  fn top_level_items(&mut self, nodes: &[&'a Node]) -> Vec<MicrodataItem> {
+    let top_level = nodes
+      .iter()
+      .filter(|node| node.attribute("itemscope").is_some() && node.attribute("itemprop").is_none());
+    top_level.map(|node| self.item(node)).collect()
+  }
+
+  fn item(&mut self, node: &'a Node) -> MicrodataItem {
+    self.item_stack.push(node);
+    let mut item = MicrodataItem {
+      types: node
+        .attribute("itemtype")
+        .unwrap_or_default()
+        .split_ascii_whitespace()
+        .map(|item_type| item_type.to_string())
+        .collect(),
+      id: node.attribute("itemid").map(|id| id.trim().to_string()),
+      properties: BTreeMap::new(),
+    };
+    let mut roots: Vec<&'a Node> = node.children.iter().collect();
+    for id in node.attribute("itemref").unwrap_or_default().split_ascii_whitespace() {
+      if let Some(referenced) = self.ids.get(id) {
+        roots.push(referenced);
+      }
+    }
+    for root in roots {
+      self.crawl(root, &mut item);
+    }
+    self.item_stack.pop();
+    item
+  }
+
+  // Add the properties of `node` and its descendants, without entering nested items.
+  fn crawl(&mut self, node: &'a Node, item: &mut MicrodataItem) {
+    if node.element().is_none() {
+      return;
+    }
+    if let Some(names) = node.attribute("itemprop") {
+      if let Some(value) = self.property_value(node) {
+        for name in names.split_ascii_whitespace() {
+          item.properties.entry(name.to_string()).or_default().push(value.clone());
+        }
+      }
+    }
+    if node.attribute("itemscope").is_some() {
+      return;
+    }
+    for child in node.children.iter() {
+      self.crawl(child, item);
+    }
+  }
+
+  // https://html.spec.whatwg.org/multipage/microdata.html#values
+  fn property_value(&mut self, node: &'a Node) -> Option<MicrodataValue> {
+    if node.attribute("itemscope").is_some() {
+      if self.item_stack.iter().any(|item| std::ptr::eq(*item, node)) {
+        return None;
+      }
+      return Some(MicrodataValue::Item(self.item(node)));
+    }
+    let attribute_value = |name: &str| node.attribute(name).unwrap_or_default().to_string();
+    let value = match &*node.tag_name().to_ascii_lowercase() {
+      "meta" => attribute_value("content"),
+      "audio" | "embed" | "iframe" | "img" | "source" | "track" | "video" => self.url_value(node, "src"),
+      "a" | "area" | "link" => self.url_value(node, "href"),
+      "object" => self.url_value(node, "data"),
+      "data" | "meter" => attribute_value("value"),
+      "time" if node.attribute("datetime").is_some() => attribute_value("datetime"),
+      _ => collapse_whitespace(&node.text_content()),
+    };
+    Some(MicrodataValue::Text(value))
+  }
+
+  // The absolute URL in `attribute`, or the empty string if it can't be resolved.
+  fn url_value(&self, node: &Node, attribute: &str) -> String {
+    let href = node.attribute(attribute).unwrap_or_default().trim();
+    let url = network::resolve(self.base_url.as_ref(), href);
+    url.map(|url| url.to_string()).unwrap_or_default()
+  }
+}
@@ -1,2 +1,15 @@
+mod document;
+#[allow(clippy::module_inception)]
 mod dom;
+mod event;
+mod form;
+mod links;
+mod metadata;
+mod outline;
+pub use document::*;
 pub use dom::*;
+pub use event::*;
+pub use form::*;
+pub use links::*;
+pub use metadata::*;
+pub use outline::*;
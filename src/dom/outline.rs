@@ -0,0 +1,237 @@
+#![allow(dead_code)]
+use std::fmt::Write;
+
+use crate::dom::{collapse_whitespace, HtmlRoot, Node, NodePath};
+// ==============================
+// Document outline from sectioning elements and headings.
+// https://html.spec.whatwg.org/multipage/sections.html#headings-and-outlines
+// https://www.w3.org/TR/2014/REC-html5-20141028/sections.html#outlines
+//
+
+const SECTIONING_ELEMENTS: [&str; 4] = ["section", "article", "nav", "aside"];
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SectionKind {
+  // the `<body>`, or the document element if there is none.
+  Body,
+  Section,
+  Article,
+  Nav,
+  Aside,
+  // started by a heading inside another section.
+  Implicit,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Heading {
+  // 1 for `<h1>` through 6 for `<h6>`.
+  pub level: u8,
+  pub text: String,
+  pub id: Option<String>,
+  pub path: NodePath,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Section {
+  pub kind: SectionKind,
+  // the first heading of the section, if it has one.
+  pub heading: Option<Heading>,
+  // the sectioning element, or the heading of an implicit section.
+  pub path: NodePath,
+  pub children: Vec<Section>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Anchor {
+  pub id: String,
+  pub tag_name: String,
+  pub path: NodePath,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Outline {
+  pub root: Section,
+  // every heading in tree order.
+  pub headings: Vec<Heading>,
+  // every element with an `id`, in tree order.
+  pub anchors: Vec<Anchor>,
+}
+
+impl Outline {
+  /// Headings more than one level below the heading before them, e.g. an `<h4>` after an `<h2>`.
+  pub fn skipped_heading_levels(&self) -> Vec<&Heading> {
+    let pairs = self.headings.iter().zip(self.headings.iter().skip(1));
+    pairs
+      .filter(|(previous, heading)| heading.level > previous.level + 1)
+      .map(|(_, heading)| heading)
+      .collect()
+  }
+}
+
+// What a sectioning element contains, in tree order, before nesting.
+enum OutlineItem {
+  Heading(Heading),
+  Section(Section),
+}
+
+/// Build the outline of the `<body>` of `root`.
+pub fn outline(root: &HtmlRoot) -> Outline {
+  let body_path = root
+    .find_path(|node| node.is_element("body"))
+    .or_else(|| {
+      root
+        .children
+        .iter()
+        .position(|node| node.element().is_some())
+        .map(|index| vec![index])
+    })
+    .unwrap_or_default();
+  let mut headings = Vec::new();
+  let root_section = match root.node_at(&body_path) {
+    Some(body) if !body_path.is_empty() => {
+      let mut path = body_path.clone();
+      section(SectionKind::Body, body, &mut path, &mut headings)
+    }
+    _ => Section { kind: SectionKind::Body, heading: None, path: Vec::new(), children: Vec::new() },
+  };
+  let mut anchors = Vec::new();
+  let mut path = Vec::new();
+  for (index, node) in root.children.iter().enumerate() {
+    path.push(index);
+    collect_anchors(node, &mut path, &mut anchors);
+    path.pop();
+  }
+  Outline { root: root_section, headings, anchors }
+}
+
+// The section for the sectioning element at `path`. Its first heading names it; every later
+// heading starts an implicit section nested under the last open section of a higher rank.
+fn section(kind: SectionKind, node: &Node, path: &mut NodePath, headings: &mut Vec<Heading>) -> Section {
+  let mut items = Vec::new();
+  for (index, child) in node.children.iter().enumerate() {
+    path.push(index);
+    collect_items(child, path, &mut items, headings);
+    path.pop();
+  }
+  let mut section = Section { kind, heading: None, path: path.clone(), children: Vec::new() };
+  // open implicit sections, outermost first.
+  let mut open: Vec<Section> = Vec::new();
+  for item in items {
+    match item {
+      OutlineItem::Heading(heading) if section.heading.is_none() && open.is_empty() => {
+        section.heading = Some(heading);
+      }
+      OutlineItem::Heading(heading) => {
+        while open
+          .last()
+          .is_some_and(|last| last.heading.as_ref().unwrap().level >= heading.level)
+        {
+          close_last(&mut open, &mut section);
+        }
+        let path = heading.path.clone();
+        open.push(Section { kind: SectionKind::Implicit, heading: Some(heading), path, children: Vec::new() });
+      }
+      OutlineItem::Section(child) => match open.last_mut() {
+        Some(last) => last.children.push(child),
+        None => section.children.push(child),
+      },
+    }
+  }
+  while !open.is_empty() {
+    close_last(&mut open, &mut section);
+  }
+  section
+}
+
+fn close_last(open: &mut Vec<Section>, section: &mut Section) {
+  let last = open.pop().unwrap();
+  match open.last_mut() {
+    Some(parent) => parent.children.push(last),
+    None => section.children.push(last),
+  }
+}
+
+// Headings and sectioning elements under `node`, without entering nested sectioning elements.
+fn collect_items(node: &Node, path: &mut NodePath, items: &mut Vec<OutlineItem>, headings: &mut Vec<Heading>) {
+  let Some(element) = node.element() else {
+    return;
+  };
+  let tag_name = element.tag_name.to_ascii_lowercase();
+  if let Some(level) = heading_level(&tag_name) {
+    let heading = Heading {
+      level,
+      text: collapse_whitespace(&node.text_content()),
+      id: element.id().cloned(),
+      path: path.clone(),
+    };
+    headings.push(heading.clone());
+    items.push(OutlineItem::Heading(heading));
+    return;
+  }
+  if SECTIONING_ELEMENTS.contains(&tag_name.as_str()) {
+    let kind = match &*tag_name {
+      "article" => SectionKind::Article,
+      "nav" => SectionKind::Nav,
+      "aside" => SectionKind::Aside,
+      _ => SectionKind::Section,
+    };
+    items.push(OutlineItem::Section(section(kind, node, path, headings)));
+    return;
+  }
+  for (index, child) in node.children.iter().enumerate() {
+    path.push(index);
+    collect_items(child, path, items, headings);
+    path.pop();
+  }
+}
+
+fn collect_anchors(node: &Node, path: &mut NodePath, anchors: &mut Vec<Anchor>) {
+  let Some(element) = node.element() else {
+    return;
+  };
+  if let Some(id) = element.id().filter(|id| !id.is_empty()) {
+    anchors.push(Anchor { id: id.clone(), tag_name: element.tag_name.to_ascii_lowercase(), path: path.clone() });
+  }
+  for (index, child) in node.children.iter().enumerate() {
+    path.push(index);
+    collect_anchors(child, path, anchors);
+    path.pop();
+  }
+}
+
+fn heading_level(tag_name: &str) -> Option<u8> {
+  match tag_name {
+    "h1" => Some(1),
+    "h2" => Some(2),
+    "h3" => Some(3),
+    "h4" => Some(4),
+    "h5" => Some(5),
+    "h6" => Some(6),
+    _ => None,
+  }
+}
+
+/// Indented dump of the outline, one section per line, e.g. `Section h2 "Intro" [0, 1, 2]`.
+pub fn dump_outline(outline: &Outline) -> String {
+  let mut output = String::new();
+  dump_section(&outline.root, 0, &mut output);
+  output
+}
+
+fn dump_section(section: &Section, depth: usize, output: &mut String) {
+  let heading = match &section.heading {
+    Some(heading) => format!("h{} \"{}\"", heading.level, heading.text),
+    None => "untitled".to_string(),
+  };
+  let _ = writeln!(
+    output,
+    "{}{:?} {} {:?}",
+    "  ".repeat(depth),
+    section.kind,
+    heading,
+    section.path
+  );
+  for child in &section.children {
+    dump_section(child, depth + 1, output);
+  }
+}
@@ -1,2 +1,6 @@
 mod parser;
+mod sanitizer;
+mod serializer;
 pub use parser::HTMLParser;
+pub use sanitizer::Sanitizer;
+pub use serializer::{serialize_nodes, serialize_root, VOID_ELEMENTS};
@@ -13,13 +13,12 @@ impl HTMLParser {
   }
 
   pub fn parse_root_children(&mut self) -> Vec<dom::Node> {
-    let nodes = self.parse_nodes();
-    return nodes;
+    self.parse_nodes()
   }
   pub fn parse_root(&mut self) -> dom::HtmlRoot {
     let doctype = self.parse_doctype();
     let children = self.parse_root_children();
-    return dom::HtmlRoot { doctype, children };
+    dom::HtmlRoot { doctype, children }
   }
 
   pub fn parse_doctype(&mut self) -> Option<dom::Doctype> {
@@ -174,7 +173,7 @@ impl HTMLParser {
   }
 
   fn consume_expect(&mut self, text: &str) {
-    if &self.peek_many(text.len()) == text {
+    if self.peek_many(text.len()) == text {
       self.advance_many(text.len());
     } else {
       panic!("Expected '{}' but got '{}'", text, &self.peek_many(text.len()));
@@ -0,0 +1,290 @@
+#![allow(dead_code)]
+use std::collections::{HashMap, HashSet};
+
+use crate::css::{self, CSSParser, Token};
+use crate::dom::{self, NodeType};
+use crate::html::serializer;
+// ==============================
+// Allowlist-based sanitizer for untrusted HTML.
+// Disallowed elements are unwrapped (their children are kept) unless their contents are unsafe
+// too, e.g. `<script>`, in which case the whole subtree is dropped.
+//
+
+// attributes that hold a URL and must use an allowed scheme.
+const URL_ATTRIBUTES: [&str; 8] = [
+  "href",
+  "src",
+  "action",
+  "formaction",
+  "cite",
+  "poster",
+  "background",
+  "longdesc",
+];
+
+// elements dropped together with their contents.
+const DROP_CONTENT_ELEMENTS: [&str; 11] = [
+  "script", "style", "template", "iframe", "object", "embed", "applet", "noscript", "frame", "frameset", "title",
+];
+
+const DEFAULT_TAGS: [&str; 40] = [
+  "a",
+  "abbr",
+  "b",
+  "blockquote",
+  "br",
+  "caption",
+  "code",
+  "dd",
+  "del",
+  "div",
+  "dl",
+  "dt",
+  "em",
+  "figcaption",
+  "figure",
+  "h1",
+  "h2",
+  "h3",
+  "h4",
+  "h5",
+  "h6",
+  "hr",
+  "i",
+  "img",
+  "ins",
+  "kbd",
+  "li",
+  "ol",
+  "p",
+  "pre",
+  "q",
+  "s",
+  "small",
+  "span",
+  "strong",
+  "sub",
+  "sup",
+  "table",
+  "u",
+  "ul",
+];
+
+// table parts, allowed with `table`.
+const DEFAULT_TABLE_TAGS: [&str; 5] = ["tbody", "td", "tfoot", "th", "thead"];
+
+const DEFAULT_CSS_PROPERTIES: [&str; 7] = [
+  "color",
+  "background-color",
+  "font-style",
+  "font-weight",
+  "text-align",
+  "text-decoration",
+  "vertical-align",
+];
+
+#[derive(Debug, Clone)]
+pub struct Sanitizer {
+  pub allowed_tags: HashSet<String>,
+  // allowed attributes per tag name; the `*` entry applies to every allowed tag.
+  pub allowed_attributes: HashMap<String, HashSet<String>>,
+  pub allowed_url_schemes: HashSet<String>,
+  // properties kept in `style` attributes, which must also be allowed as attributes.
+  pub allowed_css_properties: HashSet<String>,
+}
+
+impl Default for Sanitizer {
+  fn default() -> Self {
+    Sanitizer::new()
+  }
+}
+
+impl Sanitizer {
+  /// A sanitizer allowing basic formatting, links, images and tables over http(s) and mailto.
+  pub fn new() -> Self {
+    let mut sanitizer = Sanitizer {
+      allowed_tags: DEFAULT_TAGS
+        .iter()
+        .chain(DEFAULT_TABLE_TAGS.iter())
+        .map(|tag| tag.to_string())
+        .collect(),
+      allowed_attributes: HashMap::new(),
+      allowed_url_schemes: ["http", "https", "mailto"]
+        .iter()
+        .map(|scheme| scheme.to_string())
+        .collect(),
+      allowed_css_properties: DEFAULT_CSS_PROPERTIES
+        .iter()
+        .map(|property| property.to_string())
+        .collect(),
+    };
+    for attribute in ["title", "lang", "dir"] {
+      sanitizer.allow_attribute("*", attribute);
+    }
+    for (tag_name, attribute) in [
+      ("a", "href"),
+      ("img", "src"),
+      ("img", "alt"),
+      ("img", "width"),
+      ("img", "height"),
+      ("td", "colspan"),
+      ("td", "rowspan"),
+      ("th", "colspan"),
+      ("th", "rowspan"),
+      ("blockquote", "cite"),
+      ("q", "cite"),
+    ] {
+      sanitizer.allow_attribute(tag_name, attribute);
+    }
+    sanitizer
+  }
+
+  /// A sanitizer that allows nothing; every element is unwrapped to its text.
+  pub fn empty() -> Self {
+    Sanitizer {
+      allowed_tags: HashSet::new(),
+      allowed_attributes: HashMap::new(),
+      allowed_url_schemes: HashSet::new(),
+      allowed_css_properties: HashSet::new(),
+    }
+  }
+
+  pub fn allow_tag(&mut self, tag_name: &str) -> &mut Self {
+    self.allowed_tags.insert(tag_name.to_ascii_lowercase());
+    self
+  }
+
+  pub fn allow_attribute(&mut self, tag_name: &str, attribute: &str) -> &mut Self {
+    let attributes = self
+      .allowed_attributes
+      .entry(tag_name.to_ascii_lowercase())
+      .or_default();
+    attributes.insert(attribute.to_ascii_lowercase());
+    self
+  }
+
+  pub fn allow_url_scheme(&mut self, scheme: &str) -> &mut Self {
+    self.allowed_url_schemes.insert(scheme.to_ascii_lowercase());
+    self
+  }
+
+  pub fn allow_css_property(&mut self, property: &str) -> &mut Self {
+    self.allowed_css_properties.insert(property.to_ascii_lowercase());
+    self
+  }
+
+  pub fn sanitize(&self, nodes: &[dom::Node]) -> Vec<dom::Node> {
+    nodes.iter().flat_map(|node| self.sanitize_node(node)).collect()
+  }
+
+  pub fn sanitize_to_html(&self, nodes: &[dom::Node]) -> String {
+    serializer::serialize_nodes(&self.sanitize(nodes))
+  }
+
+  fn sanitize_node(&self, node: &dom::Node) -> Vec<dom::Node> {
+    let element = match &node.node_type {
+      NodeType::Text(_) => return vec![node.clone()],
+      NodeType::Element(element) => element,
+    };
+    let tag_name = element.tag_name.to_ascii_lowercase();
+    if DROP_CONTENT_ELEMENTS.contains(&tag_name.as_str()) && !self.allowed_tags.contains(&tag_name) {
+      return Vec::new();
+    }
+    let children = self.sanitize(&node.children);
+    if !self.allowed_tags.contains(&tag_name) {
+      return children;
+    }
+    let mut atributes = dom::AtributeMapType::new();
+    for (name, value) in element.atributes.iter() {
+      if let Some(value) = self.sanitize_attribute(&tag_name, &name.to_ascii_lowercase(), value) {
+        atributes.insert(name.to_ascii_lowercase(), value);
+      }
+    }
+    vec![dom::create_element(tag_name, atributes, children)]
+  }
+
+  fn sanitize_attribute(&self, tag_name: &str, name: &str, value: &str) -> Option<String> {
+    // event handlers are never allowed, whatever the configuration says.
+    if name.starts_with("on") {
+      return None;
+    }
+    let is_allowed = |tag_name: &str| {
+      self
+        .allowed_attributes
+        .get(tag_name)
+        .is_some_and(|names| names.contains(name))
+    };
+    if !is_allowed(tag_name) && !is_allowed("*") {
+      return None;
+    }
+    if URL_ATTRIBUTES.contains(&name) && !self.is_allowed_url(value) {
+      return None;
+    }
+    if name == "srcset" {
+      let candidates: Vec<&str> = value.split(',').collect();
+      let urls = candidates
+        .iter()
+        .filter_map(|candidate| candidate.split_whitespace().next());
+      return urls
+        .into_iter()
+        .all(|url| self.is_allowed_url(url))
+        .then(|| value.to_string());
+    }
+    if name == "style" {
+      let style = self.sanitize_style(value);
+      return (!style.is_empty()).then_some(style);
+    }
+    Some(value.to_string())
+  }
+
+  // Relative URLs are allowed; absolute ones need an allowed scheme.
+  fn is_allowed_url(&self, url: &str) -> bool {
+    // browsers ignore control characters and whitespace in a scheme, e.g. `java\tscript:`.
+    let url: String = url
+      .chars()
+      .filter(|character| !character.is_control() && !character.is_whitespace())
+      .collect();
+    let Some(colon) = url.find(':') else {
+      return true;
+    };
+    // a colon after a path, query or fragment character doesn't start a scheme.
+    let scheme = &url[..colon];
+    let is_scheme = scheme.starts_with(|character: char| character.is_ascii_alphabetic())
+      && scheme
+        .chars()
+        .all(|character| character.is_ascii_alphanumeric() || "+-.".contains(character));
+    !is_scheme || self.allowed_url_schemes.contains(&scheme.to_ascii_lowercase())
+  }
+
+  // Keep only allowed properties whose values can't load or run anything. Shorthands are expanded
+  // by the parser, so it's their longhands that must be allowed.
+  fn sanitize_style(&self, style: &str) -> String {
+    let declarations = CSSParser::new(style.to_string()).parse_declaration_list();
+    let declarations: Vec<css::Declaration> = declarations
+      .into_iter()
+      .filter(|declaration| {
+        self
+          .allowed_css_properties
+          .contains(&declaration.name.to_ascii_lowercase())
+      })
+      // check the declaration as it will be written out, which is what the browser parses.
+      .filter(|declaration| {
+        !css::tokenize(&css::serialize_declarations(std::slice::from_ref(declaration)))
+          .iter()
+          .any(is_unsafe_token)
+      })
+      .collect();
+    css::serialize_declarations(&declarations)
+  }
+}
+
+// Tokens that load a resource or run script from a style value.
+fn is_unsafe_token(token: &Token) -> bool {
+  match token {
+    Token::Url(_) | Token::BadUrl => true,
+    Token::Function(name) => ["url", "expression"]
+      .iter()
+      .any(|unsafe_name| name.eq_ignore_ascii_case(unsafe_name)),
+    _ => false,
+  }
+}
@@ -0,0 +1,72 @@
+#![allow(dead_code)]
+use std::collections::BTreeMap;
+
+use crate::dom::{self, NodeType};
+// ==============================
+// https://html.spec.whatwg.org/multipage/parsing.html#serialising-html-fragments
+//
+
+pub const VOID_ELEMENTS: [&str; 14] = [
+  "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "param", "source", "track", "wbr",
+];
+
+// elements whose text children are written without escaping.
+const RAW_TEXT_ELEMENTS: [&str; 7] = ["style", "script", "xmp", "iframe", "noembed", "noframes", "noscript"];
+
+/// Serialize a list of nodes back to HTML. Attributes are written in name order.
+pub fn serialize_nodes(nodes: &[dom::Node]) -> String {
+  let mut output = String::new();
+  for node in nodes {
+    serialize_node(node, false, &mut output);
+  }
+  output
+}
+
+pub fn serialize_root(root: &dom::HtmlRoot) -> String {
+  let mut output = String::new();
+  if let Some(doctype) = &root.doctype {
+    output.push_str(&format!("<!DOCTYPE {}>", doctype.name));
+  }
+  output.push_str(&serialize_nodes(&root.children));
+  output
+}
+
+fn serialize_node(node: &dom::Node, in_raw_text: bool, output: &mut String) {
+  let element = match &node.node_type {
+    NodeType::Text(text) if in_raw_text => return output.push_str(text),
+    NodeType::Text(text) => return output.push_str(&escape_text(text, false)),
+    NodeType::Element(element) => element,
+  };
+  let tag_name = element.tag_name.to_ascii_lowercase();
+  output.push('<');
+  output.push_str(&element.tag_name);
+  let ordered_attributes: BTreeMap<_, _> = element.atributes.iter().collect();
+  for (name, value) in ordered_attributes {
+    output.push_str(&format!(" {}=\"{}\"", name, escape_text(value, true)));
+  }
+  output.push('>');
+  if VOID_ELEMENTS.contains(&tag_name.as_str()) {
+    return;
+  }
+  let is_raw_text = RAW_TEXT_ELEMENTS.contains(&tag_name.as_str());
+  for child in &node.children {
+    serialize_node(child, is_raw_text, output);
+  }
+  output.push_str(&format!("</{}>", element.tag_name));
+}
+
+// https://html.spec.whatwg.org/multipage/parsing.html#escapingString
+fn escape_text(text: &str, attribute_mode: bool) -> String {
+  let mut escaped = String::new();
+  for character in text.chars() {
+    match character {
+      '&' => escaped.push_str("&amp;"),
+      '\u{a0}' => escaped.push_str("&nbsp;"),
+      '"' if attribute_mode => escaped.push_str("&quot;"),
+      '<' if !attribute_mode => escaped.push_str("&lt;"),
+      '>' if !attribute_mode => escaped.push_str("&gt;"),
+      _ => escaped.push(character),
+    }
+  }
+  escaped
+}
@@ -1,16 +1,17 @@
 #![allow(dead_code)]
 
-use crate::css::{DeclarationValue, StyledNode};
+use crate::css::{DeclarationValue, PseudoElement, StyledNode, UnitValue};
+use std::fmt::Write;
 // =============================================
 // https://www.w3.org/TR/CSS2/visuren.html#box-gen
 //
 
 #[derive(Clone, Copy, Default, Debug)]
 pub struct Rect {
-  width: f32,
-  height: f32,
-  y: f32,
-  x: f32,
+  pub width: f32,
+  pub height: f32,
+  pub y: f32,
+  pub x: f32,
 }
 pub enum BoxModalType<'a> {
   BlockNode(&'a StyledNode<'a>),
@@ -37,9 +38,9 @@ pub struct Dimensions {
 }
 
 pub struct LayoutBox<'a> {
-  dimensions: Dimensions,
-  box_modal_type: BoxModalType<'a>,
-  children: Vec<LayoutBox<'a>>,
+  pub dimensions: Dimensions,
+  pub box_modal_type: BoxModalType<'a>,
+  pub children: Vec<LayoutBox<'a>>,
 }
 
 impl<'a> LayoutBox<'a> {
@@ -54,15 +55,214 @@ impl<'a> LayoutBox<'a> {
         // If we've just generated an anonymous block box, keep using it.
         // Otherwise, create a new one.
         match &self.children.last() {
-          Some(LayoutBox { .. }) => {}
+          Some(LayoutBox { box_modal_type: BoxModalType::AnonymousBlock, .. }) => {}
           _ => self.children.push(LayoutBox::new(BoxModalType::AnonymousBlock)),
         }
         self.children.last_mut().unwrap()
       }
     }
   }
+
+  fn get_style_node(&self) -> &'a StyledNode<'a> {
+    match self.box_modal_type {
+      BoxModalType::BlockNode(node) | BoxModalType::InlineNode(node) => node,
+      BoxModalType::AnonymousBlock => panic!("Anonymous block box has no style node"),
+    }
+  }
+
+  /// Lay out a box and its descendants.
+  fn layout(&mut self, containing_block: Dimensions) {
+    match self.box_modal_type {
+      BoxModalType::BlockNode(_) => self.layout_block(containing_block),
+      // todo: inline layout, anonymous blocks only stack their children for now.
+      BoxModalType::InlineNode(_) | BoxModalType::AnonymousBlock => {}
+    }
+  }
+
+  fn layout_block(&mut self, containing_block: Dimensions) {
+    // Child width can depend on parent width, so we need to calculate this box's width before
+    // laying out its children.
+    self.calculate_block_width(containing_block);
+    // Determine where the box is located within its container.
+    self.calculate_block_position(containing_block);
+    // Recursively lay out the children of this box.
+    self.layout_block_children();
+    // Parent height can depend on child height, so `calculate_height` must be called after the
+    // children are laid out.
+    self.calculate_block_height();
+  }
+
+  // http://www.w3.org/TR/CSS2/visudet.html#blockwidth
+  fn calculate_block_width(&mut self, containing_block: Dimensions) {
+    let style = self.get_style_node();
+    let containing_width = containing_block.content.width;
+    // `width` has initial value `auto`.
+    let auto = DeclarationValue::Keyword("auto".to_string());
+    let mut width = resolve_percentage(style.value("width").unwrap_or(auto.clone()), containing_width);
+    // margin, border, and padding have initial value 0.
+    let zero = DeclarationValue::Length(0.0, UnitValue::Px);
+
+    let mut margin_left = resolve_percentage(style.lookup("margin-left", "margin", &zero), containing_width);
+    let mut margin_right = resolve_percentage(style.lookup("margin-right", "margin", &zero), containing_width);
+    let border_left = style.lookup("border-left-width", "border-width", &zero);
+    let border_right = style.lookup("border-right-width", "border-width", &zero);
+    let padding_left = resolve_percentage(style.lookup("padding-left", "padding", &zero), containing_width);
+    let padding_right = resolve_percentage(style.lookup("padding-right", "padding", &zero), containing_width);
+
+    let values = [
+      &margin_left,
+      &margin_right,
+      &border_left,
+      &border_right,
+      &padding_left,
+      &padding_right,
+      &width,
+    ];
+    let total: f32 = values.iter().map(|value| value.to_px()).sum();
+
+    // If width is not auto and the total is wider than the container, treat auto margins as 0.
+    if width != auto && total > containing_block.content.width {
+      if margin_left == auto {
+        margin_left = zero.clone();
+      }
+      if margin_right == auto {
+        margin_right = zero.clone();
+      }
+    }
+
+    // Adjust used values so that the above sum equals `containing_block.width`.
+    // Each arm of the `match` should increase the total width by exactly `underflow`,
+    // and afterward all values should be absolute lengths in px.
+    let underflow = containing_block.content.width - total;
+    match (width == auto, margin_left == auto, margin_right == auto) {
+      // If the values are overconstrained, calculate margin_right.
+      (false, false, false) => {
+        margin_right = DeclarationValue::Length(margin_right.to_px() + underflow, UnitValue::Px);
+      }
+      // If exactly one size is auto, its used value follows from the equality.
+      (false, false, true) => margin_right = DeclarationValue::Length(underflow, UnitValue::Px),
+      (false, true, false) => margin_left = DeclarationValue::Length(underflow, UnitValue::Px),
+      // If width is set to auto, any other auto values become 0.
+      (true, _, _) => {
+        if margin_left == auto {
+          margin_left = zero.clone();
+        }
+        if margin_right == auto {
+          margin_right = zero.clone();
+        }
+        if underflow >= 0.0 {
+          // Expand width to fill the underflow.
+          width = DeclarationValue::Length(underflow, UnitValue::Px);
+        } else {
+          // Width can't be negative. Adjust the right margin instead.
+          width = zero.clone();
+          margin_right = DeclarationValue::Length(margin_right.to_px() + underflow, UnitValue::Px);
+        }
+      }
+      // If margin-left and margin-right are both auto, their used values are equal.
+      (false, true, true) => {
+        margin_left = DeclarationValue::Length(underflow / 2.0, UnitValue::Px);
+        margin_right = DeclarationValue::Length(underflow / 2.0, UnitValue::Px);
+      }
+    }
+
+    let dimensions = &mut self.dimensions;
+    dimensions.content.width = width.to_px();
+    dimensions.padding.left = padding_left.to_px();
+    dimensions.padding.right = padding_right.to_px();
+    dimensions.border.left = border_left.to_px();
+    dimensions.border.right = border_right.to_px();
+    dimensions.margin.left = margin_left.to_px();
+    dimensions.margin.right = margin_right.to_px();
+  }
+
+  // http://www.w3.org/TR/CSS2/visudet.html#normal-block
+  fn calculate_block_position(&mut self, containing_block: Dimensions) {
+    let style = self.get_style_node();
+    // margin, border, and padding have initial value 0.
+    let zero = DeclarationValue::Length(0.0, UnitValue::Px);
+    // Vertical margin and padding percentages also refer to the containing block's width.
+    let lookup = |name: &str, fallback_name: &str| {
+      resolve_percentage(style.lookup(name, fallback_name, &zero), containing_block.content.width).to_px()
+    };
+
+    let dimensions = &mut self.dimensions;
+    // If margin-top or margin-bottom is `auto`, the used value is zero.
+    dimensions.margin.top = lookup("margin-top", "margin");
+    dimensions.margin.bottom = lookup("margin-bottom", "margin");
+    dimensions.border.top = style.lookup("border-top-width", "border-width", &zero).to_px();
+    dimensions.border.bottom = style.lookup("border-bottom-width", "border-width", &zero).to_px();
+    dimensions.padding.top = lookup("padding-top", "padding");
+    dimensions.padding.bottom = lookup("padding-bottom", "padding");
+
+    dimensions.content.x =
+      containing_block.content.x + dimensions.margin.left + dimensions.border.left + dimensions.padding.left;
+    // Position the box below all the previous boxes in the container.
+    dimensions.content.y = containing_block.content.height
+      + containing_block.content.y
+      + dimensions.margin.top
+      + dimensions.border.top
+      + dimensions.padding.top;
+  }
+
+  fn layout_block_children(&mut self) {
+    let dimensions = &mut self.dimensions;
+    for child in &mut self.children {
+      child.layout(*dimensions);
+      // Track the height so each child is laid out below the previous content.
+      dimensions.content.height += child.dimensions.margin_box().height;
+    }
+  }
+
+  fn calculate_block_height(&mut self) {
+    // If the height is set to an explicit length, use that exact length.
+    // Otherwise, just keep the value set by `layout_block_children`.
+    if let Some(height) = self.get_style_node().value("height") {
+      if matches!(&height, DeclarationValue::Length(_, unit) if unit.is_length()) {
+        self.dimensions.content.height = height.to_px();
+      }
+    }
+  }
+}
+
+// http://www.w3.org/TR/CSS2/box.html#margin-properties
+// Percentages of width, margins and padding refer to the width of the containing block.
+fn resolve_percentage(value: DeclarationValue, containing_width: f32) -> DeclarationValue {
+  match value {
+    DeclarationValue::Length(percent, UnitValue::Percent) => {
+      DeclarationValue::Length(percent / 100.0 * containing_width, UnitValue::Px)
+    }
+    value => value,
+  }
+}
+
+impl Rect {
+  pub fn expanded_by(self, edge: EdgeSizes) -> Rect {
+    Rect {
+      x: self.x - edge.left,
+      y: self.y - edge.top,
+      width: self.width + edge.left + edge.right,
+      height: self.height + edge.top + edge.bottom,
+    }
+  }
+}
+
+impl Dimensions {
+  /// The area covered by the content area plus its padding.
+  pub fn padding_box(self) -> Rect {
+    self.content.expanded_by(self.padding)
+  }
+  /// The area covered by the content area plus padding and borders.
+  pub fn border_box(self) -> Rect {
+    self.padding_box().expanded_by(self.border)
+  }
+  /// The area covered by the content area plus padding, borders, and margin.
+  pub fn margin_box(self) -> Rect {
+    self.border_box().expanded_by(self.margin)
+  }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Display {
   Inline,
   Block,
@@ -71,22 +271,22 @@ pub enum Display {
 
 impl<'a> StyledNode<'a> {
   /// Return the specified value of a property if it exists, otherwise `None`.
-  pub fn value(&mut self, name: &str) -> Option<DeclarationValue> {
-    self.specified_values.get(name).map(|value| value.clone())
+  pub fn value(&self, name: &str) -> Option<DeclarationValue> {
+    self.specified_values.get(name).cloned()
   }
 
   /// Return the specified value of property `name`, or property `fallback_name` if that doesn't
   /// exist, or value `default` if neither does.
-  pub fn lookup(&mut self, name: &str, fallback_name: &str, default: &DeclarationValue) -> DeclarationValue {
+  pub fn lookup(&self, name: &str, fallback_name: &str, default: &DeclarationValue) -> DeclarationValue {
     let declaration = self.value(name);
     declaration.unwrap_or_else(|| self.value(fallback_name).unwrap_or_else(|| default.clone()))
   }
 
   /// The value of the `display` property (defaults to inline).
-  pub fn display(&mut self) -> Display {
+  pub fn display(&self) -> Display {
     match self.value("display") {
       Some(DeclarationValue::Keyword(keyword)) => match &*keyword {
-        "block" => Display::Block,
+        "block" | "list-item" => Display::Block,
         "none" => Display::None,
         _ => Display::Inline,
       },
@@ -95,16 +295,78 @@ impl<'a> StyledNode<'a> {
   }
 }
 
+/// Lay out the styled tree inside `containing_block`, usually the viewport. `None` if the root
+/// has `display: none`, which generates no boxes.
+pub fn layout_tree<'a>(node: &'a StyledNode<'a>, mut containing_block: Dimensions) -> Option<LayoutBox<'a>> {
+  // The layout algorithm expects the container height to start at 0.
+  containing_block.content.height = 0.0;
+  let mut root_box = build_layout_tree(node)?;
+  root_box.layout(containing_block);
+  Some(root_box)
+}
+
 // Build the tree of LayoutBoxes, but don't perform any layout calculations yet.
-// fn create_layout_tree<'a>(style_node: &'a mut StyledNode<'a>) -> LayoutBox<'a> {
-//   // Create the root box.
-//   let box_modal = match style_node {
-//     Block => BoxModalType::BlockNode(&style_node.clone()),
-//     Inline => BoxModalType::InlineNode(style_node),
-//     _ => panic!("Root node has display: none."),
-//   };
-
-//   let mut root = LayoutBox::new(box_modal);
-//   return root;
-//   // Create the descendant boxes.
-// }
+fn build_layout_tree<'a>(style_node: &'a StyledNode<'a>) -> Option<LayoutBox<'a>> {
+  // Create the root box.
+  let mut root = LayoutBox::new(match style_node.display() {
+    Display::Block => BoxModalType::BlockNode(style_node),
+    Display::Inline => BoxModalType::InlineNode(style_node),
+    Display::None => return None,
+  });
+  // Create the descendant boxes, with those of the pseudo-elements that generate content around
+  // the children.
+  let pseudo_elements = style_node.pseudo_elements.iter();
+  let pseudo_elements = pseudo_elements.filter(|pseudo_element| pseudo_element.content.is_some());
+  let (after, before): (Vec<_>, Vec<_>) =
+    pseudo_elements.partition(|pseudo_element| pseudo_element.pseudo_element == Some(PseudoElement::After));
+  for child in before.into_iter().chain(&style_node.children).chain(after) {
+    match child.display() {
+      Display::Block => root.children.extend(build_layout_tree(child)),
+      Display::Inline => root.get_inline_container().children.extend(build_layout_tree(child)),
+      Display::None => {} // Skip nodes with `display: none;`
+    }
+  }
+  Some(root)
+}
+
+/// One line per box with its type, node and content rectangle, indented by depth.
+pub fn dump_layout_tree(layout_box: &LayoutBox) -> String {
+  let mut output = String::new();
+  dump_layout_box(layout_box, 0, &mut output);
+  output
+}
+
+fn dump_layout_box(layout_box: &LayoutBox, depth: usize, output: &mut String) {
+  let label = match layout_box.box_modal_type {
+    BoxModalType::BlockNode(style_node) => format!("BlockNode {}", describe_node(style_node)),
+    BoxModalType::InlineNode(style_node) => format!("InlineNode {}", describe_node(style_node)),
+    BoxModalType::AnonymousBlock => "AnonymousBlock".to_string(),
+  };
+  let Rect { x, y, width, height } = layout_box.dimensions.content;
+  let _ = writeln!(
+    output,
+    "{}{} ({}, {}, {}x{})",
+    "  ".repeat(depth),
+    label,
+    x,
+    y,
+    width,
+    height
+  );
+  for child in &layout_box.children {
+    dump_layout_box(child, depth + 1, output);
+  }
+}
+
+fn describe_node(style_node: &StyledNode) -> String {
+  match (style_node.node.text(), style_node.pseudo_element) {
+    (Some(text), _) => format!("{:?}", text),
+    (None, Some(pseudo_element)) => format!(
+      "<{}>{} {:?}",
+      style_node.node.tag_name(),
+      pseudo_element,
+      style_node.content.as_deref().unwrap_or_default()
+    ),
+    (None, None) => format!("<{}>", style_node.node.tag_name()),
+  }
+}
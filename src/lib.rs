@@ -1,11 +1,15 @@
 use crate::html::HTMLParser;
 use css::CSSParser;
 
+pub mod accessibility;
 pub mod css;
+pub mod diagnostics;
 pub mod dom;
 pub mod html;
 pub mod layout;
+pub mod network;
 pub mod paint;
+pub mod reader;
 pub mod utils;
 
 pub fn parse_html(input: String) -> dom::HtmlRoot {
@@ -13,6 +17,10 @@ pub fn parse_html(input: String) -> dom::HtmlRoot {
   parser.parse_root()
 }
 
+pub fn parse_document(input: String) -> dom::Document {
+  dom::Document::new(parse_html(input))
+}
+
 pub fn parse_css(input: String) -> css::StyleSheet {
   let mut parser = CSSParser::new(input);
   parser.parse_syle_sheet()
@@ -1,13 +1,5 @@
-use html::HTMLParser;
+use webcore::{css, dom, layout};
 
-mod css;
-mod diagnostics;
-mod dom;
-mod html;
-mod layout;
-mod network;
-mod paint;
-mod utils;
 fn main() {
   let html = r#"
   <!DOCTYPE html>
@@ -19,7 +11,24 @@ fn main() {
       </div>
     </body>
   </html>"#;
-  let mut html = HTMLParser::new(html.to_string());
-  let node = html.parse_root();
-  println!("{:#?}", node);
+  let css = r#"
+  html, body, h1, div, p { display: block; }
+  body { margin: 8px; }
+  h1 { height: 32px; }
+  .test { padding: 4px; color: #ff0000; }
+  "#;
+  let root = webcore::parse_html(html.to_string());
+  let stylesheet = webcore::parse_css(css.to_string());
+  println!("{}", dom::dump_dom(&root));
+
+  let document_element = root.children.iter().find(|node| node.element().is_some()).unwrap();
+  let styled_root = css::style_tree(document_element, &stylesheet);
+  println!("{}", css::dump_style_tree(&styled_root));
+
+  let mut viewport = layout::Dimensions::default();
+  viewport.content.width = 800.0;
+  viewport.content.height = 600.0;
+  if let Some(layout_root) = layout::layout_tree(&styled_root, viewport) {
+    println!("{}", layout::dump_layout_tree(&layout_root));
+  }
 }
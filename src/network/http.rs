@@ -1,12 +1,230 @@
 #![allow(dead_code)]
+use std::fmt;
+// ==============================
+// https://url.spec.whatwg.org/
+// https://www.rfc-editor.org/rfc/rfc3986#section-5.2
+//
 
+#[derive(Debug, Clone, PartialEq)]
 pub struct URL {
   pub scheme: String,
   pub host: String,
+  // 0 when the URL has no port and its scheme has no default port.
   pub port: u16,
   pub path: String,
+  // without the leading `?`.
   pub query: String,
+  // without the leading `#`.
   pub fragment: String,
 }
 
-impl URL {}
+impl URL {
+  /// Parse an absolute URL, e.g. `https://example.com:8080/a/b?q#top`.
+  pub fn parse(input: &str) -> Option<URL> {
+    let input = clean_input(input);
+    let (scheme, rest) = split_scheme(&input)?;
+    let scheme = scheme.to_ascii_lowercase();
+    let (rest, fragment) = split_once_or_empty(rest, '#');
+    let (rest, query) = split_once_or_empty(rest, '?');
+    let mut url = URL {
+      port: default_port(&scheme),
+      scheme,
+      host: String::new(),
+      path: String::new(),
+      query: percent_encode(query),
+      fragment: percent_encode(fragment),
+    };
+    match rest.strip_prefix("//") {
+      Some(authority_and_path) => {
+        let path_start = authority_and_path.find('/').unwrap_or(authority_and_path.len());
+        url.set_authority(&authority_and_path[..path_start])?;
+        url.path = remove_dot_segments(&percent_encode(&authority_and_path[path_start..]));
+        if url.path.is_empty() && url.is_special() {
+          url.path = "/".to_string();
+        }
+      }
+      None if url.is_special() => {
+        url.path = remove_dot_segments(&percent_encode(&format!("/{}", rest.trim_start_matches('/'))))
+      }
+      // opaque paths such as `mailto:someone@example.com` are kept as written.
+      None => url.path = percent_encode(rest),
+    }
+    Some(url)
+  }
+
+  /// Resolve `reference` (absolute or relative) against this URL.
+  pub fn join(&self, reference: &str) -> Option<URL> {
+    let reference = clean_input(reference);
+    if split_scheme(&reference).is_some() {
+      return URL::parse(&reference);
+    }
+    if reference.starts_with("//") {
+      return URL::parse(&format!("{}:{}", self.scheme, reference));
+    }
+    if !self.has_hierarchical_path() {
+      // only fragment-only references can be resolved against an opaque URL.
+      let fragment = reference.strip_prefix('#')?;
+      return Some(URL { fragment: percent_encode(fragment), ..self.clone() });
+    }
+    let (rest, fragment) = split_once_or_empty(&reference, '#');
+    let mut url = URL { fragment: percent_encode(fragment), ..self.clone() };
+    // an empty or fragment-only reference keeps the base path and query.
+    if rest.is_empty() {
+      return Some(url);
+    }
+    let (path, query) = split_once_or_empty(rest, '?');
+    url.query = percent_encode(query);
+    if path.is_empty() {
+      return Some(url);
+    }
+    let path = percent_encode(path);
+    url.path = if path.starts_with('/') {
+      remove_dot_segments(&path)
+    } else {
+      // merge with the base path, dropping its last segment.
+      let directory = &self.path[..self.path.rfind('/').map(|index| index + 1).unwrap_or(0)];
+      let directory = if directory.is_empty() && !self.host.is_empty() {
+        "/"
+      } else {
+        directory
+      };
+      remove_dot_segments(&format!("{}{}", directory, path))
+    };
+    Some(url)
+  }
+
+  /// `http`, `https`, `ws`, `wss`, `ftp` and `file` URLs always have a host and a path.
+  pub fn is_special(&self) -> bool {
+    matches!(&*self.scheme, "http" | "https" | "ws" | "wss" | "ftp" | "file")
+  }
+
+  fn has_hierarchical_path(&self) -> bool {
+    self.is_special() || self.path.starts_with('/') || !self.host.is_empty()
+  }
+
+  // `host[:port]`; user info is dropped.
+  fn set_authority(&mut self, authority: &str) -> Option<()> {
+    let host_and_port = authority.rsplit('@').next().unwrap_or_default();
+    // keep IPv6 literals such as `[::1]:8080` together.
+    let port_separator = match host_and_port.rfind(']') {
+      Some(bracket) => host_and_port[bracket..].find(':').map(|index| index + bracket),
+      None => host_and_port.rfind(':'),
+    };
+    let (host, port) = match port_separator {
+      Some(index) => (&host_and_port[..index], &host_and_port[index + 1..]),
+      None => (host_and_port, ""),
+    };
+    if !port.is_empty() {
+      self.port = port.parse().ok()?;
+    }
+    self.host = host.to_ascii_lowercase();
+    if self.host.is_empty() && self.is_special() && self.scheme != "file" {
+      return None;
+    }
+    Some(())
+  }
+}
+
+/// Resolve `reference` against `base`, e.g. a document base URL; without one, `reference` must be
+/// absolute.
+pub fn resolve(base: Option<&URL>, reference: &str) -> Option<URL> {
+  match base {
+    Some(base) => base.join(reference),
+    None => URL::parse(reference),
+  }
+}
+
+impl fmt::Display for URL {
+  fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+    write!(formatter, "{}:", self.scheme)?;
+    if !self.host.is_empty() || self.is_special() {
+      write!(formatter, "//{}", self.host)?;
+      if self.port != 0 && self.port != default_port(&self.scheme) {
+        write!(formatter, ":{}", self.port)?;
+      }
+    }
+    write!(formatter, "{}", self.path)?;
+    if !self.query.is_empty() {
+      write!(formatter, "?{}", self.query)?;
+    }
+    if !self.fragment.is_empty() {
+      write!(formatter, "#{}", self.fragment)?;
+    }
+    Ok(())
+  }
+}
+
+fn default_port(scheme: &str) -> u16 {
+  match scheme {
+    "http" | "ws" => 80,
+    "https" | "wss" => 443,
+    "ftp" => 21,
+    _ => 0,
+  }
+}
+
+// Strip leading and trailing C0 controls and spaces, and remove tabs and newlines.
+fn clean_input(input: &str) -> String {
+  let input = input.trim_matches(|character: char| character <= ' ');
+  input
+    .chars()
+    .filter(|character| !matches!(character, '\t' | '\n' | '\r'))
+    .collect()
+}
+
+// `scheme:rest` if `input` starts with a valid scheme.
+fn split_scheme(input: &str) -> Option<(&str, &str)> {
+  let (scheme, rest) = input.split_once(':')?;
+  let mut characters = scheme.chars();
+  let is_scheme = characters
+    .next()
+    .is_some_and(|character| character.is_ascii_alphabetic())
+    && characters.all(|character| character.is_ascii_alphanumeric() || "+-.".contains(character));
+  is_scheme.then_some((scheme, rest))
+}
+
+fn split_once_or_empty(input: &str, separator: char) -> (&str, &str) {
+  input.split_once(separator).unwrap_or((input, ""))
+}
+
+// https://www.rfc-editor.org/rfc/rfc3986#section-5.2.4
+fn remove_dot_segments(path: &str) -> String {
+  let mut segments: Vec<&str> = Vec::new();
+  let parts: Vec<&str> = path.split('/').collect();
+  for (index, segment) in parts.iter().enumerate() {
+    let is_last = index == parts.len() - 1;
+    match *segment {
+      "." | "%2e" | "%2E" => {
+        if is_last {
+          segments.push("");
+        }
+      }
+      ".." | ".%2e" | "%2e." | "%2e%2e" | "%2E%2E" => {
+        if segments.len() > 1 {
+          segments.pop();
+        }
+        if is_last {
+          segments.push("");
+        }
+      }
+      segment => segments.push(segment),
+    }
+  }
+  segments.join("/")
+}
+
+// Percent-encode spaces, controls, non-ASCII and characters that can't appear in a URL.
+fn percent_encode(input: &str) -> String {
+  let mut encoded = String::new();
+  for character in input.chars() {
+    if character.is_ascii_graphic() && !matches!(character, '"' | '<' | '>' | '`' | '{' | '}') {
+      encoded.push(character);
+      continue;
+    }
+    let mut buffer = [0; 4];
+    for byte in character.encode_utf8(&mut buffer).bytes() {
+      encoded.push_str(&format!("%{:02X}", byte));
+    }
+  }
+  encoded
+}
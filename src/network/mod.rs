@@ -1 +1,2 @@
 mod http;
+pub use http::{resolve, URL};
@@ -1,4 +1,5 @@
 #![allow(dead_code)]
 
 pub mod canvas;
+#[allow(clippy::module_inception)]
 mod paint;
@@ -0,0 +1,307 @@
+#![allow(dead_code)]
+use std::collections::HashMap;
+
+use crate::dom::{self, collapse_whitespace, extract_metadata, Document, Metadata, Node, NodePath, NodeType};
+// ==============================
+// Main content extraction, after Mozilla's Readability.
+// https://github.com/mozilla/readability/blob/main/Readability.js
+//
+
+// class and id hints, matched as substrings of the lowercased attribute.
+const UNLIKELY_CANDIDATES: [&str; 21] = [
+  "banner",
+  "breadcrumbs",
+  "combx",
+  "comment",
+  "community",
+  "cover-wrap",
+  "disqus",
+  "extra",
+  "footer",
+  "gdpr",
+  "header",
+  "menu",
+  "related",
+  "remark",
+  "rss",
+  "share",
+  "shoutbox",
+  "sidebar",
+  "social",
+  "sponsor",
+  "popup",
+];
+const MAYBE_CANDIDATES: [&str; 6] = ["and", "article", "body", "column", "content", "main"];
+const POSITIVE_HINTS: [&str; 10] = [
+  "article", "body", "content", "entry", "hentry", "main", "page", "post", "text", "story",
+];
+const NEGATIVE_HINTS: [&str; 15] = [
+  "hidden", "banner", "combx", "comment", "com-", "contact", "footer", "masthead", "media", "meta", "promo", "related",
+  "shoutbox", "sidebar", "widget",
+];
+const BYLINE_HINTS: [&str; 4] = ["byline", "author", "dateline", "writtenby"];
+
+// elements removed before scoring; they never hold the main content.
+const REMOVED_ELEMENTS: [&str; 12] = [
+  "script", "style", "noscript", "iframe", "form", "nav", "aside", "footer", "button", "input", "select", "textarea",
+];
+const BLOCK_ELEMENTS: [&str; 12] = [
+  "address",
+  "article",
+  "blockquote",
+  "div",
+  "dl",
+  "figure",
+  "ol",
+  "p",
+  "pre",
+  "section",
+  "table",
+  "ul",
+];
+
+// paragraphs shorter than this don't count towards their container's score.
+const MIN_PARAGRAPH_LENGTH: usize = 25;
+
+#[derive(Debug, Clone)]
+pub struct Article {
+  pub title: String,
+  pub byline: Option<String>,
+  pub excerpt: Option<String>,
+  // a `<div>` wrapping the cleaned main content.
+  pub content: Node,
+  pub text_content: String,
+}
+
+/// Find the main content of `document`, or `None` if it has no body.
+pub fn extract_article(document: &Document) -> Option<Article> {
+  let body = document.body().or_else(|| document.document_element())?;
+  let metadata = extract_metadata(document);
+  let byline = find_byline(&metadata, body);
+  let mut body = body.clone();
+  remove_unlikely_candidates(&mut body);
+
+  let mut scores = HashMap::new();
+  score_paragraphs(&body, &mut Vec::new(), &mut scores);
+  // scale each candidate by how much of its text isn't links.
+  for (path, score) in scores.iter_mut() {
+    *score *= 1.0 - link_density(node_at(&body, path));
+  }
+  let top_candidate = scores
+    .iter()
+    // on ties, prefer the candidate that comes first in tree order.
+    .max_by(|(left_path, left), (right_path, right)| left.total_cmp(right).then_with(|| right_path.cmp(left_path)))
+    .map(|(path, score)| (path.clone(), *score))
+    .unwrap_or((Vec::new(), 0.0));
+
+  let mut content = dom::create_element(
+    "div".to_string(),
+    dom::AtributeMapType::new(),
+    gather_content(&body, &top_candidate, &scores),
+  );
+  clean_content(&mut content);
+  let text_content = collapse_whitespace(&content.text_content());
+  let excerpt = meta_content(&metadata, &["description", "og:description", "twitter:description"]).or_else(|| {
+    let paragraph = content.descendants().into_iter().find(|node| node.is_element("p"));
+    paragraph
+      .map(|node| collapse_whitespace(&node.text_content()))
+      .filter(|text| !text.is_empty())
+  });
+  Some(Article { title: article_title(document, &content), byline, excerpt, content, text_content })
+}
+
+fn node_at<'a>(root: &'a Node, path: &[usize]) -> &'a Node {
+  path.iter().fold(root, |node, index| &node.children[*index])
+}
+
+// Drop scripts, navigation and elements whose class or id marks them as page furniture.
+fn remove_unlikely_candidates(node: &mut Node) {
+  node.children.retain(|child| {
+    let Some(element) = child.element() else {
+      return true;
+    };
+    let tag_name = element.tag_name.to_ascii_lowercase();
+    if REMOVED_ELEMENTS.contains(&tag_name.as_str()) {
+      return false;
+    }
+    let hints = class_and_id(child);
+    let is_unlikely = UNLIKELY_CANDIDATES.iter().any(|hint| hints.contains(hint));
+    let is_maybe = MAYBE_CANDIDATES.iter().any(|hint| hints.contains(hint));
+    !is_unlikely || is_maybe || tag_name == "body"
+  });
+  for child in node.children.iter_mut() {
+    remove_unlikely_candidates(child);
+  }
+}
+
+// Score paragraph-like elements and add the score to their ancestors: fully to the parent, half to
+// the grandparent and a third of that higher up.
+fn score_paragraphs(root: &Node, path: &mut NodePath, scores: &mut HashMap<NodePath, f32>) {
+  let node = node_at(root, path);
+  for index in 0..node.children.len() {
+    path.push(index);
+    let child = &node.children[index];
+    let text = collapse_whitespace(&child.text_content());
+    if is_paragraph(child) && text.len() >= MIN_PARAGRAPH_LENGTH {
+      let content_score = 1.0 + text.matches(',').count() as f32 + (text.len() as f32 / 100.0).min(3.0);
+      for level in 1..=path.len().min(3) {
+        let ancestor_path = &path[..path.len() - level];
+        let divider = match level {
+          1 => 1.0,
+          2 => 2.0,
+          _ => level as f32 * 3.0,
+        };
+        let ancestor = node_at(root, ancestor_path);
+        let score = scores
+          .entry(ancestor_path.to_vec())
+          .or_insert_with(|| initial_score(ancestor));
+        *score += content_score / divider;
+      }
+    }
+    score_paragraphs(root, path, scores);
+    path.pop();
+  }
+}
+
+fn initial_score(node: &Node) -> f32 {
+  let tag_score = match &*node.tag_name().to_ascii_lowercase() {
+    "div" | "article" | "section" | "main" => 5.0,
+    "pre" | "td" | "blockquote" => 3.0,
+    "address" | "ol" | "ul" | "dl" | "dd" | "dt" | "li" | "form" => -3.0,
+    "h1" | "h2" | "h3" | "h4" | "h5" | "h6" | "th" => -5.0,
+    _ => 0.0,
+  };
+  tag_score + class_weight(node)
+}
+
+// +25 for each of class and id that looks like content, -25 for each that looks like furniture.
+fn class_weight(node: &Node) -> f32 {
+  let mut weight = 0.0;
+  for attribute in ["class", "id"] {
+    let value = node.attribute(attribute).unwrap_or_default().to_ascii_lowercase();
+    if value.is_empty() {
+      continue;
+    }
+    if NEGATIVE_HINTS.iter().any(|hint| value.contains(hint)) {
+      weight -= 25.0;
+    }
+    if POSITIVE_HINTS.iter().any(|hint| value.contains(hint)) {
+      weight += 25.0;
+    }
+  }
+  weight
+}
+
+// `<p>`, `<pre>`, `<td>` and `<div>`s without block children, which are paragraphs in disguise.
+fn is_paragraph(node: &Node) -> bool {
+  if node.is_element("p") || node.is_element("pre") || node.is_element("td") {
+    return true;
+  }
+  node.is_element("div")
+    && !node
+      .descendants()
+      .into_iter()
+      .any(|child| BLOCK_ELEMENTS.iter().any(|tag| child.is_element(tag)))
+}
+
+/// Share of the text of `node` that is inside links.
+fn link_density(node: &Node) -> f32 {
+  let text_length = collapse_whitespace(&node.text_content()).len();
+  if text_length == 0 {
+    return 0.0;
+  }
+  let links = node.descendants().into_iter().filter(|child| child.is_element("a"));
+  let link_length: usize = links.map(|link| collapse_whitespace(&link.text_content()).len()).sum();
+  link_length as f32 / text_length as f32
+}
+
+// The top candidate plus siblings that score well or read like paragraphs.
+fn gather_content(body: &Node, top_candidate: &(NodePath, f32), scores: &HashMap<NodePath, f32>) -> Vec<Node> {
+  let (top_path, top_score) = top_candidate;
+  let Some((top_index, parent_path)) = top_path.split_last() else {
+    return body.children.clone();
+  };
+  let parent = node_at(body, parent_path);
+  let threshold = (top_score * 0.2).max(10.0);
+  let mut content = Vec::new();
+  for (index, sibling) in parent.children.iter().enumerate() {
+    if index == *top_index {
+      content.push(sibling.clone());
+      continue;
+    }
+    let mut sibling_path = parent_path.to_vec();
+    sibling_path.push(index);
+    let score = scores.get(&sibling_path).copied().unwrap_or(0.0);
+    let text = collapse_whitespace(&sibling.text_content());
+    let density = link_density(sibling);
+    let is_good_paragraph = sibling.is_element("p")
+      && ((text.len() > 80 && density < 0.25) || (!text.is_empty() && density == 0.0 && text.ends_with('.')));
+    if score >= threshold || is_good_paragraph {
+      content.push(sibling.clone());
+    }
+  }
+  content
+}
+
+// Remove link-heavy or negatively hinted blocks left inside the content.
+fn clean_content(node: &mut Node) {
+  node.children.retain(|child| {
+    let is_container = ["div", "section", "ul", "ol", "table"]
+      .iter()
+      .any(|tag| child.is_element(tag));
+    !is_container || (class_weight(child) >= 0.0 && link_density(child) <= 0.5)
+  });
+  for child in node.children.iter_mut() {
+    clean_content(child);
+  }
+}
+
+// The `<title>` without a trailing ` | Site name`, falling back to the first heading.
+fn article_title(document: &Document, content: &Node) -> String {
+  let title = document.title();
+  for separator in [" | ", " - ", " — ", " :: ", " / "] {
+    if let Some((head, _)) = title.rsplit_once(separator) {
+      if head.split_whitespace().count() >= 3 {
+        return head.to_string();
+      }
+    }
+  }
+  if !title.is_empty() {
+    return title;
+  }
+  let heading = content
+    .descendants()
+    .into_iter()
+    .find(|node| node.is_element("h1") || node.is_element("h2"));
+  heading
+    .map(|node| collapse_whitespace(&node.text_content()))
+    .unwrap_or_default()
+}
+
+fn find_byline(metadata: &Metadata, body: &Node) -> Option<String> {
+  if let Some(author) = meta_content(metadata, &["author", "article:author"]) {
+    return Some(author);
+  }
+  let candidates = body.descendants().into_iter().filter(|node| match &node.node_type {
+    NodeType::Element(_) => {
+      node.attribute("rel") == Some("author") || BYLINE_HINTS.iter().any(|hint| class_and_id(node).contains(hint))
+    }
+    NodeType::Text(_) => false,
+  });
+  let bylines = candidates.map(|node| collapse_whitespace(&node.text_content()));
+  bylines.into_iter().find(|text| !text.is_empty() && text.len() < 100)
+}
+
+// First non-empty `content` of a `<meta name>` or `<meta property>` with one of `names`.
+fn meta_content(metadata: &Metadata, names: &[&str]) -> Option<String> {
+  let contents = names.iter().filter_map(|name| metadata.meta.get(*name)).flatten();
+  contents
+    .map(|content| collapse_whitespace(content))
+    .find(|text| !text.is_empty())
+}
+
+fn class_and_id(node: &Node) -> String {
+  let class = node.attribute("class").unwrap_or_default();
+  let id = node.attribute("id").unwrap_or_default();
+  format!("{} {}", class, id).to_ascii_lowercase()
+}
@@ -1,10 +1,7 @@
 #![allow(dead_code)]
 pub fn is_tag_char(character: char) -> bool {
   // TODO: Include U+00A0 and higher.
-  match character {
-    'a'..='z' | 'A'..='Z' | '0'..='9' | '-' | '_' => true,
-    _ => false,
-  }
+  matches!(character, 'a'..='z' | 'A'..='Z' | '0'..='9' | '-' | '_')
 }
 
 pub fn is_color_pattern(pattern: &str) -> bool {
@@ -17,5 +14,5 @@ pub fn is_color_pattern(pattern: &str) -> bool {
   if pattern.contains("hsl") {
     return true;
   }
-  return pattern.starts_with("#");
+  pattern.starts_with("#")
 }
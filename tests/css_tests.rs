@@ -0,0 +1,1070 @@
+#![allow(dead_code, unused_imports, clippy::needless_return)]
+
+use webcore::css::{self, tokenize, CSSParser, DeclarationValue, Token};
+
+fn create_syle_sheet(source_code: &str) -> css::StyleSheet {
+  return CSSParser::new(source_code.to_string()).parse_syle_sheet();
+}
+
+fn declarations(style_sheet: &css::StyleSheet) -> Vec<(String, String)> {
+  let declarations = style_sheet.rules.iter().flat_map(|rule| rule.declarations.iter());
+  return declarations
+    .map(|declaration| (declaration.name.clone(), declaration.value.to_string()))
+    .collect();
+}
+
+#[test]
+fn test_tokenize_numbers() {
+  assert_eq!(
+    tokenize("12 -3.5 +.5e2 10% 1.5em 1e3px"),
+    vec![
+      Token::Number { value: 12.0, is_integer: true },
+      Token::Whitespace,
+      Token::Number { value: -3.5, is_integer: false },
+      Token::Whitespace,
+      Token::Number { value: 50.0, is_integer: false },
+      Token::Whitespace,
+      Token::Percentage(10.0),
+      Token::Whitespace,
+      Token::Dimension { value: 1.5, is_integer: false, unit: "em".to_string() },
+      Token::Whitespace,
+      Token::Dimension { value: 1000.0, is_integer: false, unit: "px".to_string() },
+    ]
+  );
+}
+
+#[test]
+fn test_tokenize_identifiers_and_hashes() {
+  assert_eq!(
+    tokenize("-webkit-box #main #123 @media rgb( --x \\31 0"),
+    vec![
+      Token::Ident("-webkit-box".to_string()),
+      Token::Whitespace,
+      Token::Hash { value: "main".to_string(), is_id: true },
+      Token::Whitespace,
+      Token::Hash { value: "123".to_string(), is_id: false },
+      Token::Whitespace,
+      Token::AtKeyword("media".to_string()),
+      Token::Whitespace,
+      Token::Function("rgb".to_string()),
+      Token::Whitespace,
+      Token::Ident("--x".to_string()),
+      Token::Whitespace,
+      Token::Ident("10".to_string()),
+    ]
+  );
+}
+
+#[test]
+fn test_tokenize_strings_and_urls() {
+  assert_eq!(
+    tokenize("\"a\\\"b\" 'c\nurl( img.png ) url(\"q.png\") url(a b)"),
+    vec![
+      Token::String("a\"b".to_string()),
+      Token::Whitespace,
+      Token::BadString,
+      Token::Whitespace,
+      Token::Url("img.png".to_string()),
+      Token::Whitespace,
+      Token::Function("url".to_string()),
+      Token::String("q.png".to_string()),
+      Token::CloseParen,
+      Token::Whitespace,
+      Token::BadUrl,
+    ]
+  );
+}
+
+#[test]
+fn test_tokenize_punctuation() {
+  assert_eq!(
+    tokenize("<!-- a{b:c;}[d], --> ~"),
+    vec![
+      Token::CDO,
+      Token::Whitespace,
+      Token::Ident("a".to_string()),
+      Token::OpenCurly,
+      Token::Ident("b".to_string()),
+      Token::Colon,
+      Token::Ident("c".to_string()),
+      Token::Semicolon,
+      Token::CloseCurly,
+      Token::OpenSquare,
+      Token::Ident("d".to_string()),
+      Token::CloseSquare,
+      Token::Comma,
+      Token::Whitespace,
+      Token::CDC,
+      Token::Whitespace,
+      Token::Delim('~'),
+    ]
+  );
+}
+
+#[test]
+fn test_parse_rules_with_component_values() {
+  let style_sheet = create_syle_sheet(
+    r#"
+    @import url(print.css);
+    @media screen { p { color: blue; } }
+    <!-- p, .note { content: "a; b"; background: url(bg.png) no-repeat; color: #ff0000 } -->
+    div { font-family: "Helvetica Neue", sans-serif; transform: rotate(45deg) }
+    "#,
+  );
+  assert_eq!(style_sheet.rules.len(), 2);
+  assert_eq!(
+    declarations(&style_sheet),
+    vec![
+      ("content".to_string(), "\"a; b\"".to_string()),
+      ("background-color".to_string(), "rgba(0, 0, 0, 0)".to_string()),
+      ("background-image".to_string(), "url(bg.png)".to_string()),
+      ("background-position".to_string(), "0% 0%".to_string()),
+      ("background-size".to_string(), "auto".to_string()),
+      ("background-repeat".to_string(), "no-repeat".to_string()),
+      ("background-attachment".to_string(), "scroll".to_string()),
+      ("background-origin".to_string(), "padding-box".to_string()),
+      ("background-clip".to_string(), "border-box".to_string()),
+      ("color".to_string(), "rgb(255, 0, 0)".to_string()),
+      ("font-family".to_string(), "\"Helvetica Neue\", sans-serif".to_string()),
+      ("transform".to_string(), "rotate(45deg)".to_string()),
+    ]
+  );
+  assert_eq!(
+    style_sheet.rules[0].declarations[9].value,
+    DeclarationValue::ColorValue(css::ColorValue::RBGColorValue(255, 0, 0, 255))
+  );
+}
+
+#[test]
+fn test_parse_declaration_list() {
+  let declarations = CSSParser::new("color: red; ; margin:0;display : block".to_string()).parse_declaration_list();
+  let declarations: Vec<(String, String)> = declarations
+    .into_iter()
+    .map(|declaration| (declaration.name, declaration.value.to_string()))
+    .collect();
+  assert_eq!(
+    declarations,
+    vec![
+      ("color".to_string(), "rgb(255, 0, 0)".to_string()),
+      ("margin-top".to_string(), "0".to_string()),
+      ("margin-right".to_string(), "0".to_string()),
+      ("margin-bottom".to_string(), "0".to_string()),
+      ("margin-left".to_string(), "0".to_string()),
+      ("display".to_string(), "block".to_string()),
+    ]
+  );
+}
+
+#[test]
+fn test_error_recovery_reports_diagnostics() {
+  let mut parser = CSSParser::new(
+    r#"
+    p { color: red; width: 10px }
+    p > > b, a { color: blue }
+    @font-face { font-family: x; src: url(x.woff) }
+    div {
+      *zoom: 1;
+      color green;
+      content: "broken
+      ;
+      : none;
+      display: block;
+      margin: ;
+    }
+    @media print { p { color: black } }
+    span { color: red; } }
+    a { color: blue
+    "#
+    .to_string(),
+  );
+  let style_sheet = parser.parse_syle_sheet();
+  assert_eq!(
+    declarations(&style_sheet),
+    vec![
+      ("color".to_string(), "rgb(255, 0, 0)".to_string()),
+      ("width".to_string(), "10px".to_string()),
+      ("display".to_string(), "block".to_string()),
+      ("color".to_string(), "rgb(255, 0, 0)".to_string()),
+    ]
+  );
+  assert_eq!(
+    parser.diagnostics.errors,
+    vec![
+      "Dropped rule with invalid selector 'p > > b, a'",
+      "Dropped invalid declaration '*zoom: 1': expected a property name",
+      "Dropped invalid declaration 'color green': expected ':'",
+      "Dropped invalid declaration 'content: ': unterminated string",
+      "Dropped invalid declaration ': none': expected a property name",
+      "Dropped invalid declaration 'margin:': empty value",
+      "Dropped rule with invalid selector '} a'",
+    ]
+  );
+  assert_eq!(
+    parser.diagnostics.warnings,
+    vec![
+      "Skipped unsupported at-rule '@font-face'",
+      "Skipped unsupported at-rule '@media'",
+      "Unclosed block, expected '}'",
+    ]
+  );
+}
+
+#[test]
+fn test_comments_are_skipped() {
+  let style_sheet = create_syle_sheet(
+    "/* header */ p /* type */ , /**/.note/* class */{ /* start */ color /* name */ : /* value */ red /* end */ ; \
+     margin: 0 /* gap */ auto; /* unterminated",
+  );
+  assert_eq!(style_sheet.rules.len(), 1);
+  assert_eq!(style_sheet.rules[0].selectors.len(), 2);
+  assert!(style_sheet.comments.is_empty());
+  assert_eq!(
+    declarations(&style_sheet),
+    vec![
+      ("color".to_string(), "rgb(255, 0, 0)".to_string()),
+      ("margin-top".to_string(), "0".to_string()),
+      ("margin-right".to_string(), "auto".to_string()),
+      ("margin-bottom".to_string(), "0".to_string()),
+      ("margin-left".to_string(), "auto".to_string()),
+    ]
+  );
+  let inline = CSSParser::new("color: /* inline */ blue".to_string()).parse_declaration_list();
+  assert_eq!(inline[0].value.to_string(), "rgb(0, 0, 255)");
+}
+
+#[test]
+fn test_preserved_comments_are_serialized() {
+  let source_code =
+    "/*! License: MIT */\n/* dropped */\np { color: red; }\n.a { /*! inside */ color: blue }\n/*! end */";
+  let style_sheet = CSSParser::with_preserved_comments(source_code.to_string()).parse_syle_sheet();
+  let comments: Vec<(&str, usize)> = style_sheet
+    .comments
+    .iter()
+    .map(|comment| (comment.text.as_str(), comment.rule_index))
+    .collect();
+  assert_eq!(comments, vec![("! License: MIT ", 0), ("! inside ", 2), ("! end ", 2)]);
+  assert_eq!(
+    css::serialize_style_sheet(&style_sheet),
+    "/*! License: MIT */\np { color: rgb(255, 0, 0); }\n.a { color: rgb(0, 0, 255); }\n/*! inside */\n/*! end */\n"
+  );
+  assert!(create_syle_sheet(source_code).comments.is_empty());
+}
+
+#[test]
+fn test_selector_list_round_trips_in_source_order() {
+  let source_code = "b, #a, .c > p { color: red; }\n";
+  let style_sheet = create_syle_sheet(source_code);
+  assert_eq!(
+    css::serialize_style_sheet(&style_sheet),
+    "b, #a, .c > p { color: rgb(255, 0, 0); }\n"
+  );
+  // a rule applies with the highest specificity of its matching selectors.
+  let html_root = webcore::parse_html(r#"<div><b id="a" class="x">a</b></div>"#.to_string());
+  let style_sheet = create_syle_sheet("b, #a { display: block; } b.x { display: inline; }");
+  let styled_root = css::style_tree(&html_root.children[0], &style_sheet);
+  assert_eq!(styled_root.children[0].value("display").unwrap().to_string(), "block");
+}
+
+#[test]
+fn test_parse_numeric_values() {
+  let mut parser = CSSParser::new(
+    "p { width: 10px; height: -1.5E2vh; font-size: 1.2rem; margin-top: 2Q; rotate: .25turn; \
+     transition-duration: 200ms; line-height: 1.5; z-index: 3; opacity: 50%; zoom: 3x; left: 2furlongs }"
+      .to_string(),
+  );
+  let style_sheet = parser.parse_syle_sheet();
+  let values: Vec<DeclarationValue> = style_sheet.rules[0]
+    .declarations
+    .iter()
+    .map(|declaration| declaration.value.clone())
+    .collect();
+  assert_eq!(
+    values,
+    vec![
+      DeclarationValue::Length(10.0, css::UnitValue::Px),
+      DeclarationValue::Length(-150.0, css::UnitValue::Vh),
+      DeclarationValue::Length(1.2, css::UnitValue::Rem),
+      DeclarationValue::Length(2.0, css::UnitValue::Q),
+      DeclarationValue::Length(0.25, css::UnitValue::Turn),
+      DeclarationValue::Length(200.0, css::UnitValue::Ms),
+      DeclarationValue::Number(1.5),
+      DeclarationValue::Integer(3),
+      DeclarationValue::Length(50.0, css::UnitValue::Percent),
+      DeclarationValue::Length(3.0, css::UnitValue::Dppx),
+    ]
+  );
+  assert_eq!(
+    parser.diagnostics.errors,
+    vec!["Dropped invalid declaration 'left: 2furlongs': unknown unit 'furlongs'"]
+  );
+}
+
+#[test]
+fn test_lengths_to_px() {
+  let px = |source_code: &str| {
+    let declarations = CSSParser::new(format!("width: {}", source_code)).parse_declaration_list();
+    return declarations[0].value.to_px();
+  };
+  assert_eq!(px("12px"), 12.0);
+  assert_eq!(px("1in"), 96.0);
+  assert_eq!(px("2.54cm"), 96.0);
+  assert_eq!(px("12pt"), 16.0);
+  assert_eq!(px("1pc"), 16.0);
+  assert_eq!(px("40Q"), 96.0 * 40.0 / 101.6);
+  assert_eq!(px("2em"), 32.0);
+  assert_eq!(px("0"), 0.0);
+}
+
+#[test]
+fn test_parse_colors() {
+  let rgba = |source_code: &str| {
+    let mut parser = CSSParser::new(format!("color: {}", source_code));
+    let declarations = parser.parse_declaration_list();
+    assert!(parser.diagnostics.errors.is_empty(), "{:?}", parser.diagnostics.errors);
+    match &declarations[0].value {
+      DeclarationValue::ColorValue(color) => return color.to_rgba(),
+      value => panic!("expected a color, got {:?}", value),
+    }
+  };
+  assert_eq!(rgba("#f00"), Some((255, 0, 0, 255)));
+  assert_eq!(rgba("#f008"), Some((255, 0, 0, 136)));
+  assert_eq!(rgba("#00FF7f"), Some((0, 255, 127, 255)));
+  assert_eq!(rgba("#0000ff80"), Some((0, 0, 255, 128)));
+  assert_eq!(rgba("rgb(255, 128, 0)"), Some((255, 128, 0, 255)));
+  assert_eq!(rgba("rgba(100%, 50%, 0%, 0.5)"), Some((255, 128, 0, 128)));
+  assert_eq!(rgba("rgb(255 128 0 / 25%)"), Some((255, 128, 0, 64)));
+  assert_eq!(rgba("hsl(120, 100%, 25%)"), Some((0, 128, 0, 255)));
+  assert_eq!(rgba("hsl(120 100 25)"), Some((0, 128, 0, 255)));
+  assert_eq!(rgba("rgb(100% 128 0)"), Some((255, 128, 0, 255)));
+  assert_eq!(rgba("hsla(0.5turn 100% 50% / .5)"), Some((0, 255, 255, 128)));
+  assert_eq!(rgba("hsl(-120deg 100% 50%)"), Some((0, 0, 255, 255)));
+  assert_eq!(rgba("hwb(0 0% 0%)"), Some((255, 0, 0, 255)));
+  assert_eq!(rgba("hwb(90 60% 60%)"), Some((128, 128, 128, 255)));
+  assert_eq!(rgba("RebeccaPurple"), Some((102, 51, 153, 255)));
+  assert_eq!(rgba("transparent"), Some((0, 0, 0, 0)));
+  assert_eq!(rgba("currentColor"), None);
+
+  let mut parser = CSSParser::new(
+    "p { color: #ff00f; background-color: rgb(1, 2 3); border-color: hwb(0, 0%, 0%); \
+     animation-name: red; color: inherit }"
+      .to_string(),
+  );
+  let style_sheet = parser.parse_syle_sheet();
+  assert_eq!(
+    declarations(&style_sheet),
+    vec![
+      ("animation-name".to_string(), "red".to_string()),
+      ("color".to_string(), "inherit".to_string()),
+    ]
+  );
+  assert_eq!(parser.diagnostics.errors.len(), 3);
+  // the legacy comma syntax can't mix numbers and percentages, or use `none`; invalid colors
+  // leave the previous declaration in effect.
+  let style_sheet = create_syle_sheet(
+    "p { color: red; color: hsl(120, 50, 50); color: rgb(255, 50%, 0); color: rgb(none, 0, 0); \
+     color: hsla(120, 50%, 50%, none) }",
+  );
+  assert_eq!(
+    declarations(&style_sheet),
+    vec![("color".to_string(), "rgb(255, 0, 0)".to_string())]
+  );
+  assert_eq!(
+    css::ColorValue::RBGColorValue(255, 0, 0, 128).to_string(),
+    "rgba(255, 0, 0, 0.5)"
+  );
+}
+
+#[test]
+fn test_multi_value_declarations() {
+  let declarations =
+    CSSParser::new("font-family: \"Open Sans\", serif; transition: opacity 1s".to_string()).parse_declaration_list();
+  assert_eq!(
+    declarations[0].value,
+    DeclarationValue::List(vec![
+      DeclarationValue::Keyword("\"Open Sans\"".to_string()),
+      DeclarationValue::Separator(','),
+      DeclarationValue::Keyword("serif".to_string()),
+    ])
+  );
+  assert_eq!(
+    declarations[1].value,
+    DeclarationValue::List(vec![
+      DeclarationValue::Keyword("opacity".to_string()),
+      DeclarationValue::Length(1.0, css::UnitValue::S),
+    ])
+  );
+  let mut parser = CSSParser::new("box-shadow: 1px 2furlongs red".to_string());
+  assert!(parser.parse_declaration_list().is_empty());
+  assert_eq!(
+    parser.diagnostics.errors,
+    vec!["Dropped invalid declaration 'box-shadow: 1px 2furlongs red': unknown unit 'furlongs'"]
+  );
+}
+
+#[test]
+fn test_expand_shorthands() {
+  let expand = |source_code: &str| {
+    let mut parser = CSSParser::new(source_code.to_string());
+    let declarations = parser.parse_declaration_list();
+    assert!(parser.diagnostics.errors.is_empty(), "{:?}", parser.diagnostics.errors);
+    return declarations
+      .iter()
+      .map(|declaration| format!("{}: {}", declaration.name, declaration.value))
+      .collect::<Vec<_>>()
+      .join("; ");
+  };
+  assert_eq!(
+    expand("margin: 1px 2px 3px"),
+    "margin-top: 1px; margin-right: 2px; margin-bottom: 3px; margin-left: 2px"
+  );
+  assert_eq!(
+    expand("padding: 1em 5%"),
+    "padding-top: 1em; padding-right: 5%; padding-bottom: 1em; padding-left: 5%"
+  );
+  assert_eq!(
+    expand("inset: auto"),
+    "top: auto; right: auto; bottom: auto; left: auto"
+  );
+  assert_eq!(
+    expand("border-top: dashed 2px"),
+    "border-top-width: 2px; border-top-style: dashed; border-top-color: currentcolor"
+  );
+  assert_eq!(
+    expand("border: 1px solid red"),
+    "border-top-width: 1px; border-right-width: 1px; border-bottom-width: 1px; border-left-width: 1px; \
+     border-top-style: solid; border-right-style: solid; border-bottom-style: solid; border-left-style: solid; \
+     border-top-color: rgb(255, 0, 0); border-right-color: rgb(255, 0, 0); border-bottom-color: rgb(255, 0, 0); \
+     border-left-color: rgb(255, 0, 0)"
+  );
+  assert_eq!(
+    expand("border-color: red blue"),
+    "border-top-color: rgb(255, 0, 0); border-right-color: rgb(0, 0, 255); border-bottom-color: rgb(255, 0, 0); \
+     border-left-color: rgb(0, 0, 255)"
+  );
+  assert_eq!(
+    expand("border-radius: 10px 5% / 20px"),
+    "border-top-left-radius: 10px 20px; border-top-right-radius: 5% 20px; border-bottom-right-radius: 10px 20px; \
+     border-bottom-left-radius: 5% 20px"
+  );
+  assert_eq!(
+    expand("background: url(a.png) center / cover no-repeat, linear-gradient(red, blue) fixed #00f"),
+    "background-color: rgb(0, 0, 255); background-image: url(a.png), linear-gradient(red, blue); \
+     background-position: center, 0% 0%; background-size: cover, auto; background-repeat: no-repeat, repeat; \
+     background-attachment: scroll, fixed; background-origin: padding-box, padding-box; \
+     background-clip: border-box, border-box"
+  );
+  assert_eq!(
+    expand("background: left 10px top 5px / 50% auto content-box repeat-x"),
+    "background-color: rgba(0, 0, 0, 0); background-image: none; background-position: left 10px top 5px; \
+     background-size: 50% auto; background-repeat: repeat-x; background-attachment: scroll; \
+     background-origin: content-box; background-clip: content-box"
+  );
+  assert_eq!(
+    expand("font: italic bold 12px/1.5 \"Open Sans\", sans-serif"),
+    "font-style: italic; font-variant: normal; font-weight: bold; font-stretch: normal; font-size: 12px; \
+     line-height: 1.5; font-family: \"Open Sans\", sans-serif"
+  );
+  assert_eq!(
+    expand("font: normal small-caps 700 condensed large Times New Roman"),
+    "font-style: normal; font-variant: small-caps; font-weight: 700; font-stretch: condensed; font-size: large; \
+     line-height: normal; font-family: Times New Roman"
+  );
+  assert_eq!(expand("flex: none"), "flex-grow: 0; flex-shrink: 0; flex-basis: auto");
+  assert_eq!(expand("flex: 2"), "flex-grow: 2; flex-shrink: 1; flex-basis: 0%");
+  assert_eq!(expand("flex: 1 30px"), "flex-grow: 1; flex-shrink: 1; flex-basis: 30px");
+  assert_eq!(expand("flex: 2 3 0"), "flex-grow: 2; flex-shrink: 3; flex-basis: 0");
+  assert_eq!(expand("gap: 10px 1em"), "row-gap: 10px; column-gap: 1em");
+  assert_eq!(expand("gap: normal"), "row-gap: normal; column-gap: normal");
+  // CSS-wide keywords apply to every longhand; `var()` and system fonts can't be expanded yet.
+  assert_eq!(
+    expand("flex: inherit"),
+    "flex-grow: inherit; flex-shrink: inherit; flex-basis: inherit"
+  );
+  assert_eq!(expand("margin: var(--gap) auto"), "margin: var(--gap) auto");
+  assert_eq!(expand("font: menu"), "font: menu");
+  // later longhands override the shorthand.
+  assert_eq!(
+    expand("Margin: 0; margin-left: auto"),
+    "margin-top: 0; margin-right: 0; margin-bottom: 0; margin-left: 0; margin-left: auto"
+  );
+
+  let mut parser = CSSParser::new(
+    "margin: 1px 2px 3px 4px 5px; padding: auto; border: 1px 2px; font: bold serif; flex: 1 2 3; \
+     background: red, url(a.png)"
+      .to_string(),
+  );
+  assert!(parser.parse_declaration_list().is_empty());
+  assert_eq!(
+    parser.diagnostics.errors,
+    vec![
+      "Dropped invalid declaration 'margin: 1px 2px 3px 4px 5px': expected 1 to 4 values",
+      "Dropped invalid declaration 'padding: auto': unexpected 'auto'",
+      "Dropped invalid declaration 'border: 1px 2px': unexpected '2px'",
+      "Dropped invalid declaration 'font: bold serif': unexpected 'serif'",
+      "Dropped invalid declaration 'flex: 1 2 3': unexpected '3'",
+      "Dropped invalid declaration 'background: red, url(a.png)': unexpected 'red'",
+    ]
+  );
+}
+
+#[test]
+fn test_parse_important() {
+  let mut parser = CSSParser::new(
+    "color: red !important; margin: 0 auto ! IMPORTANT; width: 10px; height: 1px !ie; top: !important".to_string(),
+  );
+  let declarations = parser.parse_declaration_list();
+  let important: Vec<(&str, bool)> = declarations
+    .iter()
+    .map(|declaration| (declaration.name.as_str(), declaration.important))
+    .collect();
+  assert_eq!(
+    important,
+    vec![
+      ("color", true),
+      ("margin-top", true),
+      ("margin-right", true),
+      ("margin-bottom", true),
+      ("margin-left", true),
+      ("width", false),
+    ]
+  );
+  assert_eq!(declarations[2].value.to_string(), "auto");
+  assert_eq!(
+    parser.diagnostics.errors,
+    vec![
+      "Dropped invalid declaration 'height: 1px !ie': unexpected '!'",
+      "Dropped invalid declaration 'top: !important': empty value",
+    ]
+  );
+  assert_eq!(
+    css::serialize_declarations(&declarations[..2]),
+    "color: rgb(255, 0, 0) !important; margin-top: 0 !important;"
+  );
+}
+
+#[test]
+fn test_important_declarations_in_cascade() {
+  let html_root = webcore::parse_html(
+    r#"<div><p id="a" class="note">a</p><p class="note" style="color: black; width: 5px !important">b</p></div>"#
+      .to_string(),
+  );
+  let mut user_agent = create_syle_sheet("p { display: block !important; color: gray; width: 1px !important }");
+  user_agent.origin = css::Origin::UserAgent;
+  let mut user = create_syle_sheet("p { display: inline !important; color: green !important }");
+  user.origin = css::Origin::User;
+  let author = create_syle_sheet(
+    "#a { display: none; color: blue } .note { color: red !important } p { color: purple !important; width: 2px }",
+  );
+  let styled_root = css::cascade_style_tree(&html_root.children[0], &[&author, &user, &user_agent]);
+  let value = |index: usize, name: &str| styled_root.children[index].value(name).unwrap().to_string();
+  // important user-agent and user declarations beat every author declaration.
+  assert_eq!(value(0, "display"), "block");
+  assert_eq!(value(0, "color"), "rgb(0, 128, 0)");
+  assert_eq!(value(0, "width"), "1px");
+
+  let author = create_syle_sheet(
+    "#a { color: blue } .note { color: red !important } p { color: purple !important; width: 2px !important }",
+  );
+  let styled_root = css::cascade_style_tree(&html_root.children[0], &[&author]);
+  let value = |index: usize, name: &str| styled_root.children[index].value(name).unwrap().to_string();
+  // among important declarations, specificity still decides.
+  assert_eq!(value(0, "color"), "rgb(255, 0, 0)");
+  // important rules beat a normal `style` attribute, an important `style` attribute beats them.
+  assert_eq!(value(1, "color"), "rgb(255, 0, 0)");
+  assert_eq!(value(1, "width"), "5px");
+}
+
+#[test]
+fn test_parse_combinators() {
+  let mut parser =
+    CSSParser::new("div.test p, ul>li+li ~ #last, a b > c {} > p {} p + {} p > > a {} p ~ ~ a {}".to_string());
+  let style_sheet = parser.parse_syle_sheet();
+  let selectors: Vec<(String, css::Specificity)> = style_sheet.rules[0]
+    .selectors
+    .iter()
+    .map(|selector| (selector.to_string(), selector.specificity()))
+    .collect();
+  assert_eq!(
+    selectors,
+    vec![
+      ("div.test p".to_string(), (0, 1, 2)),
+      ("ul > li + li ~ #last".to_string(), (1, 0, 3)),
+      ("a b > c".to_string(), (0, 0, 3)),
+    ]
+  );
+  assert_eq!(style_sheet.rules.len(), 1);
+  assert_eq!(
+    parser.diagnostics.errors,
+    vec![
+      "Dropped rule with invalid selector '> p'",
+      "Dropped rule with invalid selector 'p +'",
+      "Dropped rule with invalid selector 'p > > a'",
+      "Dropped rule with invalid selector 'p ~ ~ a'",
+    ]
+  );
+}
+
+#[test]
+fn test_match_combinators() {
+  let html_root = webcore::parse_html(
+    r#"<div class="test"><section><p id="a">a</p><h2>b</h2><p id="c">c</p><p id="d">d</p></section><p id="e">e</p></div>"#
+      .to_string(),
+  );
+  let style_sheet = create_syle_sheet(
+    "div.test p { color: red } section > p { width: 1px } h2 + p { height: 2px } h2 ~ p { top: 3px } \
+     div > p { left: 4px } .test section h2 ~ p + p { right: 5px } span p { bottom: 6px }",
+  );
+  let styled_root = css::style_tree(&html_root.children[0], &style_sheet);
+  let properties = |styled_node: &css::StyledNode| {
+    let mut names: Vec<String> = styled_node.specified_values.keys().cloned().collect();
+    names.sort();
+    return names.join(" ");
+  };
+  let section = &styled_root.children[0];
+  assert_eq!(properties(&section.children[0]), "color width");
+  assert_eq!(properties(&section.children[1]), "");
+  assert_eq!(properties(&section.children[2]), "color height top width");
+  assert_eq!(properties(&section.children[3]), "color right top width");
+  assert_eq!(properties(&styled_root.children[1]), "color left");
+}
+
+#[test]
+fn test_parse_attribute_selectors() {
+  let mut parser = CSSParser::new(
+    r#"a[href] {} a[ href = "x\"y" ] {} [lang|=en][class~=b] {} [src^='http' i] {} [src$=png s] {} [title*=x] {}
+       [=x] {} [a==b] {} [a ~ = b] {} [a=b c] {} [a=b x] {} [a=1] {}"#
+      .to_string(),
+  );
+  let style_sheet = parser.parse_syle_sheet();
+  let selectors: Vec<(String, css::Specificity)> = style_sheet
+    .rules
+    .iter()
+    .map(|rule| (rule.selectors[0].to_string(), rule.selectors[0].specificity()))
+    .collect();
+  assert_eq!(
+    selectors,
+    vec![
+      ("a[href]".to_string(), (0, 1, 1)),
+      (r#"a[href="x\"y"]"#.to_string(), (0, 1, 1)),
+      (r#"[lang|="en"][class~="b"]"#.to_string(), (0, 2, 0)),
+      (r#"[src^="http" i]"#.to_string(), (0, 1, 0)),
+      (r#"[src$="png" s]"#.to_string(), (0, 1, 0)),
+      (r#"[title*="x"]"#.to_string(), (0, 1, 0)),
+    ]
+  );
+  assert_eq!(parser.diagnostics.errors.len(), 6);
+}
+
+#[test]
+fn test_match_attribute_selectors() {
+  let html_root = webcore::parse_html(
+    r#"<div><a href="https://example.com/logo.PNG" lang="en-US" rel="Nofollow noopener" title="">a</a><a HREF="/">b</a></div>"#
+      .to_string(),
+  );
+  let matches = |selector: &str| {
+    let style_sheet = create_syle_sheet(&format!("{} {{ color: red }}", selector));
+    let styled_root = css::style_tree(&html_root.children[0], &style_sheet);
+    return styled_root
+      .children
+      .iter()
+      .map(|child| child.value("color").is_some())
+      .collect::<Vec<bool>>();
+  };
+  assert_eq!(matches("[href]"), vec![true, true]);
+  assert_eq!(matches("[title]"), vec![true, false]);
+  assert_eq!(matches("[href='/']"), vec![false, true]);
+  assert_eq!(matches("[lang|=en]"), vec![true, false]);
+  assert_eq!(matches("[lang|=en-US]"), vec![true, false]);
+  assert_eq!(matches("[lang|=e]"), vec![false, false]);
+  assert_eq!(matches("[href^=https]"), vec![true, false]);
+  assert_eq!(matches("[href$='.png']"), vec![false, false]);
+  assert_eq!(matches("[href$='.png' i]"), vec![true, false]);
+  assert_eq!(matches("[href*=example]"), vec![true, false]);
+  assert_eq!(matches("[title^='']"), vec![false, false]);
+  assert_eq!(matches("[title='']"), vec![true, false]);
+  // `rel` values are case-insensitive in HTML, unless the `s` flag says otherwise.
+  assert_eq!(matches("[rel~=nofollow]"), vec![true, false]);
+  assert_eq!(matches("[rel~=nofollow s]"), vec![false, false]);
+  assert_eq!(matches("[rel~='nofollow noopener']"), vec![false, false]);
+}
+
+#[test]
+fn test_parse_pseudo_classes() {
+  let mut parser = CSSParser::new(
+    "li:first-child:nth-child( 2n + 1 ), :nth-last-child(-n+3 of .a, b), :nth-of-type(odd), :nth-of-type(even), \
+     :nth-last-of-type(-2n-1), :nth-child(5), :nth-child(n), :nth-child(+n-0) {} \
+     :not(#a, .b.c) {} :is(#a, p, ::x) {} :where(#a .b) p {} :has(> img, + p.x, a b) {} :root, :empty:last-child {} \
+     :nth-child(2n+) {} :nth-child(2.5n) {} :nth-child(n of) {} :not() {} :not(p, ::x) {} :hovered {} :has(> > a) {}"
+      .to_string(),
+  );
+  let style_sheet = parser.parse_syle_sheet();
+  let selectors: Vec<(String, css::Specificity)> = style_sheet
+    .rules
+    .iter()
+    .flat_map(|rule| rule.selectors.iter())
+    .map(|selector| (selector.to_string(), selector.specificity()))
+    .collect();
+  assert_eq!(
+    selectors,
+    vec![
+      ("li:first-child:nth-child(2n+1)".to_string(), (0, 2, 1)),
+      (":nth-last-child(-n+3 of .a, b)".to_string(), (0, 2, 0)),
+      (":nth-of-type(2n+1)".to_string(), (0, 1, 0)),
+      (":nth-of-type(2n)".to_string(), (0, 1, 0)),
+      (":nth-last-of-type(-2n-1)".to_string(), (0, 1, 0)),
+      (":nth-child(5)".to_string(), (0, 1, 0)),
+      (":nth-child(n)".to_string(), (0, 1, 0)),
+      (":nth-child(n)".to_string(), (0, 1, 0)),
+      (":not(#a, .b.c)".to_string(), (1, 0, 0)),
+      (":is(#a, p)".to_string(), (1, 0, 0)),
+      (":where(#a .b) p".to_string(), (0, 0, 1)),
+      (":has(> img, + p.x, a b)".to_string(), (0, 1, 1)),
+      (":root".to_string(), (0, 1, 0)),
+      (":empty:last-child".to_string(), (0, 2, 0)),
+    ]
+  );
+  assert_eq!(
+    parser.diagnostics.errors,
+    vec![
+      "Dropped rule with invalid selector ':nth-child(2n+)'",
+      "Dropped rule with invalid selector ':nth-child(2.5n)'",
+      "Dropped rule with invalid selector ':nth-child(n of)'",
+      "Dropped rule with invalid selector ':not()'",
+      "Dropped rule with invalid selector ':not(p, ::x)'",
+      "Dropped rule with invalid selector ':hovered'",
+      "Dropped rule with invalid selector ':has(> > a)'",
+    ]
+  );
+}
+
+#[test]
+fn test_match_pseudo_classes() {
+  let html_root = webcore::parse_html(
+    r#"<ul><li class="a">1</li><p>2</p><li>3</li><li class="a"><img/></li><li class="a"></li><li>6</li></ul>"#
+      .to_string(),
+  );
+  let matches = |selector: &str| {
+    let style_sheet = create_syle_sheet(&format!("{} {{ color: red }}", selector));
+    let styled_root = css::style_tree(&html_root.children[0], &style_sheet);
+    let mut matched = Vec::new();
+    if styled_root.value("color").is_some() {
+      matched.push(0);
+    }
+    for (index, child) in styled_root.children.iter().enumerate() {
+      if child.value("color").is_some() {
+        matched.push(index + 1);
+      }
+    }
+    return matched;
+  };
+  assert_eq!(matches(":root"), vec![0]);
+  assert_eq!(matches("li:first-child"), vec![1]);
+  assert_eq!(matches("ul > :last-child"), vec![6]);
+  assert_eq!(matches(":only-child"), vec![0]);
+  assert_eq!(matches("li:first-of-type, p:last-of-type"), vec![1, 2]);
+  assert_eq!(matches(":nth-child(odd)"), vec![0, 1, 3, 5]);
+  assert_eq!(matches("ul :nth-child(2n)"), vec![2, 4, 6]);
+  assert_eq!(matches(":nth-last-child(-n+2)"), vec![0, 5, 6]);
+  assert_eq!(matches(":nth-child(2 of .a)"), vec![4]);
+  assert_eq!(matches(":nth-last-child(1 of .a)"), vec![5]);
+  assert_eq!(matches("li:nth-of-type(2)"), vec![3]);
+  assert_eq!(matches("li:nth-last-of-type(3)"), vec![4]);
+  assert_eq!(matches("li:empty"), vec![5]);
+  assert_eq!(matches("li:not(.a, :empty)"), vec![3, 6]);
+  assert_eq!(matches(":is(p, .a):not(:where(:has(img)))"), vec![1, 2, 5]);
+  assert_eq!(matches("ul:has(> li img) li:has(img)"), vec![4]);
+  assert_eq!(matches(":has(+ li.a, ~ p)"), vec![1, 3, 4]);
+  assert_eq!(matches("ul:has(> img)"), Vec::<usize>::new());
+  // `:where()` adds no specificity, `:is()` that of its most specific selector.
+  let style_sheet = create_syle_sheet(":is(li, #x).a { color: red } :where(.a) { color: blue } li { color: green }");
+  let styled_root = css::style_tree(&html_root.children[0], &style_sheet);
+  assert_eq!(
+    styled_root.children[0].value("color").unwrap().to_string(),
+    "rgb(255, 0, 0)"
+  );
+  assert_eq!(
+    styled_root.children[2].value("color").unwrap().to_string(),
+    "rgb(0, 128, 0)"
+  );
+}
+
+#[test]
+fn test_parse_pseudo_elements() {
+  let mut parser = CSSParser::new(
+    "p::before, li::MARKER, a:first-line {} p.note:hover::after, ::first-letter, div > p:before {} \
+     p::before::after {} p::before:hover {} p::before span {} :not(::before) {} p::selection {} p::before(x) {}"
+      .to_string(),
+  );
+  let style_sheet = parser.parse_syle_sheet();
+  let selectors: Vec<(String, css::Specificity)> = style_sheet
+    .rules
+    .iter()
+    .flat_map(|rule| rule.selectors.iter())
+    .map(|selector| (selector.to_string(), selector.specificity()))
+    .collect();
+  assert_eq!(
+    selectors,
+    vec![
+      ("p::before".to_string(), (0, 0, 2)),
+      ("li::marker".to_string(), (0, 0, 2)),
+      ("a::first-line".to_string(), (0, 0, 2)),
+      ("p.note:hover::after".to_string(), (0, 2, 2)),
+      ("::first-letter".to_string(), (0, 0, 1)),
+      ("div > p::before".to_string(), (0, 0, 3)),
+    ]
+  );
+  assert_eq!(parser.diagnostics.errors.len(), 6);
+
+  let mut parser = CSSParser::new(
+    r#"p::before {
+      content: "\201C" attr(title) counter(item) counters(item, ".", lower-alpha) open-quote close-quote;
+      quotes: "<" ">" "[" "]";
+    }
+    p::after { content: none; quotes: auto; content: no-open-quote no-close-quote }
+    p::after { content: attr(); content: counter(a, b, c); content: counters(a); content: 1px; quotes: "a" }"#
+      .to_string(),
+  );
+  let style_sheet = parser.parse_syle_sheet();
+  assert_eq!(
+    declarations(&style_sheet),
+    vec![
+      (
+        "content".to_string(),
+        r#""“" attr(title) counter(item, decimal) counters(item, ".", lower-alpha) open-quote close-quote"#.to_string()
+      ),
+      ("quotes".to_string(), r#""<" ">" "[" "]""#.to_string()),
+      ("content".to_string(), "none".to_string()),
+      ("quotes".to_string(), "auto".to_string()),
+      ("content".to_string(), "no-open-quote no-close-quote".to_string()),
+    ]
+  );
+  assert_eq!(
+    parser.diagnostics.errors,
+    vec![
+      "Dropped invalid declaration 'content: attr()': unexpected 'attr()'",
+      "Dropped invalid declaration 'content: counter(a, b, c)': unexpected 'counter(a, b, c)'",
+      "Dropped invalid declaration 'content: counters(a)': unexpected 'counters(a)'",
+      "Dropped invalid declaration 'content: 1px': unexpected '1px'",
+      "Dropped invalid declaration 'quotes: \"a\"': expected pairs of strings",
+    ]
+  );
+}
+
+#[test]
+fn test_generated_content() {
+  let html_root = webcore::parse_html(
+    r#"<div><h2 title="Intro">a</h2><h2 title="Usage">b</h2><h3>c</h3><h3>d</h3><ol><li>x</li><li class="skip">y</li><li>z</li></ol><q>e<q>f</q></q></div>"#
+      .to_string(),
+  );
+  let style_sheet = create_syle_sheet(
+    r#"
+    div { counter-reset: section; display: block }
+    h2 { counter-increment: section; counter-reset: sub }
+    h2::before { content: counter(section, upper-roman) ". " attr(TITLE) ": "; color: red }
+    h3::before { counter-increment: sub; content: counters(section, "-") "." counter(sub, lower-alpha) " " }
+    ol { list-style-type: decimal; counter-reset: list-item 10; display: block }
+    li { display: list-item }
+    .skip { counter-increment: list-item 0; list-style-type: lower-roman }
+    li::marker { color: blue }
+    .skip::after { content: "!" }
+    q::before { content: open-quote }
+    q::after { content: close-quote close-quote }
+    q q { quotes: "<" ">" }
+    h2::first-letter { color: green }
+    "#,
+  );
+  let styled_root = css::style_tree(&html_root.children[0], &style_sheet);
+  assert_eq!(
+    css::dump_style_tree(&styled_root),
+    r#"<div> counter-reset: section; display: block
+  <h2> counter-increment: section; counter-reset: sub
+    ::before "I. Intro: " color: rgb(255, 0, 0); content: counter(section, upper-roman) ". " attr(TITLE) ": "
+    ::first-letter color: rgb(0, 128, 0)
+    "a"
+  <h2> counter-increment: section; counter-reset: sub
+    ::before "II. Usage: " color: rgb(255, 0, 0); content: counter(section, upper-roman) ". " attr(TITLE) ": "
+    ::first-letter color: rgb(0, 128, 0)
+    "b"
+  <h3>
+    ::before "2.a " content: counters(section, "-", decimal) "." counter(sub, lower-alpha) " "; counter-increment: sub
+    "c"
+  <h3>
+    ::before "2.b " content: counters(section, "-", decimal) "." counter(sub, lower-alpha) " "; counter-increment: sub
+    "d"
+  <ol> counter-reset: list-item 10; display: block; list-style-type: decimal
+    <li> display: list-item
+      ::marker "11. " color: rgb(0, 0, 255)
+      "x"
+    <li> counter-increment: list-item 0; display: list-item; list-style-type: lower-roman
+      ::marker "xi. " color: rgb(0, 0, 255)
+      ::after "!" content: "!"
+      "y"
+    <li> display: list-item
+      ::marker "12. " color: rgb(0, 0, 255)
+      "z"
+  <q>
+    ::before "“" content: open-quote
+    ::after "" content: close-quote close-quote
+    "e"
+    <q> quotes: "<" ">"
+      ::before "<" content: open-quote
+      ::after ">>" content: close-quote close-quote
+      "f"
+"#
+  );
+
+  let mut viewport = webcore::layout::Dimensions::default();
+  viewport.content.width = 800.0;
+  let ordered_list = &styled_root.children[4];
+  let layout_root = webcore::layout::layout_tree(ordered_list, viewport).unwrap();
+  assert_eq!(
+    webcore::layout::dump_layout_tree(&layout_root),
+    r#"BlockNode <ol> (0, 0, 800x0)
+  BlockNode <li> (0, 0, 800x0)
+    AnonymousBlock (0, 0, 0x0)
+      InlineNode <li>::marker "11. " (0, 0, 0x0)
+      InlineNode "x" (0, 0, 0x0)
+  BlockNode <li> (0, 0, 800x0)
+    AnonymousBlock (0, 0, 0x0)
+      InlineNode <li>::marker "xi. " (0, 0, 0x0)
+      InlineNode "y" (0, 0, 0x0)
+      InlineNode <li>::after "!" (0, 0, 0x0)
+  BlockNode <li> (0, 0, 800x0)
+    AnonymousBlock (0, 0, 0x0)
+      InlineNode <li>::marker "12. " (0, 0, 0x0)
+      InlineNode "z" (0, 0, 0x0)
+"#
+  );
+}
+
+#[test]
+fn test_parse_namespaces() {
+  let mut parser = CSSParser::new(
+    r#"@namespace svg url(http://www.w3.org/2000/svg);
+    @namespace "http://www.w3.org/1999/xhtml";
+    @namespace x "a" "b";
+    @namespace 1 "a";
+    @namespace svg "http://www.w3.org/2000/svg";
+    * {} *.foo {} svg|rect {} *|* {} |a {} svg|*:hover {} a[svg|href] {} [*|href] {} [|href] {} p .x {}
+    x|p {} [*] {} svg| rect {} [lang|=en] {}
+    @namespace m "m";"#
+      .to_string(),
+  );
+  let style_sheet = parser.parse_syle_sheet();
+  assert_eq!(
+    style_sheet.namespaces,
+    vec![
+      css::NamespaceRule { prefix: None, url: "http://www.w3.org/1999/xhtml".to_string() },
+      css::NamespaceRule { prefix: Some("svg".to_string()), url: "http://www.w3.org/2000/svg".to_string() },
+    ]
+  );
+  let selectors: Vec<(String, css::Specificity)> = style_sheet
+    .rules
+    .iter()
+    .map(|rule| (rule.selectors[0].to_string(), rule.selectors[0].specificity()))
+    .collect();
+  assert_eq!(
+    selectors,
+    vec![
+      ("*".to_string(), (0, 0, 0)),
+      (".foo".to_string(), (0, 1, 0)),
+      ("svg|rect".to_string(), (0, 0, 1)),
+      ("*|*".to_string(), (0, 0, 0)),
+      ("|a".to_string(), (0, 0, 1)),
+      ("svg|*:hover".to_string(), (0, 1, 0)),
+      (r#"a[svg|href]"#.to_string(), (0, 1, 1)),
+      (r#"[*|href]"#.to_string(), (0, 1, 0)),
+      (r#"[|href]"#.to_string(), (0, 1, 0)),
+      ("p .x".to_string(), (0, 1, 1)),
+      (r#"[lang|="en"]"#.to_string(), (0, 1, 0)),
+    ]
+  );
+  assert_eq!(
+    parser.diagnostics.errors[..2],
+    [
+      "Dropped invalid @namespace rule '@namespace x \"a\" \"b\"': expected a prefix and a URL".to_string(),
+      "Dropped invalid @namespace rule '@namespace 1 \"a\"': expected a prefix and a URL".to_string(),
+    ]
+  );
+  assert_eq!(
+    parser.diagnostics.errors.last().unwrap(),
+    "Dropped invalid @namespace rule '@namespace m \"m\"': after style rules"
+  );
+  assert_eq!(parser.diagnostics.errors.len(), 6);
+
+  // round-trips through the serializer, prefixes included.
+  let serialized = css::serialize_style_sheet(&style_sheet);
+  assert!(serialized.starts_with("@namespace \"http://www.w3.org/1999/xhtml\";\n@namespace svg"));
+  let reparsed = create_syle_sheet(&serialized);
+  assert_eq!(reparsed.namespaces, style_sheet.namespaces);
+  assert_eq!(reparsed.rules.len(), style_sheet.rules.len());
+}
+
+#[test]
+fn test_match_namespaces() {
+  let html_root = webcore::parse_html(
+    r#"<div><svg><rect></rect><foreignObject><p>1</p></foreignObject><a href="/">2</a></svg><math><mi><b>3</b></mi></math><a href="/">4</a></div>"#
+      .to_string(),
+  );
+  fn matched(styled_node: &css::StyledNode, tag_names: &mut Vec<String>) {
+    if let Some(element) = styled_node.node.element() {
+      if styled_node.value("color").is_some() {
+        tag_names.push(element.tag_name.clone());
+      }
+    }
+    for child in &styled_node.children {
+      matched(child, tag_names);
+    }
+  }
+  let matches = |style_sheet: &str| {
+    let style_sheet = create_syle_sheet(&format!("{} {{ color: red }}", style_sheet));
+    let styled_root = css::style_tree(&html_root.children[0], &style_sheet);
+    let mut tag_names = Vec::new();
+    matched(&styled_root, &mut tag_names);
+    return tag_names.join(" ");
+  };
+  let svg = r#"@namespace svg "http://www.w3.org/2000/svg";"#;
+  assert_eq!(matches(&format!("{} svg|*", svg)), "svg rect foreignObject a");
+  assert_eq!(matches(&format!("{} svg|a", svg)), "a");
+  assert_eq!(matches(&format!("{} *|a", svg)), "a a");
+  assert_eq!(matches(&format!("{} |a", svg)), "");
+  assert_eq!(matches("@namespace \"http://www.w3.org/1999/xhtml\"; a, p, b"), "p b a");
+  assert_eq!(
+    matches("@namespace \"http://www.w3.org/1998/Math/MathML\"; *"),
+    "math mi"
+  );
+  assert_eq!(matches("a"), "a a");
+  assert_eq!(matches("*"), "div svg rect foreignObject p a math mi b a");
+  assert_eq!(matches("[href]"), "a a");
+  assert_eq!(matches("[|href]"), "a a");
+  assert_eq!(matches(&format!("{} [svg|href]", svg)), "");
+  // type selectors ignore case for HTML elements only.
+  assert_eq!(matches("DIV, P"), "div p");
+  assert_eq!(matches("foreignObject, FOREIGNOBJECT, foreignobject"), "foreignObject");
+}
+
+#[test]
+fn test_cascade_applies_rules_in_specificity_order() {
+  let html_root = webcore::parse_html(r#"<div><p class="note">a</p><p>b</p></div>"#.to_string());
+  // the more specific rule wins even when it comes first.
+  let style_sheet = create_syle_sheet("p.note { display: block; } p { display: inline; }");
+  let styled_root = css::style_tree(&html_root.children[0], &style_sheet);
+  let value = |index: usize| styled_root.children[index].value("display").unwrap().to_string();
+  assert_eq!(value(0), "block");
+  assert_eq!(value(1), "inline");
+}
+
+#[test]
+fn test_id_selector_beats_type_and_class_selectors() {
+  let html_root = webcore::parse_html(r#"<div><p id="x" class="a b c">a</p></div>"#.to_string());
+  let style_sheet = create_syle_sheet("#x { display: block; } p.a { display: inline; } p.a.b.c { display: none; }");
+  // specificity compares ids first, then classes, then types.
+  let specificities: Vec<css::Specificity> = style_sheet
+    .rules
+    .iter()
+    .map(|rule| rule.selectors[0].specificity())
+    .collect();
+  assert_eq!(specificities, vec![(1, 0, 0), (0, 1, 1), (0, 3, 1)]);
+  let styled_root = css::style_tree(&html_root.children[0], &style_sheet);
+  assert_eq!(styled_root.children[0].value("display").unwrap().to_string(), "block");
+}
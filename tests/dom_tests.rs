@@ -0,0 +1,674 @@
+#![allow(dead_code, unused_imports, clippy::needless_return)]
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use webcore::dom::{self, Event, EventDispatcher, EventListenerOptions, EventPhase};
+use webcore::network::URL;
+use webcore::parse_html;
+
+fn create_html_root(source_code: &str) -> dom::HtmlRoot {
+  return parse_html(source_code.to_string());
+}
+
+fn path_of_id(root: &dom::HtmlRoot, id: &str) -> dom::NodePath {
+  let path = root.find_path(|node| match &node.node_type {
+    dom::NodeType::Element(element) => element.atributes.get("id").is_some_and(|value| value == id),
+    _ => false,
+  });
+  return path.expect("element not found");
+}
+
+#[test]
+fn test_event_dispatch_phases() {
+  let root = create_html_root(r#"<div id="outer"><p id="inner"><em id="target">hi</em></p></div>"#);
+  let outer = path_of_id(&root, "outer");
+  let target = path_of_id(&root, "target");
+  let log = Rc::new(RefCell::new(Vec::new()));
+  let mut dispatcher = EventDispatcher::new(&root);
+
+  let capture = EventListenerOptions { capture: true, ..Default::default() };
+  let entries = log.clone();
+  dispatcher.add_event_listener(&outer, "click", capture, move |event, _| {
+    entries.borrow_mut().push(format!("outer {:?}", event.phase))
+  });
+  let entries = log.clone();
+  dispatcher.add_event_listener(&outer, "click", Default::default(), move |event, _| {
+    entries.borrow_mut().push(format!("outer {:?}", event.phase))
+  });
+  let entries = log.clone();
+  dispatcher.add_event_listener(&target, "click", Default::default(), move |event, _| {
+    entries.borrow_mut().push(format!("target {:?}", event.phase))
+  });
+
+  assert!(dispatcher.dispatch_event(&target, &mut Event::click()));
+  assert_eq!(
+    *log.borrow(),
+    vec!["outer Capturing", "target AtTarget", "outer Bubbling"]
+  );
+}
+
+#[test]
+fn test_event_stop_propagation_and_prevent_default() {
+  let root = create_html_root(r#"<form id="form"><button id="submit">Send</button></form>"#);
+  let form = path_of_id(&root, "form");
+  let button = path_of_id(&root, "submit");
+  let form_calls = Rc::new(RefCell::new(0));
+  let mut dispatcher = EventDispatcher::new(&root);
+
+  dispatcher.add_event_listener(&button, "click", Default::default(), |event, _| {
+    event.prevent_default();
+    event.stop_propagation();
+  });
+  let calls = form_calls.clone();
+  dispatcher.add_event_listener(&form, "click", Default::default(), move |_, _| *calls.borrow_mut() += 1);
+
+  let mut event = Event::click();
+  assert!(!dispatcher.dispatch_event(&button, &mut event));
+  assert!(event.default_prevented());
+  assert_eq!(*form_calls.borrow(), 0);
+
+  // stopping in a capturing listener on the target skips its non-capturing listeners, but not
+  // its other capturing ones.
+  let log = Rc::new(RefCell::new(Vec::new()));
+  let capture = EventListenerOptions { capture: true, ..Default::default() };
+  let entries = log.clone();
+  dispatcher.add_event_listener(&button, "focus", capture, move |event, _| {
+    event.stop_propagation();
+    entries.borrow_mut().push("capture 1");
+  });
+  let entries = log.clone();
+  dispatcher.add_event_listener(&button, "focus", capture, move |_, _| {
+    entries.borrow_mut().push("capture 2")
+  });
+  let entries = log.clone();
+  dispatcher.add_event_listener(&button, "focus", Default::default(), move |_, _| {
+    entries.borrow_mut().push("bubble")
+  });
+  dispatcher.dispatch_event(&button, &mut Event::new("focus", false, false));
+  assert_eq!(*log.borrow(), vec!["capture 1", "capture 2"]);
+}
+
+#[test]
+fn test_event_once_and_passive_listeners() {
+  let root = create_html_root(r#"<input id="name" value="" />"#);
+  let input = path_of_id(&root, "name");
+  let values = Rc::new(RefCell::new(Vec::new()));
+  let mut dispatcher = EventDispatcher::new(&root);
+
+  let once = EventListenerOptions { once: true, passive: true, ..Default::default() };
+  let received = values.clone();
+  dispatcher.add_event_listener(&input, "input", once, move |event, _| {
+    event.prevent_default();
+    received.borrow_mut().push(event.data.clone().unwrap());
+  });
+  let mut click = Event::click();
+  dispatcher.add_event_listener(
+    &input,
+    "click",
+    EventListenerOptions { passive: true, ..Default::default() },
+    |event, _| event.prevent_default(),
+  );
+
+  dispatcher.dispatch_event(&input, &mut Event::input("a"));
+  dispatcher.dispatch_event(&input, &mut Event::input("b"));
+  assert_eq!(*values.borrow(), vec!["a"]);
+  assert!(dispatcher.dispatch_event(&input, &mut click));
+}
+
+#[test]
+fn test_event_listeners_receive_current_target_node() {
+  let root = create_html_root(r#"<ul id="list"><li id="first">a</li><li id="second">b</li></ul>"#);
+  let list = path_of_id(&root, "list");
+  let second = path_of_id(&root, "second");
+  let ids = Rc::new(RefCell::new(Vec::new()));
+  let mut dispatcher = EventDispatcher::new(&root);
+
+  for path in [&list, &second] {
+    let received = ids.clone();
+    let id = dispatcher.add_event_listener(path, "click", Default::default(), move |_, node| {
+      received.borrow_mut().push(node.attribute("id").unwrap().to_string())
+    });
+    assert!(id.is_some());
+  }
+  // listeners need a node to attach to.
+  assert!(dispatcher
+    .add_event_listener(&[0, 5], "click", Default::default(), |_, _| {})
+    .is_none());
+
+  dispatcher.dispatch_event(&second, &mut Event::click());
+  assert_eq!(*ids.borrow(), vec!["second", "list"]);
+}
+
+#[test]
+fn test_document_accessors() {
+  let source_code = r#"
+  <!DOCTYPE html>
+  <html lang="en">
+    <head>
+      <title>
+        My   page
+      </title>
+      <base href="https://example.com/docs/" />
+      <link rel="stylesheet" href="main.css" />
+      <style>p { color: red; }</style>
+    </head>
+    <body><p>Hello</p></body>
+  </html>"#;
+  let mut document = webcore::parse_document(source_code.to_string());
+  assert_eq!(document.document_element().unwrap().tag_name(), "html");
+  assert_eq!(document.head().unwrap().tag_name(), "head");
+  assert_eq!(document.body().unwrap().text_content(), "Hello");
+  assert_eq!(document.title(), "My page");
+  assert_eq!(document.base_url().unwrap().to_string(), "https://example.com/docs/");
+  assert_eq!(document.lang().as_deref(), Some("en"));
+  assert_eq!(
+    document.style_sheets(),
+    vec![
+      dom::StyleSheetSource::Link("main.css".to_string()),
+      dom::StyleSheetSource::Style("p { color: red; }".to_string())
+    ]
+  );
+
+  document.set_title("Renamed");
+  assert_eq!(document.title(), "Renamed");
+}
+
+#[test]
+fn test_document_set_title_creates_title_element() {
+  let mut document = webcore::parse_document("<html><head></head><body></body></html>".to_string());
+  assert_eq!(document.title(), "");
+  document.set_title("Created");
+  assert_eq!(document.head().unwrap().children[0].tag_name(), "title");
+  assert_eq!(document.title(), "Created");
+}
+
+#[test]
+fn test_form_data_set() {
+  let source_code = r#"
+  <html>
+    <body>
+      <form id="signup" action="/signup" method="POST">
+        <input name="user" value="ana maria" />
+        <input type="checkbox" name="news" checked="" />
+        <input type="checkbox" name="spam" value="yes" />
+        <input type="radio" name="plan" value="free" />
+        <input type="radio" name="plan" value="pro" checked="" />
+        <input name="locked" value="x" disabled="" />
+        <fieldset disabled="">
+          <legend><input name="in-legend" value="1" /></legend>
+          <input name="in-fieldset" value="2" />
+        </fieldset>
+        <select name="color"><option>Red</option><option value="b">Blue</option></select>
+        <select name="tags" multiple=""><option selected="">a</option><option selected="" disabled="">b</option></select>
+        <textarea name="bio">line 1
+line 2</textarea>
+        <button name="action" value="save">Save</button>
+        <button name="action" value="delete">Delete</button>
+      </form>
+      <input name="outside" value="&" form="signup" />
+      <input name="orphan" value="no" />
+    </body>
+  </html>"#;
+  let document = webcore::parse_document(source_code.to_string());
+  let forms = document.forms();
+  assert_eq!(forms.len(), 1);
+  let form = &forms[0];
+  assert_eq!(
+    (form.action(), form.method(), form.enctype()),
+    (
+      "/signup".to_string(),
+      "post".to_string(),
+      "application/x-www-form-urlencoded".to_string()
+    )
+  );
+
+  let submitter = document.root.find_path(|node| node.attribute("value") == Some("save"));
+  let form_data = form.form_data(submitter.as_deref());
+  assert_eq!(
+    form_data.to_urlencoded(),
+    "user=ana+maria&news=on&plan=pro&in-legend=1&color=Red&tags=a&bio=line+1%0D%0Aline+2&action=save&outside=%26"
+  );
+
+  let form_data = dom::FormData {
+    entries: form_data
+      .entries
+      .into_iter()
+      .filter(|entry| entry.name == "user" || entry.name == "bio")
+      .collect(),
+  };
+  assert_eq!(
+    form_data.to_multipart("BOUNDARY"),
+    "--BOUNDARY\r\nContent-Disposition: form-data; name=\"user\"\r\n\r\nana maria\r\n\
+     --BOUNDARY\r\nContent-Disposition: form-data; name=\"bio\"\r\n\r\nline 1\r\nline 2\r\n\
+     --BOUNDARY--\r\n"
+  );
+}
+
+#[test]
+fn test_url_resolution() {
+  let base = URL::parse("http://a/b/c/d;p?q").unwrap();
+  let cases = [
+    ("g", "http://a/b/c/g"),
+    ("./g", "http://a/b/c/g"),
+    ("g/", "http://a/b/c/g/"),
+    ("/g", "http://a/g"),
+    ("//g", "http://g/"),
+    ("?y", "http://a/b/c/d;p?y"),
+    ("g?y#s", "http://a/b/c/g?y#s"),
+    ("#s", "http://a/b/c/d;p?q#s"),
+    ("", "http://a/b/c/d;p?q"),
+    ("../g", "http://a/b/g"),
+    ("../../../g", "http://a/g"),
+    ("g;x=1/../y", "http://a/b/c/y"),
+    ("https://Example.COM:443/x y", "https://example.com/x%20y"),
+    ("mailto:someone@example.com", "mailto:someone@example.com"),
+  ];
+  for (reference, expected) in cases {
+    assert_eq!(
+      base.join(reference).unwrap().to_string(),
+      expected,
+      "resolving {:?}",
+      reference
+    );
+  }
+  assert_eq!(URL::parse("http://localhost:8080").unwrap().port, 8080);
+  assert!(URL::parse("/relative").is_none());
+}
+
+#[test]
+fn test_css_urls_skip_comments_and_strings() {
+  let css = r#"
+    @import url("a.css"); @IMPORT 'b.css'; @import url(c.css) print;
+    /* body { background: url(commented.png) } @import "commented.css"; */
+    p::before { content: "url(string.png)"; }
+    div { mask: my-url(other.png); background: URL( "d.png" ), url(e\(1\).png), url(); }
+  "#;
+  assert_eq!(dom::css_urls(css), vec!["a.css", "b.css", "c.css", "d.png", "e(1).png"]);
+}
+
+#[test]
+fn test_extract_links() {
+  let source_code = r#"
+  <html>
+    <head>
+      <base href="/docs/" />
+      <link rel="stylesheet" href="main.css" />
+      <link rel="icon" href="/favicon.ico" />
+      <link rel="canonical" href="https://example.com/docs/" />
+      <style>@import "print.css"; body { background: url('bg.png'); }</style>
+      <script src="app.js"></script>
+    </head>
+    <body>
+      <a href="../about">About</a>
+      <img src="logo.png" srcset="logo-2x.png 2x, logo-3x.png 3x" />
+      <div style="background-image: url(pattern.svg)"></div>
+    </body>
+  </html>"#;
+  let root = create_html_root(source_code);
+  let document = dom::Document::with_url(root, URL::parse("https://example.com/index.html").unwrap());
+  let links: Vec<(dom::LinkKind, String)> = document
+    .links()
+    .into_iter()
+    .map(|link| (link.kind, link.url.unwrap().to_string()))
+    .collect();
+  assert_eq!(
+    links,
+    vec![
+      (
+        dom::LinkKind::Stylesheet,
+        "https://example.com/docs/main.css".to_string()
+      ),
+      (dom::LinkKind::Icon, "https://example.com/favicon.ico".to_string()),
+      (
+        dom::LinkKind::Link("canonical".to_string()),
+        "https://example.com/docs/".to_string()
+      ),
+      (
+        dom::LinkKind::StyleUrl,
+        "https://example.com/docs/print.css".to_string()
+      ),
+      (dom::LinkKind::StyleUrl, "https://example.com/docs/bg.png".to_string()),
+      (dom::LinkKind::Script, "https://example.com/docs/app.js".to_string()),
+      (dom::LinkKind::Hyperlink, "https://example.com/about".to_string()),
+      (dom::LinkKind::Image, "https://example.com/docs/logo.png".to_string()),
+      (dom::LinkKind::Image, "https://example.com/docs/logo-2x.png".to_string()),
+      (dom::LinkKind::Image, "https://example.com/docs/logo-3x.png".to_string()),
+      (
+        dom::LinkKind::StyleUrl,
+        "https://example.com/docs/pattern.svg".to_string()
+      ),
+    ]
+  );
+}
+
+#[test]
+fn test_document_style_tree_uses_style_elements_and_attributes() {
+  let source_code = r#"
+  <html>
+    <head>
+      <style>p { color: red; display: block; } .note { color: green; }</style>
+      <style type="text/less">p { color: purple; }</style>
+      <style>p { color: blue; }</style>
+    </head>
+    <body>
+      <p>first</p>
+      <p class="note">second</p>
+      <p class="note" style="color: black; margin: 0">third</p>
+    </body>
+  </html>"#;
+  let document = webcore::parse_document(source_code.to_string());
+  assert_eq!(document.author_style_sheets().len(), 2);
+  // both skip the `text/less` sheet.
+  assert_eq!(document.style_sheets().len(), 2);
+  let styled_root = document.style_tree().unwrap();
+  let body = &styled_root.children[1];
+  let colors: Vec<String> = body
+    .children
+    .iter()
+    .map(|paragraph| paragraph.value("color").unwrap().to_string())
+    .collect();
+  assert_eq!(colors, vec!["rgb(0, 0, 255)", "rgb(0, 128, 0)", "rgb(0, 0, 0)"]);
+  assert_eq!(body.children[2].value("display").unwrap().to_string(), "block");
+}
+
+#[test]
+fn test_layout_document_with_display_none_root() {
+  let mut viewport = webcore::layout::Dimensions::default();
+  viewport.content.width = 800.0;
+  for source_code in [
+    "<html><head><style>html { display: none }</style></head><body><p>hidden</p></body></html>",
+    r#"<html style="display:none"><body><p>hidden</p></body></html>"#,
+  ] {
+    let document = webcore::parse_document(source_code.to_string());
+    let styled_root = document.style_tree().unwrap();
+    assert!(webcore::layout::layout_tree(&styled_root, viewport).is_none());
+  }
+  let document = webcore::parse_document("<html><body><p>shown</p></body></html>".to_string());
+  let styled_root = document.style_tree().unwrap();
+  assert!(webcore::layout::layout_tree(&styled_root, viewport).is_some());
+}
+
+#[test]
+fn test_layout_resolves_percentages_against_containing_block() {
+  let html_root = parse_html(r#"<div><p>a</p></div>"#.to_string());
+  let style_sheet = webcore::parse_css(
+    "div { display: block; width: 50%; padding: 10%; } p { display: block; width: 25%; margin-left: 50%; }".to_string(),
+  );
+  let styled_root = webcore::css::style_tree(&html_root.children[0], &style_sheet);
+  let mut viewport = webcore::layout::Dimensions::default();
+  viewport.content.width = 800.0;
+  let layout_root = webcore::layout::layout_tree(&styled_root, viewport).unwrap();
+  let div = layout_root.dimensions;
+  assert_eq!(
+    (div.content.width, div.padding.left, div.padding.top),
+    (400.0, 80.0, 80.0)
+  );
+  let p = layout_root.children[0].dimensions;
+  assert_eq!((p.content.width, p.margin.left, p.content.x), (100.0, 200.0, 280.0));
+}
+
+#[test]
+fn test_accessibility_roles_and_states() {
+  let source_code = r#"
+  <html>
+    <body>
+      <section><p>plain</p></section>
+      <section aria-label="News"></section>
+      <section title="Links"></section>
+      <input id="remember" type="checkbox" />
+    </body>
+  </html>"#;
+  let mut document = webcore::parse_document(source_code.to_string());
+  let remember = path_of_id(&document.root, "remember");
+  document.root.state_mut(&remember).unwrap().checked = true;
+  let styled_root = document.style_tree().unwrap();
+  let tree = webcore::accessibility::accessibility_tree(&styled_root);
+  let roles: Vec<&str> = tree.children.iter().map(|node| node.role.as_str()).collect();
+  // an unnamed section has no role, so its content takes its place.
+  assert_eq!(roles, vec!["paragraph", "region", "region", "checkbox"]);
+  assert_eq!(
+    tree.children[3].properties.get("checked").map(String::as_str),
+    Some("true")
+  );
+}
+
+#[test]
+fn test_element_state_pseudo_classes_restyle() {
+  let source_code = r#"
+  <html>
+    <head>
+      <style>
+        a:hover { color: red; }
+        a:visited { color: purple; }
+        nav:hover { display: flex; }
+        input:focus { color: blue; }
+        input:focus-visible { display: inline; }
+        form:focus-within { display: block; }
+        input:checked { width: 10px; }
+        input:disabled { width: 20px; }
+        button:active { color: green; }
+        section:target { color: orange; }
+      </style>
+    </head>
+    <body>
+      <nav id="nav"><a id="link" href="/">home</a></nav>
+      <form id="form"><input id="field" checked="" /><input id="other" disabled="" /><button id="button">go</button></form>
+      <section id="section"></section>
+    </body>
+  </html>"#;
+  let mut document = webcore::parse_document(source_code.to_string());
+  let value = |document: &webcore::dom::Document, id: &str, name: &str| {
+    let styled_root = document.style_tree().unwrap();
+    let path = path_of_id(&document.root, id);
+    // the style tree is rooted at the document element, the first node of the document.
+    let mut styled_node = &styled_root;
+    for index in &path[1..] {
+      styled_node = &styled_node.children[*index];
+    }
+    styled_node.value(name).map(|value| value.to_string())
+  };
+  // the initial state comes from the `checked` and `disabled` attributes.
+  assert_eq!(value(&document, "field", "width").as_deref(), Some("10px"));
+  assert_eq!(value(&document, "other", "width").as_deref(), Some("20px"));
+  assert_eq!(value(&document, "link", "color"), None);
+
+  // hovering an element hovers its ancestors too.
+  let link = path_of_id(&document.root, "link");
+  document.root.set_hover(Some(&link));
+  assert_eq!(value(&document, "link", "color").as_deref(), Some("rgb(255, 0, 0)"));
+  assert_eq!(value(&document, "nav", "display").as_deref(), Some("flex"));
+  document.root.set_hover(None);
+  assert_eq!(value(&document, "nav", "display"), None);
+
+  document.root.state_mut(&link).unwrap().visited = true;
+  assert_eq!(value(&document, "link", "color").as_deref(), Some("rgb(128, 0, 128)"));
+
+  let field = path_of_id(&document.root, "field");
+  document.root.set_focus(Some(&field), false);
+  assert_eq!(value(&document, "field", "color").as_deref(), Some("rgb(0, 0, 255)"));
+  assert_eq!(value(&document, "field", "display"), None);
+  assert_eq!(value(&document, "form", "display").as_deref(), Some("block"));
+  document.root.set_focus(Some(&field), true);
+  assert_eq!(value(&document, "field", "display").as_deref(), Some("inline"));
+  // moving the focus clears it from the previous element.
+  let button = path_of_id(&document.root, "button");
+  document.root.set_focus(Some(&button), false);
+  assert_eq!(value(&document, "field", "color"), None);
+  assert_eq!(value(&document, "form", "display").as_deref(), Some("block"));
+
+  document.root.state_mut(&field).unwrap().checked = false;
+  assert_eq!(value(&document, "field", "width"), None);
+  document.root.set_active(Some(&button));
+  assert_eq!(value(&document, "button", "color").as_deref(), Some("rgb(0, 128, 0)"));
+  let section = path_of_id(&document.root, "section");
+  document.root.set_target(Some(&section));
+  assert_eq!(
+    value(&document, "section", "color").as_deref(),
+    Some("rgb(255, 165, 0)")
+  );
+  // text nodes have no state.
+  assert!(document.root.state_mut(&[link, vec![0]].concat()).is_none());
+}
+
+#[test]
+fn test_disabled_pseudo_class_follows_fieldsets_and_optgroups() {
+  let source_code = r#"
+  <html>
+    <head><style>:disabled { color: red; }</style></head>
+    <body>
+      <form id="form">
+        <fieldset id="fieldset" disabled="">
+          <legend><input id="in_legend" name="a" value="1" /></legend>
+          <input id="in_fieldset" name="b" value="2" />
+          <legend><input id="in_second_legend" name="c" value="3" /></legend>
+        </fieldset>
+        <input id="outside" name="d" value="4" />
+        <select id="select"><optgroup id="group" disabled=""><option id="option">x</option></optgroup></select>
+      </form>
+    </body>
+  </html>"#;
+  let document = webcore::parse_document(source_code.to_string());
+  let styled_root = document.style_tree().unwrap();
+  let matches = |path: &[usize]| {
+    let mut styled_node = &styled_root;
+    for index in &path[1..] {
+      styled_node = &styled_node.children[*index];
+    }
+    styled_node.value("color").is_some()
+  };
+  let disabled: Vec<&str> = [
+    "fieldset",
+    "in_legend",
+    "in_fieldset",
+    "in_second_legend",
+    "outside",
+    "select",
+    "group",
+    "option",
+  ]
+  .into_iter()
+  .filter(|id| matches(&path_of_id(&document.root, id)))
+  .collect();
+  assert_eq!(
+    disabled,
+    vec!["fieldset", "in_fieldset", "in_second_legend", "group", "option"]
+  );
+  // form submission agrees on which controls are disabled.
+  let forms = document.forms();
+  assert_eq!(forms[0].controls.len(), 5);
+  for control in &forms[0].controls {
+    assert_eq!(control.disabled, matches(&control.path));
+  }
+}
+
+#[test]
+fn test_extract_metadata() {
+  let source_code = r#"
+  <html>
+    <head>
+      <meta name="description" content="A short summary." />
+      <meta property="og:title" content="Metadata" />
+      <meta property="og:type" content="article" />
+      <meta property="og:image" content="https://example.com/a.png" />
+      <meta property="og:image:width" content="1200" />
+      <meta property="og:image" content="https://example.com/b.png" />
+      <meta property="og:image:alt" content="Second image" />
+      <meta name="twitter:card" content="summary_large_image" />
+      <meta name="twitter:site" content="@example" />
+      <link rel="canonical" href="/post" />
+      <link rel="icon" href="favicon.png" sizes="32x32" />
+      <script type="application/ld+json">{"@context": "https://schema.org", "@type": "Article"}</script>
+      <script type="application/ld+json">{ not json }</script>
+    </head>
+    <body>
+      <div itemscope="" itemtype="https://schema.org/Person" itemref="address">
+        <span itemprop="name">Ada</span>
+        <a itemprop="url" href="ada">home</a>
+        <div itemprop="employer" itemscope="" itemtype="https://schema.org/Organization">
+          <span itemprop="name">Engines Ltd</span>
+        </div>
+      </div>
+      <p id="address" itemprop="address">London</p>
+    </body>
+  </html>"#;
+  let root = create_html_root(source_code);
+  let document = dom::Document::with_url(root, URL::parse("https://example.com/blog/index.html").unwrap());
+  let metadata = document.metadata();
+  assert_eq!(metadata.description.as_deref(), Some("A short summary."));
+  assert_eq!(metadata.canonical.unwrap().to_string(), "https://example.com/post");
+  assert_eq!(metadata.icons.len(), 1);
+  assert_eq!(
+    metadata.icons[0].url.as_ref().unwrap().to_string(),
+    "https://example.com/blog/favicon.png"
+  );
+  assert_eq!(metadata.icons[0].sizes.as_deref(), Some("32x32"));
+
+  assert_eq!(metadata.open_graph.title.as_deref(), Some("Metadata"));
+  assert_eq!(metadata.open_graph.kind.as_deref(), Some("article"));
+  let images = &metadata.open_graph.images;
+  assert_eq!(images.len(), 2);
+  assert_eq!(
+    (images[0].url.as_str(), images[0].width),
+    ("https://example.com/a.png", Some(1200))
+  );
+  assert_eq!(images[1].alt.as_deref(), Some("Second image"));
+  assert_eq!(metadata.twitter.card.as_deref(), Some("summary_large_image"));
+  assert_eq!(metadata.twitter.site.as_deref(), Some("@example"));
+  assert_eq!(metadata.meta["og:image"].len(), 2);
+
+  assert_eq!(metadata.json_ld.len(), 2);
+  assert_eq!(metadata.json_ld[0].value.as_ref().unwrap()["@type"], "Article");
+  assert_eq!(metadata.json_ld[1].raw, "{ not json }");
+  assert!(metadata.json_ld[1].value.is_none());
+
+  assert_eq!(metadata.microdata.len(), 1);
+  let person = &metadata.microdata[0];
+  assert_eq!(person.types, vec!["https://schema.org/Person"]);
+  let text = |value: &dom::MicrodataValue| match value {
+    dom::MicrodataValue::Text(text) => text.clone(),
+    dom::MicrodataValue::Item(_) => panic!("expected a text value"),
+  };
+  assert_eq!(text(&person.properties["name"][0]), "Ada");
+  assert_eq!(text(&person.properties["url"][0]), "https://example.com/blog/ada");
+  assert_eq!(text(&person.properties["address"][0]), "London");
+  let dom::MicrodataValue::Item(employer) = &person.properties["employer"][0] else {
+    panic!("expected an item value");
+  };
+  assert_eq!(text(&employer.properties["name"][0]), "Engines Ltd");
+  assert_eq!(person.properties["name"].len(), 1);
+}
+
+#[test]
+fn test_outline_anchors_and_heading_order() {
+  let source_code = r#"
+  <html>
+    <body>
+      <h1 id="title">Title</h1>
+      <h3 id="details">Details</h3>
+      <section id="more">
+        <h2>More</h2>
+      </section>
+    </body>
+  </html>"#;
+  let document = dom::Document::new(create_html_root(source_code));
+  let outline = document.outline();
+  let anchors: Vec<(&str, &str)> = outline
+    .anchors
+    .iter()
+    .map(|anchor| (anchor.id.as_str(), anchor.tag_name.as_str()))
+    .collect();
+  assert_eq!(anchors, vec![("title", "h1"), ("details", "h3"), ("more", "section")]);
+  assert_eq!(outline.anchors[0].path, path_of_id(&document.root, "title"));
+  let skipped: Vec<&str> = outline
+    .skipped_heading_levels()
+    .iter()
+    .map(|heading| heading.text.as_str())
+    .collect();
+  assert_eq!(skipped, vec!["Details"]);
+  assert_eq!(outline.root.heading.as_ref().unwrap().text, "Title");
+  // the section follows "Details", so it is nested in that implicit section.
+  let details = &outline.root.children[0];
+  assert_eq!(details.heading.as_ref().unwrap().text, "Details");
+  assert_eq!(details.children[0].kind, dom::SectionKind::Section);
+  assert_eq!(details.children[0].path, path_of_id(&document.root, "more"));
+}
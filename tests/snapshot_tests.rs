@@ -1,12 +1,15 @@
-#![allow(dead_code, unused_imports)]
+#![allow(dead_code, unused_imports, clippy::needless_return)]
 
 use glob::glob;
-use insta::assert_ron_snapshot;
+use insta::{assert_ron_snapshot, assert_snapshot};
 use std::fs;
 use std::path::Path;
+use webcore::accessibility;
 use webcore::css::{self, CSSParser};
 use webcore::dom;
-use webcore::html::HTMLParser;
+use webcore::html::{self, HTMLParser};
+use webcore::layout;
+use webcore::reader;
 
 fn read_test_files_with_pattern(pattern: &str) -> Vec<(String, String)> {
   let mut patterns = Vec::new();
@@ -66,3 +69,126 @@ fn test_html_parser_snapshot() {
     }
   });
 }
+
+#[test]
+fn test_accessibility_tree_snapshot() {
+  let test_files = read_test_files_with_pattern("tests/golden_tests/accessibility/*.html");
+  let stylesheet = create_syle_sheet_parser(".hidden { display: none; }");
+  let settings = setings_snapshot();
+  settings.bind(|| {
+    for (file_name, source_code) in test_files.iter() {
+      println!("running {}...", file_name);
+      let html_root = create_html_parser(source_code);
+      let styled_root = css::style_tree(&html_root.children[0], &stylesheet);
+      let tree = accessibility::accessibility_tree(&styled_root);
+      let file_name = format_file_name_with_module(file_name, "accessibility", ".html");
+      assert_ron_snapshot!(file_name.clone(), tree);
+    }
+  });
+}
+
+// load the snapshot body (after the `---` header) back into its type.
+fn read_snapshot<T: serde::de::DeserializeOwned>(snapshot_name: &str) -> T {
+  let path = format!("tests/snapshots/{}.snap", snapshot_name);
+  let content = fs::read_to_string(path).expect("Failed to read snapshot");
+  let body = content.splitn(3, "---").nth(2).expect("Invalid snapshot header");
+  ron::from_str(body).expect("Failed to deserialize snapshot")
+}
+
+#[test]
+fn test_deserialize_snapshots() {
+  for (file_name, source_code) in read_test_files_with_pattern("tests/golden_tests/html/*.html") {
+    let snapshot_name = format_file_name_with_module(&file_name, "html_parser", ".html");
+    let html_root: dom::HtmlRoot = read_snapshot(&snapshot_name);
+    let expected = create_html_parser(&source_code);
+    assert_eq!(ron::to_string(&html_root).unwrap(), ron::to_string(&expected).unwrap());
+  }
+  for (file_name, source_code) in read_test_files_with_pattern("tests/golden_tests/css/*.css") {
+    let snapshot_name = format_file_name_with_module(&file_name, "css_parser", ".css");
+    let stylesheet: css::StyleSheet = read_snapshot(&snapshot_name);
+    let expected = create_syle_sheet_parser(&source_code);
+    assert_eq!(ron::to_string(&stylesheet).unwrap(), ron::to_string(&expected).unwrap());
+  }
+}
+
+#[test]
+fn test_dom_dump_snapshot() {
+  let test_files = read_test_files_with_pattern("tests/golden_tests/html/*.html");
+  let settings = setings_snapshot();
+  settings.bind(|| {
+    for (file_name, source_code) in test_files.iter() {
+      println!("running {}...", file_name);
+      let html_root = create_html_parser(source_code);
+      let file_name = format_file_name_with_module(file_name, "dom_dump", ".html");
+      assert_snapshot!(file_name.clone(), dom::dump_dom(&html_root));
+    }
+  });
+}
+
+#[test]
+fn test_style_and_layout_dump_snapshot() {
+  let html_root = create_html_parser(r#"<div class="box"><p id="first">Hello</p><p>World</p></div>"#);
+  let stylesheet =
+    create_syle_sheet_parser("div { display: block; } p { display: block; height: 20px; } .box { color: red; }");
+  let styled_root = css::style_tree(&html_root.children[0], &stylesheet);
+  let mut viewport = layout::Dimensions::default();
+  viewport.content.width = 800.0;
+  let layout_root = layout::layout_tree(&styled_root, viewport).unwrap();
+  let settings = setings_snapshot();
+  settings.bind(|| {
+    assert_snapshot!("style_dump", css::dump_style_tree(&styled_root));
+    assert_snapshot!("layout_dump", layout::dump_layout_tree(&layout_root));
+  });
+}
+
+#[test]
+fn test_sanitizer_snapshot() {
+  let test_files = read_test_files_with_pattern("tests/golden_tests/sanitizer/*.html");
+  let mut sanitizer = html::Sanitizer::new();
+  sanitizer.allow_attribute("*", "style");
+  let settings = setings_snapshot();
+  settings.bind(|| {
+    for (file_name, source_code) in test_files.iter() {
+      println!("running {}...", file_name);
+      let html_root = create_html_parser(source_code);
+      let file_name = format_file_name_with_module(file_name, "sanitizer", ".html");
+      assert_snapshot!(file_name.clone(), sanitizer.sanitize_to_html(&html_root.children));
+    }
+  });
+}
+
+#[test]
+fn test_reader_snapshot() {
+  let test_files = read_test_files_with_pattern("tests/golden_tests/reader/*.html");
+  let settings = setings_snapshot();
+  settings.bind(|| {
+    for (file_name, source_code) in test_files.iter() {
+      println!("running {}...", file_name);
+      let document = dom::Document::new(create_html_parser(source_code));
+      let article = reader::extract_article(&document).expect("no article found");
+      let summary = format!(
+        "title: {}\nbyline: {:?}\nexcerpt: {:?}\n\n{}",
+        article.title,
+        article.byline,
+        article.excerpt,
+        html::serialize_nodes(&[article.content])
+      );
+      let file_name = format_file_name_with_module(file_name, "reader", ".html");
+      assert_snapshot!(file_name.clone(), summary);
+    }
+  });
+}
+
+#[test]
+fn test_outline_snapshot() {
+  let test_files = read_test_files_with_pattern("tests/golden_tests/outline/*.html");
+  let settings = setings_snapshot();
+  settings.bind(|| {
+    for (file_name, source_code) in test_files.iter() {
+      println!("running {}...", file_name);
+      let outline = dom::outline(&create_html_parser(source_code));
+      let file_name = format_file_name_with_module(file_name, "outline", ".html");
+      assert_snapshot!(file_name.clone(), dom::dump_outline(&outline));
+    }
+  });
+}